@@ -77,6 +77,18 @@ export default defineConfig({
             text: "Ollama",
             link: "/features/ollama"
           },
+          {
+            text: "MCP Servers",
+            link: "/features/mcp"
+          },
+          {
+            text: "Browser Agent",
+            link: "/features/browser-agent"
+          },
+          {
+            text: "Code Interpreter",
+            link: "/features/code-interpreter"
+          },
           {
             text: "Other",
             link: "/features/other"
@@ -26,16 +26,30 @@ const formatChatHistoryAsString = (history: BaseMessage[]) => {
     .join("\n")
 }
 
+export const getUniqueDocs = (docs: Document[]) => {
+  return docs.filter(
+    (doc, i, self) =>
+      self.findIndex((d) => d.pageContent === doc.pageContent) === i
+  )
+}
+
 export const formatDocs = (docs: Document[]) => {
-  return docs
-    .filter(
-      (doc, i, self) =>
-        self.findIndex((d) => d.pageContent === doc.pageContent) === i
-    )
-    .map((doc, i) => `<doc id='${i}'>${doc.pageContent}</doc>`)
+  return getUniqueDocs(docs)
+    .map((doc, i) => `<doc id='${i + 1}'>${doc.pageContent}</doc>`)
     .join("\n")
 }
 
+const CITATION_INSTRUCTION = `When you use information from a document, cite it with its id in square brackets right after the statement, for example [1] or [1, 3]. Only cite ids that appear above.`
+
+/**
+ * Same as `formatDocs`, followed by an instruction asking the model to cite
+ * the documents it uses. Ids start at 1 and follow the order of the unique
+ * documents, so `getUniqueDocs(docs)[n - 1]` is the source of `[n]`.
+ */
+export const formatDocsWithCitations = (docs: Document[]) => {
+  return `${formatDocs(docs)}\n\n${CITATION_INSTRUCTION}`
+}
+
 const serializeHistory = (input: any) => {
   const chatHistory = input.chat_history || []
   const convertedChatHistory = []
@@ -1,3 +1,10 @@
+import {
+  CODE_OUTPUT_INSERT,
+  formatCodeResult,
+  getCodeLanguage,
+  runCode,
+  type CodeResult
+} from "@/libs/code-interpreter"
 import { programmingLanguages } from "@/utils/langauge-extension"
 import { Tooltip } from "antd"
 import {
@@ -5,8 +12,12 @@ import {
   CopyIcon,
   DownloadIcon,
   EyeIcon,
-  CodeIcon
+  CodeIcon,
+  Loader2,
+  PlayIcon,
+  XIcon
 } from "lucide-react"
+import PubSub from "pubsub-js"
 import { FC, useState, useRef, useEffect, useCallback } from "react"
 import { useTranslation } from "react-i18next"
 import { Prism as SyntaxHighlighter } from "react-syntax-highlighter"
@@ -51,6 +62,24 @@ export const CodeBlock: FC<Props> = ({ language, value }) => {
     () => globalStateMap.get(keyRef.current) || false
   )
   const { t } = useTranslation("common")
+  const runLanguage = getCodeLanguage(language)
+  const [isRunning, setIsRunning] = useState(false)
+  const [output, setOutput] = useState<CodeResult | null>(null)
+
+  const handleRun = async () => {
+    setIsRunning(true)
+    try {
+      setOutput(await runCode(runLanguage!, value))
+    } catch (e) {
+      setOutput({
+        stdout: "",
+        error: e instanceof Error ? e.message : String(e),
+        images: []
+      })
+    } finally {
+      setIsRunning(false)
+    }
+  }
 
   const handleCopy = () => {
     navigator.clipboard.writeText(value)
@@ -175,6 +204,20 @@ export const CodeBlock: FC<Props> = ({ language, value }) => {
           </div>
           <div className="sticky top-9 md:top-[5.75rem]">
             <div className="absolute bottom-0 right-2 flex h-9 items-center gap-1">
+              {runLanguage && (
+                <Tooltip title={t("runCode")}>
+                  <button
+                    onClick={handleRun}
+                    disabled={isRunning}
+                    className="flex gap-1.5 items-center rounded bg-none p-1 text-xs text-gray-200 hover:bg-gray-700 hover:text-gray-100 focus:outline-none">
+                    {isRunning ? (
+                      <Loader2 className="size-4 animate-spin" />
+                    ) : (
+                      <PlayIcon className="size-4" />
+                    )}
+                  </button>
+                </Tooltip>
+              )}
               <Tooltip title={t("downloadCode")}>
                 <button
                   onClick={handleDownload}
@@ -229,6 +272,65 @@ export const CodeBlock: FC<Props> = ({ language, value }) => {
               />
             </div>
           )}
+          {output && (
+            <div className="border-t border-gray-800 px-4 py-3 text-gray-200">
+              <div className="flex items-center justify-between mb-2">
+                <span className="font-mono text-xs text-gray-400">
+                  {t("codeOutput.title")}
+                </span>
+                <div className="flex items-center gap-1">
+                  <button
+                    onClick={() =>
+                      PubSub.publish(
+                        CODE_OUTPUT_INSERT,
+                        formatCodeResult(output)
+                      )
+                    }
+                    className="rounded px-2 py-0.5 text-xs hover:bg-gray-700">
+                    {t("codeOutput.addToChat")}
+                  </button>
+                  <Tooltip title={t("codeOutput.close")}>
+                    <button
+                      onClick={() => setOutput(null)}
+                      className="rounded p-1 hover:bg-gray-700">
+                      <XIcon className="size-3" />
+                    </button>
+                  </Tooltip>
+                </div>
+              </div>
+              {output.stdout && (
+                <pre className="whitespace-pre-wrap break-all font-mono text-xs max-h-64 overflow-y-auto">
+                  {output.stdout}
+                </pre>
+              )}
+              {output.result !== undefined && (
+                <pre className="whitespace-pre-wrap break-all font-mono text-xs text-green-400">
+                  {output.result}
+                </pre>
+              )}
+              {output.error && (
+                <pre className="whitespace-pre-wrap break-all font-mono text-xs text-red-400 max-h-64 overflow-y-auto">
+                  {output.error}
+                </pre>
+              )}
+              {output.images.map((image, index) => (
+                <img
+                  key={index}
+                  src={image}
+                  alt={`Chart ${index + 1}`}
+                  className="mt-2 max-w-full rounded-md bg-white"
+                />
+              ))}
+              {!output.stdout &&
+                output.result === undefined &&
+                !output.error &&
+                output.images.length === 0 && (
+                  <span className="text-xs text-gray-400">
+                    {t("codeOutput.empty")}
+                  </span>
+                )}
+            </div>
+          )}
         </div>
       </div>
     </>
@@ -10,20 +10,31 @@ import React from "react"
 import { CodeBlock } from "./CodeBlock"
 import { TableBlock } from "./TableBlock"
 import { preprocessLaTeX } from "@/utils/latex"
+import { getCitationFromHref, linkCitations } from "@/utils/citation"
 import { useStorage } from "@plasmohq/storage/hook"
+import { Tooltip } from "antd"
 
 function Markdown({
   message,
-  className = "prose break-words dark:prose-invert prose-p:leading-relaxed prose-pre:p-0 dark:prose-dark"
+  className = "prose break-words dark:prose-invert prose-p:leading-relaxed prose-pre:p-0 dark:prose-dark",
+  sources,
+  onSourceClick
 }: {
   message: string
   className?: string
+  sources?: any[]
+  onSourceClick?: (source: any) => void
 }) {
   const [checkWideMode] = useStorage("checkWideMode", false)
   if (checkWideMode) {
     className += " max-w-none"
   }
   message = preprocessLaTeX(message)
+  const citedSources = sources?.filter((source) => source?.citation) || []
+  message = linkCitations(
+    message,
+    citedSources.map((source) => source.citation)
+  )
   return (
     <React.Fragment>
       <ReactMarkdown
@@ -48,6 +59,20 @@ function Markdown({
             )
           },
           a({ node, ...props }) {
+            const citation = getCitationFromHref(props.href)
+            if (citation) {
+              const source = citedSources.find((s) => s.citation === citation)
+              return (
+                <Tooltip title={source?.name}>
+                  <button
+                    type="button"
+                    onClick={() => onSourceClick && onSourceClick(source)}
+                    className="mx-0.5 inline-flex items-center justify-center align-super rounded bg-gray-100 px-1 text-[10px] font-semibold leading-4 text-gray-700 no-underline hover:bg-gray-200 dark:bg-[#2a2a2a] dark:text-gray-200 dark:hover:bg-[#404040]">
+                    {citation}
+                  </button>
+                </Tooltip>
+              )
+            }
             return (
               <a
                 target="_blank"
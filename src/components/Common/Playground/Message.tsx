@@ -27,6 +27,8 @@ import { PlaygroundUserMessageBubble } from "./PlaygroundUserMessage"
 import { copyToClipboard } from "@/utils/clipboard"
 import { ChatDocuments } from "@/models/ChatTypes"
 import { PiGitBranch } from "react-icons/pi"
+import type { ToolCallStep } from "@/tools/types"
+import { ToolCalls } from "./ToolCalls"
 
 type Props = {
   message: string
@@ -60,6 +62,7 @@ type Props = {
   actionInfo?: string | null
   onNewBranch?: () => void
   temporaryChat?: boolean
+  toolCalls?: ToolCallStep[]
 }
 
 export const PlaygroundMessage = (props: Props) => {
@@ -198,6 +201,9 @@ export const PlaygroundMessage = (props: Props) => {
             {!editMode ? (
               props.isBot ? (
                 <>
+                  {props.toolCalls && props.toolCalls.length > 0 && (
+                    <ToolCalls toolCalls={props.toolCalls} />
+                  )}
                   {parseReasoning(props.message).map((e, i) => {
                     if (e.type === "reasoning") {
                       return (
@@ -231,7 +237,14 @@ export const PlaygroundMessage = (props: Props) => {
                       )
                     }
 
-                    return <Markdown key={i} message={e.content} />
+                    return (
+                      <Markdown
+                        key={i}
+                        message={e.content}
+                        sources={props.sources}
+                        onSourceClick={props.onSourceClick}
+                      />
+                    )
                   })}
                 </>
               ) : (
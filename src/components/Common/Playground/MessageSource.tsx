@@ -8,6 +8,7 @@ type Props = {
     type?: string
     pageContent?: string
     content?: string
+    citation?: number
   }
   onSourceClick?: (source: any) => void
 }
@@ -20,6 +21,9 @@ export const MessageSource: React.FC<Props> = ({ source, onSourceClick }) => {
           onSourceClick && onSourceClick(source)
         }}
         className="inline-flex gap-2   cursor-pointer transition-shadow duration-300 ease-in-out hover:shadow-lg  items-center rounded-md bg-gray-100 p-1 text-xs text-gray-800 border border-gray-300 dark:bg-[#2a2a2a] dark:border-[#404040] dark:text-gray-100 opacity-80 hover:opacity-100">
+        {source?.citation && (
+          <span className="text-xs font-semibold">{source.citation}</span>
+        )}
         <KnowledgeIcon type={source.type} className="h-3 w-3" />
         <span className="text-xs">{source.name}</span>
       </button>
@@ -1,5 +1,7 @@
 import { KnowledgeIcon } from "@/components/Option/Knowledge/KnowledgeIcon"
+import { getSourceLocation } from "@/utils/citation"
 import { Modal } from "antd"
+import { useTranslation } from "react-i18next"
 
 type Props = {
   source: any
@@ -12,6 +14,9 @@ export const MessageSourcePopup: React.FC<Props> = ({
   open,
   setOpen
 }) => {
+  const { t } = useTranslation("common")
+  const location = getSourceLocation(source?.metadata)
+
   return (
     <Modal
       open={open}
@@ -22,29 +27,39 @@ export const MessageSourcePopup: React.FC<Props> = ({
       onOk={() => setOpen(false)}>
       <div className="flex flex-col gap-2 mt-6">
         <h4 className="bg-gray-100 text-md dark:bg-gray-800 inline-flex gap-2 items-center text-gray-800 dark:text-gray-100 font-semibold p-2">
+          {source?.citation && <span>[{source.citation}]</span>}
           {source?.type && (
             <KnowledgeIcon type={source?.type} className="h-4 w-5" />
           )}
           {source?.name}
         </h4>
-        {source?.type === "pdf" ? (
-          <>
-            <p className="text-gray-500 text-sm">{source?.pageContent}</p>
+        {location.heading && (
+          <p className="text-gray-600 dark:text-gray-300 text-xs font-medium">
+            {location.heading}
+          </p>
+        )}
+        <p className="text-gray-500 text-sm whitespace-pre-wrap">
+          {source?.pageContent}
+        </p>
 
-            <div className="flex flex-wrap gap-3">
+        {(location.page || location.row || location.lines) && (
+          <div className="flex flex-wrap gap-3">
+            {location.page && (
               <span className="border border-gray-300 dark:border-gray-700 rounded-md p-1 text-gray-500 text-xs">
-                {`Page ${source?.metadata?.page}`}
+                {t("sourceLocation.page", { page: location.page })}
               </span>
-
+            )}
+            {location.row && (
+              <span className="border border-gray-300 dark:border-gray-700 rounded-md p-1 text-gray-500 text-xs">
+                {t("sourceLocation.row", { row: location.row })}
+              </span>
+            )}
+            {location.lines && (
               <span className="border border-gray-300 dark:border-gray-700 rounded-md p-1 text-xs text-gray-500">
-                {`Line ${source?.metadata?.loc?.lines?.from} - ${source?.metadata?.loc?.lines?.to}`}
+                {t("sourceLocation.lines", location.lines)}
               </span>
-            </div>
-          </>
-        ) : (
-          <>
-            <p className="text-gray-500 text-sm">{source?.pageContent}</p>
-          </>
+            )}
+          </div>
         )}
       </div>
     </Modal>
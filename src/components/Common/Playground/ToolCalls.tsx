@@ -0,0 +1,79 @@
+import type { ToolCallStep } from "@/tools/types"
+import { Collapse } from "antd"
+import { CheckIcon, Loader2, WrenchIcon, XIcon } from "lucide-react"
+import { useTranslation } from "react-i18next"
+
+type Props = {
+  toolCalls: ToolCallStep[]
+}
+
+const StatusIcon = ({ status }: { status: ToolCallStep["status"] }) => {
+  switch (status) {
+    case "running":
+      return <Loader2 className="w-4 h-4 animate-spin" />
+    case "error":
+      return <XIcon className="w-4 h-4 text-red-500" />
+    default:
+      return <CheckIcon className="w-4 h-4 text-green-600" />
+  }
+}
+
+export const ToolCalls = ({ toolCalls }: Props) => {
+  const { t } = useTranslation("common")
+  // charts are shown below the steps, not hidden in them
+  const images = toolCalls.flatMap((step) => step.images || [])
+  return (
+    <>
+      <Collapse
+        className="border-none text-gray-500 dark:text-gray-400 !mb-3"
+        size="small"
+        items={toolCalls.map((step) => ({
+          key: step.id,
+          label: (
+            <div className="flex items-center gap-2">
+              <WrenchIcon className="w-4 h-4" />
+              <span
+                className={step.status === "running" ? "shimmer-text" : ""}>
+                {t(`toolCalls.${step.status}`, { name: step.name })}
+              </span>
+              <StatusIcon status={step.status} />
+              {step.summary && (
+                <span className="truncate text-xs">{step.summary}</span>
+              )}
+            </div>
+          ),
+          children: (
+            <div className="flex flex-col gap-2 text-xs">
+              <div>
+                <div className="font-semibold">{t("toolCalls.arguments")}</div>
+                <pre className="whitespace-pre-wrap break-all">
+                  {JSON.stringify(step.args, null, 2)}
+                </pre>
+              </div>
+              {(step.result || step.error) && (
+                <div>
+                  <div className="font-semibold">{t("toolCalls.result")}</div>
+                  <pre className="whitespace-pre-wrap break-all max-h-64 overflow-y-auto">
+                    {step.error || step.result}
+                  </pre>
+                </div>
+              )}
+            </div>
+          )
+        }))}
+      />
+      {images.length > 0 && (
+        <div className="flex flex-col gap-2 mb-3">
+          {images.map((image, index) => (
+            <img
+              key={index}
+              src={image}
+              alt={`Chart ${index + 1}`}
+              className="max-w-full rounded-md bg-white"
+            />
+          ))}
+        </div>
+      )}
+    </>
+  )
+}
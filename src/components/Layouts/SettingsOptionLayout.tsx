@@ -7,7 +7,8 @@ import {
   InfoIcon,
   CombineIcon,
   ChromeIcon,
-  CpuIcon
+  CpuIcon,
+  WrenchIcon
 } from "lucide-react"
 import { useTranslation } from "react-i18next"
 import { Link, useLocation } from "react-router-dom"
@@ -40,6 +41,12 @@ export const SettingsLayout = ({ children }: { children: React.ReactNode }) => {
                     icon={CombineIcon}
                     current={location.pathname}
                   />
+                  <LinkComponent
+                    href="/settings/tools"
+                    name={t("tools.title")}
+                    icon={WrenchIcon}
+                    current={location.pathname}
+                  />
                   <LinkComponent
                     href="/settings/ollama"
                     name={t("ollamaSettings.title")}
@@ -10,7 +10,9 @@ import PubSub from "pubsub-js"
 import { KNOWLEDGE_QUEUE } from "@/queue"
 import { useStorage } from "@plasmohq/storage/hook"
 import { unsupportedTypes } from "./utils/unsupported-types"
+import { getOfficeDocumentType, officeMimeTypes } from "@/loader/office"
 import React from "react"
+import { CrawlSourceFields, crawlSourceFromForm } from "./CrawlSourceFields"
 
 type Props = {
   open: boolean
@@ -21,7 +23,9 @@ export const AddKnowledge = ({ open, setOpen }: Props) => {
   const { t } = useTranslation(["knowledge", "common"])
   const [form] = Form.useForm()
   const [totalFilePerKB] = useStorage("totalFilePerKB", 5)
-  const [mode, setMode] = React.useState<"upload" | "text">("upload")
+  const [mode, setMode] = React.useState<"upload" | "text" | "crawl">(
+    "upload"
+  )
 
   const onUploadHandler = async (data: any) => {
     const defaultEM = await defaultEmbeddingModelForRag()
@@ -37,18 +41,21 @@ export const AddKnowledge = ({ open, setOpen }: Props) => {
       "text/csv",
       "text/plain",
       "text/markdown",
-      "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+      "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+      ...officeMimeTypes
     ]
 
     if (mode === "upload") {
       for (const file of data.file || []) {
         let mime = file.type
         if (!allowedTypes.includes(mime)) {
-          mime = "text/plain"
+          mime = getOfficeDocumentType(file.name) || "text/plain"
         }
         const _src = await convertToSource({ file, mime, sourceType: "file_upload" })
         source.push(_src)
       }
+    } else if (mode === "crawl") {
+      source.push(crawlSourceFromForm(data))
     } else {
       // Text mode validation
       const rawText: string = (data?.textContent || "").trim()
@@ -80,6 +87,8 @@ export const AddKnowledge = ({ open, setOpen }: Props) => {
       if (mode === "text") {
         const text = (data?.textContent || "").trim()
         title = text.substring(0, 50) || t("form.textInput.defaultTitle")
+      } else if (mode === "crawl") {
+        title = new URL(data.crawlUrl.trim()).hostname
       } else if ((data?.file || []).length > 0) {
         title = (data.file[0]?.name as string) || t("form.textInput.defaultTitle")
       } else {
@@ -120,7 +129,8 @@ export const AddKnowledge = ({ open, setOpen }: Props) => {
         onChange={(key) => setMode(key as any)}
         items={[
           { key: "upload", label: t("form.tabs.upload") },
-          { key: "text", label: t("form.tabs.text") }
+          { key: "text", label: t("form.tabs.text") },
+          { key: "crawl", label: t("form.tabs.crawl") }
         ]}
       />
       <Form onFinish={saveKnowledge} form={form} layout="vertical">
@@ -154,7 +164,8 @@ export const AddKnowledge = ({ open, setOpen }: Props) => {
                   "text/csv",
                   "text/plain",
                   "text/markdown",
-                  "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+                  "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                  ...officeMimeTypes
                 ]
                   .map((type) => type.toLowerCase())
                   .join(", ")
@@ -178,6 +189,8 @@ export const AddKnowledge = ({ open, setOpen }: Props) => {
               </div>
             </Upload.Dragger>
           </Form.Item>
+        ) : mode === "crawl" ? (
+          <CrawlSourceFields />
         ) : (
           <>
             <Form.Item
@@ -0,0 +1,58 @@
+import { Form, Input, InputNumber, Switch } from "antd"
+import { useTranslation } from "react-i18next"
+import type { Source } from "@/db/dexie/types"
+import { createCrawlSource, DEFAULT_CRAWL_OPTIONS } from "@/libs/crawl-website"
+
+export const crawlSourceFromForm = (data: any): Source => {
+  return createCrawlSource(data.crawlUrl.trim(), {
+    maxDepth: data.crawlMaxDepth ?? DEFAULT_CRAWL_OPTIONS.maxDepth,
+    maxPages: data.crawlMaxPages ?? DEFAULT_CRAWL_OPTIONS.maxPages,
+    sameOrigin: data.crawlSameOrigin ?? DEFAULT_CRAWL_OPTIONS.sameOrigin,
+    pathPrefix: data.crawlPathPrefix?.trim() || undefined
+  })
+}
+
+export const CrawlSourceFields = () => {
+  const { t } = useTranslation("knowledge")
+
+  return (
+    <>
+      <Form.Item
+        name="crawlUrl"
+        label={t("form.crawl.url.label")}
+        rules={[
+          { required: true, message: t("form.crawl.url.required") },
+          { type: "url", message: t("form.crawl.url.invalid") }
+        ]}>
+        <Input size="large" placeholder="https://example.com/docs" />
+      </Form.Item>
+      <Form.Item
+        name="crawlMaxDepth"
+        label={t("form.crawl.maxDepth.label")}
+        help={t("form.crawl.maxDepth.help")}
+        initialValue={DEFAULT_CRAWL_OPTIONS.maxDepth}>
+        <InputNumber style={{ width: "100%" }} min={0} max={5} />
+      </Form.Item>
+      <Form.Item
+        name="crawlMaxPages"
+        label={t("form.crawl.maxPages.label")}
+        initialValue={DEFAULT_CRAWL_OPTIONS.maxPages}>
+        <InputNumber style={{ width: "100%" }} min={1} max={500} />
+      </Form.Item>
+      <Form.Item
+        name="crawlPathPrefix"
+        label={t("form.crawl.pathPrefix.label")}
+        help={t("form.crawl.pathPrefix.help")}>
+        <Input placeholder="/docs" />
+      </Form.Item>
+      <Form.Item
+        name="crawlSameOrigin"
+        label={t("form.crawl.sameOrigin.label")}
+        help={t("form.crawl.sameOrigin.help")}
+        valuePropName="checked"
+        initialValue={DEFAULT_CRAWL_OPTIONS.sameOrigin}>
+        <Switch />
+      </Form.Item>
+    </>
+  )
+}
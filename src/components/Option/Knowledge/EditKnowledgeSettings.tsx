@@ -1,9 +1,26 @@
-import { getKnowledgeById, updateKnowledgebase } from "@/db/dexie/knowledge"
+import {
+  DEFAULT_RETRIEVAL_MODE,
+  getKnowledgeById,
+  updateKnowledgebase
+} from "@/db/dexie/knowledge"
 import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
-import { Alert, Button, Form, Input, Modal, Skeleton, message } from "antd"
+import {
+  Alert,
+  Button,
+  Form,
+  Input,
+  InputNumber,
+  Modal,
+  Select,
+  Skeleton,
+  Switch,
+  message
+} from "antd"
 import { Loader2 } from "lucide-react"
+import PubSub from "pubsub-js"
 import React from "react"
 import { useTranslation } from "react-i18next"
+import { KNOWLEDGE_QUEUE } from "@/queue"
 
 const DEFAULT_RAG_QUESTION_PROMPT =
   "Given the following conversation and a follow up question, rephrase the follow up question to be a standalone question.   Chat History: {chat_history} Follow Up Input: {question} Standalone question:"
@@ -33,7 +50,11 @@ export const EditKnowledgeSettings: React.FC<Props> = ({
         form.setFieldsValue({
           title: data.title,
           systemPrompt: data.systemPrompt || "",
-          followupPrompt: data.followupPrompt || ""
+          followupPrompt: data.followupPrompt || "",
+          retrievalMode: data.retrievalMode || DEFAULT_RETRIEVAL_MODE,
+          parentChunkSize: data.parentChunkSize || 0,
+          refreshInterval: data.refresh?.interval || 0,
+          ocr: data.ocr !== false
         })
       }
       return data
@@ -44,7 +65,11 @@ export const EditKnowledgeSettings: React.FC<Props> = ({
 
   const { mutate, isPending } = useMutation({
     mutationFn: updateKnowledgebase,
-    onSuccess: async () => {
+    onSuccess: async (reindex) => {
+      // sources are split again with the new passage size or OCR setting
+      if (reindex) {
+        PubSub.publish(KNOWLEDGE_QUEUE, id)
+      }
       await queryClient.invalidateQueries({
         queryKey: ["fetchAllKnowledge"]
       })
@@ -64,7 +89,11 @@ export const EditKnowledgeSettings: React.FC<Props> = ({
       id,
       title: values.title,
       systemPrompt: values.systemPrompt,
-      followupPrompt: values.followupPrompt
+      followupPrompt: values.followupPrompt,
+      retrievalMode: values.retrievalMode,
+      parentChunkSize: values.parentChunkSize || 0,
+      refreshInterval: values.refreshInterval || 0,
+      ocr: values.ocr
     })
   }
 
@@ -95,6 +124,51 @@ export const EditKnowledgeSettings: React.FC<Props> = ({
             />
           </Form.Item>
 
+          <Form.Item
+            name="retrievalMode"
+            label={t("editSettings.form.retrievalMode.label")}
+            help={t("editSettings.form.retrievalMode.help")}>
+            <Select
+              size="large"
+              options={[
+                {
+                  value: "vector",
+                  label: t("editSettings.form.retrievalMode.options.vector")
+                },
+                {
+                  value: "keyword",
+                  label: t("editSettings.form.retrievalMode.options.keyword")
+                },
+                {
+                  value: "hybrid",
+                  label: t("editSettings.form.retrievalMode.options.hybrid")
+                }
+              ]}
+            />
+          </Form.Item>
+
+          <Form.Item
+            name="parentChunkSize"
+            label={t("editSettings.form.parentChunkSize.label")}
+            help={t("editSettings.form.parentChunkSize.help")}>
+            <InputNumber style={{ width: "100%" }} min={0} step={500} />
+          </Form.Item>
+
+          <Form.Item
+            name="refreshInterval"
+            label={t("editSettings.form.refreshInterval.label")}
+            help={t("editSettings.form.refreshInterval.help")}>
+            <InputNumber style={{ width: "100%" }} min={0} max={720} />
+          </Form.Item>
+
+          <Form.Item
+            name="ocr"
+            label={t("editSettings.form.ocr.label")}
+            help={t("editSettings.form.ocr.help")}
+            valuePropName="checked">
+            <Switch />
+          </Form.Item>
+
           <Form.Item
             name="systemPrompt"
             label={
@@ -0,0 +1,449 @@
+import {
+  deleteEvaluationRun,
+  setEvaluationQuestions
+} from "@/db/dexie/knowledge"
+import { generateID } from "@/db/dexie/helpers"
+import type {
+  EvaluationQuestion,
+  EvaluationRun,
+  Knowledge
+} from "@/db/dexie/types"
+import { evaluateKnowledge } from "@/libs/evaluate-knowledge"
+import { useMutation, useQueryClient } from "@tanstack/react-query"
+import {
+  Button,
+  Form,
+  Input,
+  Modal,
+  Progress,
+  Select,
+  Table,
+  Tag,
+  Tooltip,
+  message
+} from "antd"
+import dayjs from "dayjs"
+import { Pencil, Plus, Trash2 } from "lucide-react"
+import React from "react"
+import { useTranslation } from "react-i18next"
+
+type Props = {
+  knowledge: Knowledge
+  selectedModel: string
+}
+
+const formatPercent = (value: number) => `${(value * 100).toFixed(1)}%`
+
+const formatLatency = (value: number) => `${Math.round(value)} ms`
+
+// change against the run before it, runs are stored newest first
+const Delta = ({
+  value,
+  previous,
+  lowerIsBetter = false,
+  format
+}: {
+  value: number
+  previous?: number
+  lowerIsBetter?: boolean
+  format: (value: number) => string
+}) => {
+  if (previous === undefined || value === previous) {
+    return null
+  }
+  const better = lowerIsBetter ? value < previous : value > previous
+  return (
+    <span className={better ? "text-green-600" : "text-red-500"}>
+      {" "}
+      ({value > previous ? "+" : "-"}
+      {format(Math.abs(value - previous))})
+    </span>
+  )
+}
+
+export const KnowledgeEvaluation = ({ knowledge, selectedModel }: Props) => {
+  const { t } = useTranslation(["knowledge", "common"])
+  const queryClient = useQueryClient()
+  const [form] = Form.useForm()
+  const [editing, setEditing] = React.useState<EvaluationQuestion | null>(
+    null
+  )
+  const [progress, setProgress] = React.useState<number | null>(null)
+
+  const questions = knowledge.evaluation?.questions || []
+  const runs = knowledge.evaluation?.runs || []
+
+  const sourceNames = new Map(
+    knowledge.source.map((source) => [
+      source.source_id,
+      source.filename || source.source_id
+    ])
+  )
+  const questionText = new Map(
+    questions.map((question) => [question.id, question.question])
+  )
+
+  const refresh = () =>
+    queryClient.invalidateQueries({
+      queryKey: ["fetchKnowledgeById", knowledge.id]
+    })
+
+  const { mutate: saveQuestions } = useMutation({
+    mutationFn: (questions: EvaluationQuestion[]) =>
+      setEvaluationQuestions(knowledge.id, questions),
+    onSuccess: refresh,
+    onError: (error) => {
+      message.error(error.message)
+    }
+  })
+
+  const { mutate: runEvaluation, isPending: isRunning } = useMutation({
+    mutationFn: () => {
+      setProgress(0)
+      return evaluateKnowledge({
+        id: knowledge.id,
+        selectedModel,
+        onProgress: (done, total) => setProgress((done / total) * 100)
+      })
+    },
+    onSuccess: async () => {
+      await refresh()
+      message.success(t("evaluation.finished"))
+    },
+    onError: (error) => {
+      message.error(error.message)
+    },
+    onSettled: () => setProgress(null)
+  })
+
+  const { mutate: removeRun } = useMutation({
+    mutationFn: (runId: string) => deleteEvaluationRun(knowledge.id, runId),
+    onSuccess: refresh
+  })
+
+  const openEditor = (question?: EvaluationQuestion) => {
+    const value = question || { id: "", question: "", expectedSources: [] }
+    setEditing(value)
+    form.setFieldsValue(value)
+  }
+
+  const onSave = (values: Omit<EvaluationQuestion, "id">) => {
+    if (editing?.id) {
+      saveQuestions(
+        questions.map((question) =>
+          question.id === editing.id ? { ...question, ...values } : question
+        )
+      )
+    } else {
+      saveQuestions([...questions, { id: generateID(), ...values }])
+    }
+    setEditing(null)
+  }
+
+  return (
+    <div className="flex flex-col gap-6">
+      <div className="flex flex-col gap-3">
+        <div className="flex items-center justify-between gap-3">
+          <p className="text-sm text-gray-500 dark:text-gray-400">
+            {t("evaluation.help")}
+          </p>
+          <div className="flex gap-2">
+            <Button
+              icon={<Plus className="w-4 h-4" />}
+              onClick={() => openEditor()}>
+              {t("evaluation.addQuestion")}
+            </Button>
+            <Button
+              type="primary"
+              loading={isRunning}
+              disabled={questions.length === 0}
+              onClick={() => runEvaluation()}>
+              {t("evaluation.run")}
+            </Button>
+          </div>
+        </div>
+        {progress !== null && (
+          <Progress percent={Math.round(progress)} size="small" />
+        )}
+        <Table
+          rowKey="id"
+          dataSource={questions}
+          pagination={false}
+          columns={[
+            {
+              title: t("evaluation.question"),
+              dataIndex: "question",
+              key: "question"
+            },
+            {
+              title: t("evaluation.expectedSources"),
+              key: "expectedSources",
+              render: (_, question: EvaluationQuestion) => (
+                <div className="flex flex-wrap gap-1">
+                  {question.expectedSources.map((sourceId) => (
+                    <Tag key={sourceId}>
+                      {sourceNames.get(sourceId) ||
+                        t("evaluation.removedSource")}
+                    </Tag>
+                  ))}
+                </div>
+              )
+            },
+            {
+              title: t("columns.action"),
+              key: "action",
+              width: 100,
+              render: (_, question: EvaluationQuestion) => (
+                <div className="flex gap-4">
+                  <Tooltip title={t("evaluation.editQuestion")}>
+                    <button
+                      onClick={() => openEditor(question)}
+                      className="text-gray-700 dark:text-gray-400">
+                      <Pencil className="w-5 h-5" />
+                    </button>
+                  </Tooltip>
+                  <Tooltip title={t("common:delete")}>
+                    <button
+                      onClick={() =>
+                        saveQuestions(
+                          questions.filter((q) => q.id !== question.id)
+                        )
+                      }
+                      className="text-red-500 dark:text-red-400">
+                      <Trash2 className="w-5 h-5" />
+                    </button>
+                  </Tooltip>
+                </div>
+              )
+            }
+          ]}
+          locale={{
+            emptyText: t("evaluation.noQuestions")
+          }}
+          bordered
+        />
+      </div>
+
+      <div className="flex flex-col gap-3">
+        <h3 className="text-sm font-semibold text-gray-900 dark:text-white">
+          {t("evaluation.runs")}
+        </h3>
+        <Table
+          rowKey="id"
+          dataSource={runs}
+          pagination={false}
+          expandable={{
+            expandedRowRender: (run: EvaluationRun) => (
+              <Table
+                rowKey="questionId"
+                dataSource={run.results}
+                pagination={false}
+                size="small"
+                columns={[
+                  {
+                    title: t("evaluation.question"),
+                    key: "question",
+                    render: (_, result) =>
+                      questionText.get(result.questionId) ||
+                      t("evaluation.removedQuestion")
+                  },
+                  {
+                    title: t("evaluation.rank"),
+                    key: "rank",
+                    width: 100,
+                    render: (_, result) =>
+                      result.error ? (
+                        <Tooltip title={result.error}>
+                          <Tag color="red">{t("evaluation.error")}</Tag>
+                        </Tooltip>
+                      ) : result.rank ? (
+                        <Tag color="green">{result.rank}</Tag>
+                      ) : (
+                        <Tag>{t("evaluation.miss")}</Tag>
+                      )
+                  },
+                  {
+                    title: t("evaluation.latency"),
+                    key: "latency",
+                    width: 100,
+                    render: (_, result) => formatLatency(result.latency)
+                  },
+                  {
+                    title: t("evaluation.retrieved"),
+                    key: "retrieved",
+                    render: (_, result) => (
+                      <div className="flex flex-wrap gap-1">
+                        {result.retrieved.map((source, i) => (
+                          <Tag key={i}>{source || "untitled"}</Tag>
+                        ))}
+                      </div>
+                    )
+                  }
+                ]}
+              />
+            )
+          }}
+          columns={[
+            {
+              title: t("evaluation.date"),
+              key: "createdAt",
+              render: (_, run: EvaluationRun) =>
+                dayjs(run.createdAt).format("YYYY-MM-DD HH:mm")
+            },
+            {
+              title: t("evaluation.settings"),
+              key: "settings",
+              render: (_, run: EvaluationRun) => (
+                <div className="flex flex-wrap gap-1">
+                  <Tag>{run.settings.embedding_model}</Tag>
+                  <Tag>{run.settings.retrievalMode}</Tag>
+                  {run.settings.chunkSize !== undefined && (
+                    <Tag>
+                      {t("evaluation.chunk", {
+                        size: run.settings.chunkSize,
+                        overlap: run.settings.chunkOverlap
+                      })}
+                    </Tag>
+                  )}
+                  {run.settings.parentChunkSize && (
+                    <Tag>
+                      {t("evaluation.parent", {
+                        size: run.settings.parentChunkSize
+                      })}
+                    </Tag>
+                  )}
+                  {run.settings.reranker !== "none" && (
+                    <Tag>
+                      {t("evaluation.reranker", {
+                        type: run.settings.reranker
+                      })}
+                    </Tag>
+                  )}
+                  {run.settings.multiQueryCount > 0 && (
+                    <Tag>
+                      {t("evaluation.multiQuery", {
+                        count: run.settings.multiQueryCount
+                      })}
+                    </Tag>
+                  )}
+                </div>
+              )
+            },
+            {
+              title: "hit@k",
+              key: "hitAtK",
+              render: (_, run: EvaluationRun, i) => (
+                <span>
+                  {formatPercent(run.hitAtK)}
+                  <span className="text-gray-500"> (k={run.settings.k})</span>
+                  <Delta
+                    value={run.hitAtK}
+                    previous={runs[i + 1]?.hitAtK}
+                    format={formatPercent}
+                  />
+                </span>
+              )
+            },
+            {
+              title: "MRR",
+              key: "mrr",
+              render: (_, run: EvaluationRun, i) => (
+                <span>
+                  {run.mrr.toFixed(3)}
+                  <Delta
+                    value={run.mrr}
+                    previous={runs[i + 1]?.mrr}
+                    format={(value) => value.toFixed(3)}
+                  />
+                </span>
+              )
+            },
+            {
+              title: t("evaluation.latency"),
+              key: "latency",
+              render: (_, run: EvaluationRun, i) => (
+                <Tooltip
+                  title={t("evaluation.p95", {
+                    latency: formatLatency(run.p95Latency)
+                  })}>
+                  <span>
+                    {formatLatency(run.avgLatency)}
+                    <Delta
+                      value={run.avgLatency}
+                      previous={runs[i + 1]?.avgLatency}
+                      lowerIsBetter
+                      format={formatLatency}
+                    />
+                  </span>
+                </Tooltip>
+              )
+            },
+            {
+              title: t("columns.action"),
+              key: "action",
+              width: 80,
+              render: (_, run: EvaluationRun) => (
+                <Tooltip title={t("common:delete")}>
+                  <button
+                    onClick={() => removeRun(run.id)}
+                    className="text-red-500 dark:text-red-400">
+                    <Trash2 className="w-5 h-5" />
+                  </button>
+                </Tooltip>
+              )
+            }
+          ]}
+          locale={{
+            emptyText: t("evaluation.noRuns")
+          }}
+          bordered
+        />
+      </div>
+
+      <Modal
+        title={
+          editing?.id
+            ? t("evaluation.editQuestion")
+            : t("evaluation.addQuestion")
+        }
+        open={!!editing}
+        onCancel={() => setEditing(null)}
+        footer={null}>
+        <Form form={form} layout="vertical" onFinish={onSave}>
+          <Form.Item
+            name="question"
+            label={t("evaluation.question")}
+            rules={[
+              {
+                required: true,
+                message: t("evaluation.form.question.required")
+              }
+            ]}>
+            <Input.TextArea autoSize={{ minRows: 2, maxRows: 6 }} />
+          </Form.Item>
+          <Form.Item
+            name="expectedSources"
+            label={t("evaluation.expectedSources")}
+            help={t("evaluation.form.expectedSources.help")}
+            rules={[
+              {
+                required: true,
+                message: t("evaluation.form.expectedSources.required")
+              }
+            ]}>
+            <Select
+              mode="multiple"
+              options={knowledge.source.map((source) => ({
+                value: source.source_id,
+                label: source.filename || source.source_id
+              }))}
+            />
+          </Form.Item>
+          <Button type="primary" htmlType="submit" className="w-full mt-4">
+            {t("common:save")}
+          </Button>
+        </Form>
+      </Modal>
+    </div>
+  )
+}
@@ -1,6 +1,7 @@
 import { CSVIcon } from "@/components/Icons/CSVIcon"
 import { PDFIcon } from "@/components/Icons/PDFIcon"
 import { TXTIcon } from "@/components/Icons/TXTIcon"
+import { GlobeIcon } from "lucide-react"
 
 type Props = {
   type: string
@@ -14,5 +15,7 @@ export const KnowledgeIcon = ({ type, className = "w-6 h-6" }: Props) => {
     return <CSVIcon className={className} />
   } else if (type === "txt" || type === "text/plain") {
     return <TXTIcon className={className} />
+  } else if (type === "url" || type === "crawl") {
+    return <GlobeIcon className={className} />
   }
 }
@@ -0,0 +1,414 @@
+import { getKnowledgeById } from "@/db/dexie/knowledge"
+import type { VectorChunk } from "@/db/dexie/types"
+import { getVectorChunks } from "@/db/dexie/vector"
+import { useMessageOption } from "@/hooks/useMessageOption"
+import {
+  deleteKnowledgeChunk,
+  editKnowledgeChunk,
+  testKnowledgeQuery
+} from "@/libs/inspect-knowledge"
+import { getNoOfRetrievedDocs } from "@/services/app"
+import { KnowledgeEvaluation } from "./KnowledgeEvaluation"
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
+import {
+  Empty,
+  Input,
+  InputNumber,
+  Modal,
+  Select,
+  Skeleton,
+  Table,
+  Tabs,
+  Tag,
+  Tooltip,
+  Typography,
+  message
+} from "antd"
+import { ArrowLeft, Pencil, Trash2 } from "lucide-react"
+import React from "react"
+import { useTranslation } from "react-i18next"
+import { Link } from "react-router-dom"
+
+type Props = {
+  id: string
+}
+
+// metadata worth showing next to a chunk, e.g. the pdf page
+const LOCATION_KEYS = ["page", "slide", "sheet", "chapter", "url"]
+
+const ChunkLocation = ({ metadata }: { metadata: Record<string, any> }) => (
+  <div className="flex flex-wrap gap-1">
+    {LOCATION_KEYS.filter((key) => metadata?.[key] !== undefined).map(
+      (key) => (
+        <Tag key={key}>
+          {key}: {String(metadata[key])}
+        </Tag>
+      )
+    )}
+    {metadata?.ocr && <Tag color="blue">OCR</Tag>}
+    {metadata?.parent_id && <Tag color="purple">parent</Tag>}
+  </div>
+)
+
+export const KnowledgeInspector = ({ id }: Props) => {
+  const { t } = useTranslation(["knowledge", "common"])
+  const queryClient = useQueryClient()
+  const { selectedModel } = useMessageOption()
+  const [sourceFilter, setSourceFilter] = React.useState<string>()
+  const [search, setSearch] = React.useState("")
+  const [editing, setEditing] = React.useState<VectorChunk | null>(null)
+  const [editContent, setEditContent] = React.useState("")
+  const [query, setQuery] = React.useState("")
+  const [k, setK] = React.useState<number>()
+
+  const { data: knowledge, status } = useQuery({
+    queryKey: ["fetchKnowledgeById", id],
+    queryFn: () => getKnowledgeById(id)
+  })
+
+  const { data: chunks, status: chunkStatus } = useQuery({
+    queryKey: ["fetchKnowledgeChunks", id],
+    queryFn: () => getVectorChunks(`vector:${id}`)
+  })
+
+  const { data: defaultK } = useQuery({
+    queryKey: ["fetchNoOfRetrievedDocs"],
+    queryFn: getNoOfRetrievedDocs
+  })
+
+  const sourceNames = React.useMemo(
+    () =>
+      new Map(
+        (knowledge?.source || []).map((source) => [
+          source.source_id,
+          source.filename || source.source_id
+        ])
+      ),
+    [knowledge]
+  )
+
+  const filteredChunks = React.useMemo(
+    () =>
+      (chunks || []).filter(
+        (chunk) =>
+          (!sourceFilter || chunk.file_id === sourceFilter) &&
+          (!search ||
+            chunk.content.toLowerCase().includes(search.toLowerCase()))
+      ),
+    [chunks, sourceFilter, search]
+  )
+
+  const isProcessing = knowledge?.status === "processing"
+
+  const invalidateChunks = () =>
+    queryClient.invalidateQueries({
+      queryKey: ["fetchKnowledgeChunks", id]
+    })
+
+  const { mutate: saveChunk, isPending: isSaving } = useMutation({
+    mutationFn: () => editKnowledgeChunk(knowledge!, editing!, editContent),
+    onSuccess: async () => {
+      await invalidateChunks()
+      message.success(t("inspector.chunks.saved"))
+      setEditing(null)
+    },
+    onError: (error) => {
+      message.error(error.message)
+    }
+  })
+
+  const { mutate: removeChunk, isPending: isDeleting } = useMutation({
+    mutationFn: (chunk: VectorChunk) => deleteKnowledgeChunk(knowledge!, chunk),
+    onSuccess: async () => {
+      await invalidateChunks()
+      message.success(t("inspector.chunks.deleted"))
+    },
+    onError: (error) => {
+      message.error(error.message)
+    }
+  })
+
+  const {
+    mutate: runQuery,
+    data: results,
+    isPending: isQuerying
+  } = useMutation({
+    mutationFn: () =>
+      testKnowledgeQuery({
+        knowledge: knowledge!,
+        query,
+        k: k || defaultK || 4,
+        selectedModel
+      }),
+    onError: (error) => {
+      message.error(error.message)
+    }
+  })
+
+  const hasRerank = results?.some((result) => result.rerankScore !== undefined)
+
+  if (status === "pending") {
+    return <Skeleton paragraph={{ rows: 8 }} />
+  }
+
+  if (!knowledge) {
+    return <Empty description={t("inspector.notFound")} />
+  }
+
+  return (
+    <div className="flex flex-col gap-4">
+      <div className="flex items-center gap-3">
+        <Link
+          to="/settings/knowledge"
+          className="text-gray-700 dark:text-gray-400">
+          <ArrowLeft className="w-5 h-5" />
+        </Link>
+        <h2 className="text-base font-semibold leading-7 text-gray-900 dark:text-white">
+          {knowledge.title}
+        </h2>
+        <Tag>
+          {t("inspector.sourceCount", { count: knowledge.source.length })}
+        </Tag>
+        <Tag>{t("inspector.chunkCount", { count: chunks?.length ?? 0 })}</Tag>
+      </div>
+
+      <Tabs
+        items={[
+          {
+            key: "chunks",
+            label: t("inspector.tabs.chunks"),
+            children: (
+              <div className="flex flex-col gap-3">
+                <div className="flex flex-wrap gap-3">
+                  <Select
+                    allowClear
+                    className="min-w-64"
+                    placeholder={t("inspector.chunks.allSources")}
+                    value={sourceFilter}
+                    onChange={setSourceFilter}
+                    options={knowledge.source.map((source) => ({
+                      value: source.source_id,
+                      label: source.filename || source.source_id
+                    }))}
+                  />
+                  <Input.Search
+                    allowClear
+                    className="max-w-sm"
+                    placeholder={t("inspector.chunks.search")}
+                    onSearch={setSearch}
+                  />
+                </div>
+                <Table
+                  loading={chunkStatus === "pending"}
+                  rowKey="id"
+                  dataSource={filteredChunks}
+                  columns={[
+                    {
+                      title: t("inspector.chunks.source"),
+                      key: "source",
+                      width: 200,
+                      render: (_, chunk: VectorChunk) => (
+                        <div className="flex flex-col gap-1">
+                          <span className="break-all">
+                            {sourceNames.get(chunk.file_id) || chunk.file_id}
+                          </span>
+                          <ChunkLocation metadata={chunk.metadata} />
+                        </div>
+                      )
+                    },
+                    {
+                      title: t("inspector.chunks.content"),
+                      dataIndex: "content",
+                      key: "content",
+                      render: (content: string) => (
+                        <Typography.Paragraph
+                          className="!mb-0 whitespace-pre-wrap"
+                          ellipsis={{ rows: 3, expandable: true }}>
+                          {content}
+                        </Typography.Paragraph>
+                      )
+                    },
+                    {
+                      title: t("columns.action"),
+                      key: "action",
+                      width: 100,
+                      render: (_, chunk: VectorChunk) => (
+                        <div className="flex gap-4">
+                          <Tooltip title={t("inspector.chunks.edit")}>
+                            <button
+                              disabled={isProcessing}
+                              onClick={() => {
+                                setEditing(chunk)
+                                setEditContent(chunk.content)
+                              }}
+                              className="text-gray-700 dark:text-gray-400 disabled:opacity-50">
+                              <Pencil className="w-5 h-5" />
+                            </button>
+                          </Tooltip>
+                          <Tooltip title={t("common:delete")}>
+                            <button
+                              disabled={isProcessing || isDeleting}
+                              onClick={() => {
+                                if (
+                                  window.confirm(
+                                    t("inspector.chunks.confirmDelete")
+                                  )
+                                ) {
+                                  removeChunk(chunk)
+                                }
+                              }}
+                              className="text-red-500 dark:text-red-400 disabled:opacity-50">
+                              <Trash2 className="w-5 h-5" />
+                            </button>
+                          </Tooltip>
+                        </div>
+                      )
+                    }
+                  ]}
+                  locale={{
+                    emptyText: t("common:noData")
+                  }}
+                  bordered
+                />
+              </div>
+            )
+          },
+          {
+            key: "playground",
+            label: t("inspector.tabs.playground"),
+            children: (
+              <div className="flex flex-col gap-3">
+                <div className="flex flex-wrap gap-3">
+                  <Input.Search
+                    className="flex-1 min-w-64"
+                    placeholder={t("inspector.playground.placeholder")}
+                    enterButton={t("inspector.playground.run")}
+                    loading={isQuerying}
+                    value={query}
+                    onChange={(e) => setQuery(e.target.value)}
+                    onSearch={() => {
+                      if (query.trim()) {
+                        runQuery()
+                      }
+                    }}
+                  />
+                  <Tooltip title={t("inspector.playground.k")}>
+                    <InputNumber
+                      min={1}
+                      max={50}
+                      value={k ?? defaultK}
+                      onChange={(value) => setK(value ?? undefined)}
+                    />
+                  </Tooltip>
+                </div>
+                {results && !hasRerank && (
+                  <Typography.Text type="secondary">
+                    {t("inspector.playground.noRerank")}
+                  </Typography.Text>
+                )}
+                <Table
+                  loading={isQuerying}
+                  rowKey={(_, i) => String(i)}
+                  dataSource={results || []}
+                  pagination={false}
+                  columns={[
+                    {
+                      title: "#",
+                      key: "rank",
+                      width: 50,
+                      render: (_, __, i) => i + 1
+                    },
+                    {
+                      title: t("inspector.playground.score"),
+                      dataIndex: "score",
+                      key: "score",
+                      width: 100,
+                      render: (score: number) => score.toFixed(4)
+                    },
+                    ...(hasRerank
+                      ? [
+                          {
+                            title: t("inspector.playground.rerankScore"),
+                            key: "rerankScore",
+                            width: 140,
+                            render: (_, result) => (
+                              <Tooltip
+                                title={
+                                  result.kept
+                                    ? t("inspector.playground.kept")
+                                    : t("inspector.playground.dropped")
+                                }>
+                                <Tag color={result.kept ? "green" : "default"}>
+                                  {result.rerankScore.toFixed(4)}
+                                </Tag>
+                              </Tooltip>
+                            )
+                          }
+                        ]
+                      : []),
+                    {
+                      title: t("inspector.chunks.source"),
+                      key: "source",
+                      width: 200,
+                      render: (_, result) => (
+                        <div className="flex flex-col gap-1">
+                          <span className="break-all">
+                            {result.doc.metadata?.source || "untitled"}
+                          </span>
+                          <ChunkLocation metadata={result.doc.metadata} />
+                        </div>
+                      )
+                    },
+                    {
+                      title: t("inspector.chunks.content"),
+                      key: "content",
+                      render: (_, result) => (
+                        <Typography.Paragraph
+                          className="!mb-0 whitespace-pre-wrap"
+                          ellipsis={{ rows: 3, expandable: true }}>
+                          {result.doc.pageContent}
+                        </Typography.Paragraph>
+                      )
+                    }
+                  ]}
+                  locale={{
+                    emptyText: t("inspector.playground.empty")
+                  }}
+                  bordered
+                />
+              </div>
+            )
+          },
+          {
+            key: "evaluation",
+            label: t("inspector.tabs.evaluation"),
+            children: (
+              <KnowledgeEvaluation
+                knowledge={knowledge}
+                selectedModel={selectedModel}
+              />
+            )
+          }
+        ]}
+      />
+
+      <Modal
+        title={t("inspector.chunks.edit")}
+        open={!!editing}
+        onCancel={() => setEditing(null)}
+        onOk={() => saveChunk()}
+        okText={t("common:save")}
+        confirmLoading={isSaving}
+        width={700}>
+        <p className="mb-2 text-sm text-gray-500 dark:text-gray-400">
+          {t("inspector.chunks.editHelp")}
+        </p>
+        <Input.TextArea
+          autoSize={{ minRows: 6, maxRows: 16 }}
+          value={editContent}
+          onChange={(e) => setEditContent(e.target.value)}
+        />
+      </Modal>
+    </div>
+  )
+}
@@ -0,0 +1,123 @@
+import { getKnowledgeById, setKnowledgeMigration } from "@/db/dexie/knowledge"
+import { getEmbeddingModels } from "@/services/ollama"
+import { KNOWLEDGE_REEMBED_QUEUE } from "@/queue"
+import { ProviderIcons } from "@/components/Common/ProviderIcon"
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
+import { Avatar, Form, Modal, Select, Skeleton, message } from "antd"
+import PubSub from "pubsub-js"
+import React from "react"
+import { useTranslation } from "react-i18next"
+
+type Props = {
+  id: string
+  open: boolean
+  setOpen: (open: boolean) => void
+}
+
+export const ReEmbedKnowledge: React.FC<Props> = ({ id, open, setOpen }) => {
+  const [form] = Form.useForm()
+  const { t } = useTranslation(["knowledge", "common"])
+  const queryClient = useQueryClient()
+
+  const { data, status } = useQuery({
+    queryKey: ["fetchReEmbedInfo", id],
+    queryFn: async () => {
+      const [knowledge, models] = await Promise.all([
+        getKnowledgeById(id),
+        getEmbeddingModels({ returnEmpty: true })
+      ])
+      return { knowledge, models }
+    },
+    enabled: open && !!id,
+    staleTime: 0
+  })
+
+  const { mutate, isPending } = useMutation({
+    mutationFn: async ({ model }: { model: string }) => {
+      if (model === data?.knowledge?.embedding_model) {
+        throw new Error(t("reEmbed.sameModel"))
+      }
+      await setKnowledgeMigration(id, {
+        model,
+        status: "pending",
+        progress: 0
+      })
+      return model
+    },
+    onSuccess: async (model) => {
+      PubSub.publish(KNOWLEDGE_REEMBED_QUEUE, { id, model })
+      await queryClient.invalidateQueries({
+        queryKey: ["fetchAllKnowledge"]
+      })
+      message.success(t("reEmbed.started"))
+      form.resetFields()
+      setOpen(false)
+    },
+    onError: (error) => {
+      message.error(error.message)
+    }
+  })
+
+  return (
+    <Modal
+      title={t("reEmbed.title")}
+      open={open}
+      onCancel={() => setOpen(false)}
+      footer={null}>
+      {status === "pending" && <Skeleton active />}
+      {status === "success" && (
+        <Form onFinish={mutate} form={form} layout="vertical">
+          <p className="mb-4 text-sm text-gray-500 dark:text-gray-400">
+            {t("reEmbed.description")}
+          </p>
+          <Form.Item
+            name="model"
+            label={t("reEmbed.model.label")}
+            rules={[
+              {
+                required: true,
+                message: t("reEmbed.model.required")
+              }
+            ]}>
+            <Select
+              size="large"
+              showSearch
+              placeholder={t("reEmbed.model.placeholder")}
+              filterOption={(input, option) =>
+                option.label.key.toLowerCase().indexOf(input.toLowerCase()) >=
+                0
+              }
+              options={data.models?.map((model) => ({
+                label: (
+                  <span
+                    key={model.model}
+                    className="flex flex-row gap-3 items-center truncate">
+                    {model?.avatar ? (
+                      <Avatar src={model.avatar} alt={model.name} size="small" />
+                    ) : (
+                      <ProviderIcons
+                        provider={model?.provider}
+                        className="w-5 h-5"
+                      />
+                    )}
+                    <span className="truncate">
+                      {model?.nickname || model?.name}
+                    </span>
+                  </span>
+                ),
+                value: model.model
+              }))}
+            />
+          </Form.Item>
+
+          <button
+            type="submit"
+            disabled={isPending}
+            className="inline-flex justify-center w-full text-center mt-4 items-center rounded-md border border-transparent bg-black px-2 py-2 text-sm font-medium leading-4 text-white shadow-sm hover:bg-gray-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-white dark:text-gray-800 dark:hover:bg-gray-100 dark:focus:ring-gray-500 dark:focus:ring-offset-gray-100 disabled:opacity-50">
+            {t("reEmbed.submit")}
+          </button>
+        </Form>
+      )}
+    </Modal>
+  )
+}
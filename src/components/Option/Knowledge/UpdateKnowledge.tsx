@@ -19,7 +19,9 @@ import PubSub from "pubsub-js"
 import { KNOWLEDGE_QUEUE } from "@/queue"
 import { useStorage } from "@plasmohq/storage/hook"
 import { unsupportedTypes } from "./utils/unsupported-types"
+import { getOfficeDocumentType, officeMimeTypes } from "@/loader/office"
 import React from "react"
+import { CrawlSourceFields, crawlSourceFromForm } from "./CrawlSourceFields"
 
 type Props = {
   id: string
@@ -31,7 +33,9 @@ export const UpdateKnowledge = ({ id, open, setOpen }: Props) => {
   const { t } = useTranslation(["knowledge", "common"])
   const [form] = Form.useForm()
   const [totalFilePerKB] = useStorage("totalFilePerKB", 5)
-  const [mode, setMode] = React.useState<"upload" | "text">("upload")
+  const [mode, setMode] = React.useState<"upload" | "text" | "crawl">(
+    "upload"
+  )
 
   const onUploadHandler = async (data: any) => {
     const defaultEM = await defaultEmbeddingModelForRag()
@@ -47,14 +51,15 @@ export const UpdateKnowledge = ({ id, open, setOpen }: Props) => {
       "text/csv",
       "text/plain",
       "text/markdown",
-      "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+      "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+      ...officeMimeTypes
     ]
 
     if (mode === "upload") {
       for (const file of data.file || []) {
         let mime = file.type
         if (!allowedTypes.includes(mime)) {
-          mime = "text/plain"
+          mime = getOfficeDocumentType(file.name) || "text/plain"
         }
         const _src = await convertToSource({
           file,
@@ -63,6 +68,8 @@ export const UpdateKnowledge = ({ id, open, setOpen }: Props) => {
         })
         source.push(_src)
       }
+    } else if (mode === "crawl") {
+      source.push(crawlSourceFromForm(data))
     } else {
       const rawText: string = (data?.textContent || "").trim()
       const textType: string = data?.textType || "plain"
@@ -113,7 +120,8 @@ export const UpdateKnowledge = ({ id, open, setOpen }: Props) => {
         onChange={(key) => setMode(key as any)}
         items={[
           { key: "upload", label: t("form.tabs.upload") },
-          { key: "text", label: t("form.tabs.text") }
+          { key: "text", label: t("form.tabs.text") },
+          { key: "crawl", label: t("form.tabs.crawl") }
         ]}
       />
       <Form onFinish={saveKnowledge} form={form} layout="vertical">
@@ -142,7 +150,8 @@ export const UpdateKnowledge = ({ id, open, setOpen }: Props) => {
                   "text/csv",
                   "text/plain",
                   "text/markdown",
-                  "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+                  "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                  ...officeMimeTypes
                 ]
                   .map((type) => type.toLowerCase())
                   .join(", ")
@@ -166,6 +175,8 @@ export const UpdateKnowledge = ({ id, open, setOpen }: Props) => {
               </div>
             </Upload.Dragger>
           </Form.Item>
+        ) : mode === "crawl" ? (
+          <CrawlSourceFields />
         ) : (
           <>
             <Form.Item
@@ -5,14 +5,23 @@ import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
 import {
   deleteKnowledge,
   deleteSource,
-  getAllKnowledge
+  getAllKnowledge,
+  isKnowledgeMigrating
 } from "@/db/dexie/knowledge"
 import { Skeleton, Table, Tag, Tooltip, message, notification } from "antd"
-import { FileUpIcon, Trash2, Settings } from "lucide-react"
+import {
+  FileUpIcon,
+  Trash2,
+  Settings,
+  RefreshCcw,
+  SearchIcon
+} from "lucide-react"
+import { Link } from "react-router-dom"
 import { useMessageOption } from "@/hooks/useMessageOption"
 import { removeModelSuffix } from "@/db/dexie/models"
 import { UpdateKnowledge } from "./UpdateKnowledge"
 import { EditKnowledgeSettings } from "./EditKnowledgeSettings"
+import { ReEmbedKnowledge } from "./ReEmbedKnowledge"
 import { isFireFoxPrivateMode } from "@/utils/is-private-mode"
 
 export const KnowledgeSettings = () => {
@@ -24,6 +33,8 @@ export const KnowledgeSettings = () => {
   const [updateKnowledgeId, setUpdateKnowledgeId] = useState("")
   const [openEditSettings, setOpenEditSettings] = useState(false)
   const [editSettingsKnowledgeId, setEditSettingsKnowledgeId] = useState("")
+  const [openReEmbed, setOpenReEmbed] = useState(false)
+  const [reEmbedKnowledgeId, setReEmbedKnowledgeId] = useState("")
 
   const { data, status } = useQuery({
     queryKey: ["fetchAllKnowledge"],
@@ -92,8 +103,65 @@ export const KnowledgeSettings = () => {
                 title: t("columns.status"),
                 dataIndex: "status",
                 key: "status",
-                render: (text: string) => (
-                  <Tag color={statusColor[text]}>{t(`status.${text}`)}</Tag>
+                render: (text: string, record: any) => (
+                  <Tooltip
+                    title={
+                      record?.indexStats
+                        ? t("indexStats", {
+                            added: record.indexStats.added,
+                            removed: record.indexStats.removed,
+                            unchanged: record.indexStats.unchanged
+                          })
+                        : undefined
+                    }>
+                    <Tag color={statusColor[text]}>{t(`status.${text}`)}</Tag>
+                    {record?.migration && (
+                      <Tooltip
+                        title={
+                          record.migration.error ||
+                          removeModelSuffix(record.migration.model)
+                        }>
+                        <Tag
+                          color={
+                            record.migration.status === "failed"
+                              ? "red"
+                              : "blue"
+                          }>
+                          {t(`reEmbed.status.${record.migration.status}`, {
+                            progress: record.migration.progress
+                          })}
+                        </Tag>
+                      </Tooltip>
+                    )}
+                    {record?.source?.some((s) => s.emptyPages?.length) && (
+                      <Tooltip title={t("emptyPages.warning")}>
+                        <Tag color="orange">{t("emptyPages.tag")}</Tag>
+                      </Tooltip>
+                    )}
+                    {record?.refresh?.lastRefreshedAt && (
+                      <Tooltip
+                        title={
+                          record.refresh.error ||
+                          t("refresh.lastRefreshed", {
+                            date: new Date(
+                              record.refresh.lastRefreshedAt
+                            ).toLocaleString(),
+                            changed: record.refresh.changed ?? 0
+                          })
+                        }>
+                        <Tag
+                          color={
+                            record.refresh.status === "failed"
+                              ? "red"
+                              : record.refresh.status === "processing"
+                                ? "blue"
+                                : "default"
+                          }>
+                          {t(`refresh.status.${record.refresh.status}`)}
+                        </Tag>
+                      </Tooltip>
+                    )}
+                  </Tooltip>
                 )
               },
               {
@@ -113,6 +181,13 @@ export const KnowledgeSettings = () => {
                 key: "action",
                 render: (text: string, record: any) => (
                   <div className="flex gap-4">
+                    <Tooltip title={t("inspector.tooltip")}>
+                      <Link
+                        to={`/settings/knowledge/${record.id}`}
+                        className="text-gray-700 dark:text-gray-400">
+                        <SearchIcon className="w-5 h-5" />
+                      </Link>
+                    </Tooltip>
                     <Tooltip title={t("editSettings.tooltip", )}>
                       <button
                         disabled={isDeleting}
@@ -124,9 +199,28 @@ export const KnowledgeSettings = () => {
                         <Settings className="w-5 h-5" />
                       </button>
                     </Tooltip>
+                    <Tooltip title={t("reEmbed.tooltip")}>
+                      <button
+                        disabled={
+                          isDeleting ||
+                          record.status !== "finished" ||
+                          isKnowledgeMigrating(record)
+                        }
+                        onClick={() => {
+                          setReEmbedKnowledgeId(record.id)
+                          setOpenReEmbed(true)
+                        }}
+                        className="text-gray-700 dark:text-gray-400 disabled:opacity-50">
+                        <RefreshCcw className="w-5 h-5" />
+                      </button>
+                    </Tooltip>
                     <Tooltip title={t("updateKnowledge")}>
                       <button
-                        disabled={isDeleting || record.status === "processing"}
+                        disabled={
+                          isDeleting ||
+                          record.status === "processing" ||
+                          isKnowledgeMigrating(record)
+                        }
                         onClick={() => {
                           setUpdateKnowledgeId(record.id)
                           setOpenUpdate(true)
@@ -162,7 +256,29 @@ export const KnowledgeSettings = () => {
                     {
                       title: t("expandedColumns.name"),
                       key: "filename",
-                      dataIndex: "filename"
+                      dataIndex: "filename",
+                      render: (text: string, r: any) => (
+                        <>
+                          {text}
+                          {r.error && (
+                            <Tooltip title={r.error}>
+                              <Tag color="red" className="ml-2">
+                                {t("sourceError")}
+                              </Tag>
+                            </Tooltip>
+                          )}
+                          {r.emptyPages?.length > 0 && (
+                            <Tooltip
+                              title={t("emptyPages.pages", {
+                                pages: r.emptyPages.join(", ")
+                              })}>
+                              <Tag color="orange" className="ml-2">
+                                {t("emptyPages.tag")}
+                              </Tag>
+                            </Tooltip>
+                          )}
+                        </>
+                      )
                     },
                     {
                       title: t("columns.action"),
@@ -172,7 +288,9 @@ export const KnowledgeSettings = () => {
                           <Tooltip title={t("common:delete")}>
                             <button
                               disabled={
-                                isDeleting || record.status === "processing"
+                                isDeleting ||
+                                record.status === "processing" ||
+                                isKnowledgeMigrating(record)
                               }
                               onClick={async () => {
                                 if (window.confirm(t("confirm.deleteSource"))) {
@@ -213,6 +331,11 @@ export const KnowledgeSettings = () => {
         open={openEditSettings}
         setOpen={setOpenEditSettings}
       />
+      <ReEmbedKnowledge
+        id={reEmbedKnowledgeId}
+        open={openReEmbed}
+        setOpen={setOpenReEmbed}
+      />
     </div>
   )
 }
@@ -0,0 +1,26 @@
+import { useAgentStore } from "@/store/agent"
+import { Modal } from "antd"
+import { useTranslation } from "react-i18next"
+
+export const AgentConfirmModal = () => {
+  const { t } = useTranslation("playground")
+  const { pendingConfirmation, respondConfirmation } = useAgentStore()
+
+  return (
+    <Modal
+      open={!!pendingConfirmation}
+      title={t("agent.confirm.title")}
+      okText={t("agent.confirm.approve")}
+      cancelText={t("agent.confirm.decline")}
+      onOk={() => respondConfirmation(true)}
+      onCancel={() => respondConfirmation(false)}
+      maskClosable={false}>
+      <p className="text-sm text-gray-500 dark:text-gray-400">
+        {t("agent.confirm.description")}
+      </p>
+      <p className="mt-2 text-sm font-medium">
+        {pendingConfirmation?.description}
+      </p>
+    </Modal>
+  )
+}
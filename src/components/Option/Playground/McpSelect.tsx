@@ -0,0 +1,258 @@
+import { getEnabledMcpServers } from "@/db/dexie/mcp"
+import type { McpServer } from "@/db/dexie/types"
+import { getMcpClient, type McpPrompt } from "@/libs/mcp-client"
+import { isToolCallingEnabled } from "@/services/tools"
+import { useQuery } from "@tanstack/react-query"
+import { Dropdown, Form, Input, Modal, Spin, Tooltip, message } from "antd"
+import type { MenuProps } from "antd"
+import { PlugZap } from "lucide-react"
+import React from "react"
+import { useTranslation } from "react-i18next"
+
+type Props = {
+  // adds text to the chat input
+  onInsert: (text: string) => void
+}
+
+const listCapabilities = async (server: McpServer) => {
+  try {
+    const client = await getMcpClient(server)
+    const [tools, resources, prompts] = await Promise.all([
+      client.listTools().catch(() => []),
+      client.listResources().catch(() => []),
+      client.listPrompts().catch(() => [])
+    ])
+    return { server, tools, resources, prompts, error: null }
+  } catch (e) {
+    return {
+      server,
+      tools: [],
+      resources: [],
+      prompts: [],
+      error: e instanceof Error ? e.message : String(e)
+    }
+  }
+}
+
+export const McpSelect: React.FC<Props> = ({ onInsert }) => {
+  const { t } = useTranslation("playground")
+  const [open, setOpen] = React.useState(false)
+  const [loading, setLoading] = React.useState(false)
+  const [prompt, setPrompt] = React.useState<{
+    server: McpServer
+    prompt: McpPrompt
+  } | null>(null)
+  const [form] = Form.useForm()
+
+  const { data: servers } = useQuery({
+    queryKey: ["fetchEnabledMcpServers"],
+    queryFn: getEnabledMcpServers
+  })
+
+  const { data: toolCalling } = useQuery({
+    queryKey: ["fetchToolCallingEnabled"],
+    queryFn: isToolCallingEnabled
+  })
+
+  const { data: capabilities, isFetching } = useQuery({
+    queryKey: ["fetchMcpMenu", servers],
+    queryFn: () => Promise.all(servers!.map(listCapabilities)),
+    enabled: open && !!servers?.length
+  })
+
+  const run = async (action: () => Promise<string>) => {
+    setLoading(true)
+    try {
+      onInsert(await action())
+    } catch (e) {
+      message.error(e instanceof Error ? e.message : String(e))
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  const insertPrompt = (
+    server: McpServer,
+    name: string,
+    args: Record<string, string> = {}
+  ) =>
+    run(async () => (await getMcpClient(server)).getPrompt(name, args))
+
+  const items: MenuProps["items"] = (capabilities || []).map(
+    ({ server, tools, resources, prompts, error }) => ({
+      key: server.id,
+      label: server.name,
+      children: error
+        ? [
+            {
+              key: `${server.id}:error`,
+              label: (
+                <Tooltip title={error} placement="right">
+                  {t("mcp.connectError")}
+                </Tooltip>
+              ),
+              disabled: true
+            }
+          ]
+        : [
+            {
+              type: "group" as const,
+              key: `${server.id}:prompts`,
+              label: t("mcp.prompts"),
+              children: prompts.length
+                ? prompts.map((p) => ({
+                    key: `${server.id}:prompt:${p.name}`,
+                    label: (
+                      <Tooltip title={p.description} placement="right">
+                        {p.name}
+                      </Tooltip>
+                    ),
+                    onClick: () => {
+                      if (p.arguments?.length) {
+                        form.resetFields()
+                        setPrompt({ server, prompt: p })
+                      } else {
+                        insertPrompt(server, p.name)
+                      }
+                    }
+                  }))
+                : [
+                    {
+                      key: `${server.id}:prompts:none`,
+                      label: t("mcp.none"),
+                      disabled: true
+                    }
+                  ]
+            },
+            {
+              type: "group" as const,
+              key: `${server.id}:resources`,
+              label: t("mcp.resources"),
+              children: resources.length
+                ? resources.map((resource) => ({
+                    key: `${server.id}:resource:${resource.uri}`,
+                    label: (
+                      <Tooltip
+                        title={resource.description || resource.uri}
+                        placement="right">
+                        {resource.name}
+                      </Tooltip>
+                    ),
+                    onClick: () =>
+                      run(async () => {
+                        const client = await getMcpClient(server)
+                        const text = await client.readResource(resource.uri)
+                        return `${resource.name}:\n\`\`\`\n${text}\n\`\`\``
+                      })
+                  }))
+                : [
+                    {
+                      key: `${server.id}:resources:none`,
+                      label: t("mcp.none"),
+                      disabled: true
+                    }
+                  ]
+            },
+            {
+              type: "group" as const,
+              key: `${server.id}:tools`,
+              label: toolCalling ? t("mcp.tools") : t("mcp.toolsDisabled"),
+              children: tools.length
+                ? tools.map((tool) => ({
+                    key: `${server.id}:tool:${tool.name}`,
+                    label: (
+                      <Tooltip title={tool.description} placement="right">
+                        {tool.name}
+                      </Tooltip>
+                    ),
+                    disabled: true
+                  }))
+                : [
+                    {
+                      key: `${server.id}:tools:none`,
+                      label: t("mcp.none"),
+                      disabled: true
+                    }
+                  ]
+            }
+          ]
+    })
+  )
+
+  if (!servers?.length) {
+    return null
+  }
+
+  return (
+    <>
+      <Dropdown
+        open={open}
+        onOpenChange={setOpen}
+        menu={{
+          items: isFetching
+            ? [{ key: "loading", label: <Spin size="small" />, disabled: true }]
+            : items,
+          style: {
+            maxHeight: 500,
+            overflowY: "auto"
+          },
+          className: "no-scrollbar"
+        }}
+        placement="topLeft"
+        trigger={["click"]}>
+        <Tooltip title={t("tooltip.mcp")}>
+          <button
+            type="button"
+            disabled={loading}
+            className="flex items-center justify-center dark:text-gray-300 disabled:opacity-50">
+            {loading ? <Spin size="small" /> : <PlugZap className="h-5 w-5" />}
+          </button>
+        </Tooltip>
+      </Dropdown>
+
+      <Modal
+        open={!!prompt}
+        title={prompt?.prompt.name}
+        onCancel={() => setPrompt(null)}
+        onOk={() => form.submit()}
+        okText={t("mcp.insert")}>
+        {prompt?.prompt.description && (
+          <p className="mb-4 text-sm text-gray-500 dark:text-gray-400">
+            {prompt.prompt.description}
+          </p>
+        )}
+        <Form
+          form={form}
+          layout="vertical"
+          onFinish={(values: Record<string, string>) => {
+            const { server, prompt: selected } = prompt!
+            setPrompt(null)
+            insertPrompt(
+              server,
+              selected.name,
+              // unset optional arguments are left out
+              Object.fromEntries(
+                Object.entries(values).filter(([, value]) => value)
+              )
+            )
+          }}>
+          {prompt?.prompt.arguments?.map((argument) => (
+            <Form.Item
+              key={argument.name}
+              name={argument.name}
+              label={argument.name}
+              help={argument.description}
+              rules={[
+                {
+                  required: argument.required,
+                  message: t("mcp.argumentRequired")
+                }
+              ]}>
+              <Input />
+            </Form.Item>
+          ))}
+        </Form>
+      </Modal>
+    </>
+  )
+}
@@ -1,6 +1,8 @@
 import React from "react"
 import { TabInfo, MentionPosition } from "~/hooks/useTabMentions"
-import { Globe, X, RefreshCw } from "lucide-react"
+import { KnowledgeFileInfo } from "~/hooks/useKnowledgeMentions"
+import { Globe, X, RefreshCw, FileText } from "lucide-react"
+import { useTranslation } from "react-i18next"
 
 interface MentionsDropdownProps {
   show: boolean
@@ -11,6 +13,9 @@ interface MentionsDropdownProps {
   textareaRef: React.RefObject<HTMLTextAreaElement>
   refetchTabs: () => Promise<void>
   onMentionsOpen: () => Promise<void>
+  // knowledge base files offered for `@file:` mentions, shown instead of tabs
+  files?: KnowledgeFileInfo[]
+  onSelectFile?: (file: KnowledgeFileInfo) => void
 }
 
 export const MentionsDropdown: React.FC<MentionsDropdownProps> = ({
@@ -21,8 +26,14 @@ export const MentionsDropdown: React.FC<MentionsDropdownProps> = ({
   onClose,
   textareaRef,
   refetchTabs,
-  onMentionsOpen
+  onMentionsOpen,
+  files,
+  onSelectFile
 }) => {
+  const { t } = useTranslation("playground")
+  const showFiles = !!files && files.length > 0
+  const itemCount = showFiles ? files.length : tabs.length
+
   const [selectedIndex, setSelectedIndex] = React.useState(0)
   const [isRefreshing, setIsRefreshing] = React.useState(false)
   const dropdownRef = React.useRef<HTMLDivElement>(null)
@@ -30,7 +41,7 @@ export const MentionsDropdown: React.FC<MentionsDropdownProps> = ({
 
   React.useEffect(() => {
     setSelectedIndex(0)
-  }, [tabs])
+  }, [tabs, files])
 
   React.useEffect(() => {
     if (show && textareaRef.current && dropdownRef.current) {
@@ -42,13 +53,13 @@ export const MentionsDropdown: React.FC<MentionsDropdownProps> = ({
         left: 0
       })
     }
-  }, [show, tabs])
+  }, [show, tabs, files])
 
   React.useEffect(() => {
-    if (show) {
+    if (show && !showFiles) {
       onMentionsOpen()
     }
-  }, [show, onMentionsOpen])
+  }, [show, showFiles, onMentionsOpen])
 
   React.useEffect(() => {
     const handleKeyDown = (e: KeyboardEvent) => {
@@ -57,15 +68,19 @@ export const MentionsDropdown: React.FC<MentionsDropdownProps> = ({
       switch (e.key) {
         case "ArrowDown":
           e.preventDefault()
-          setSelectedIndex((prev) => (prev + 1) % tabs.length)
+          setSelectedIndex((prev) => (prev + 1) % itemCount)
           break
         case "ArrowUp":
           e.preventDefault()
-          setSelectedIndex((prev) => (prev - 1 + tabs.length) % tabs.length)
+          setSelectedIndex((prev) => (prev - 1 + itemCount) % itemCount)
           break
         case "Enter":
           e.preventDefault()
-          if (tabs[selectedIndex]) {
+          if (showFiles) {
+            if (files[selectedIndex]) {
+              onSelectFile?.(files[selectedIndex])
+            }
+          } else if (tabs[selectedIndex]) {
             onSelectTab(tabs[selectedIndex])
           }
           break
@@ -80,7 +95,17 @@ export const MentionsDropdown: React.FC<MentionsDropdownProps> = ({
       document.addEventListener("keydown", handleKeyDown)
       return () => document.removeEventListener("keydown", handleKeyDown)
     }
-  }, [show, tabs, selectedIndex, onSelectTab, onClose])
+  }, [
+    show,
+    tabs,
+    files,
+    showFiles,
+    itemCount,
+    selectedIndex,
+    onSelectTab,
+    onSelectFile,
+    onClose
+  ])
 
   const handleRefreshTabs = async () => {
     if (isRefreshing) return
@@ -95,7 +120,49 @@ export const MentionsDropdown: React.FC<MentionsDropdownProps> = ({
     }
   }
 
-  if (!show || tabs.length === 0) return null
+  if (!show || itemCount === 0) return null
+
+  if (showFiles) {
+    return (
+      <div
+        ref={dropdownRef}
+        className="absolute z-50 bg-neutral-50 dark:bg-[#2a2a2a] border border-gray-300 dark:border-gray-600 rounded-lg shadow-lg max-h-80 overflow-y-auto w-80"
+        style={{
+          top: position.top,
+          left: position.left
+        }}>
+        <div className="p-2 border-b border-gray-200 dark:border-gray-600 flex items-center justify-between">
+          <span className="text-sm font-medium text-gray-700 dark:text-gray-100">
+            {t("mentions.selectFile")}
+          </span>
+          <button
+            onClick={onClose}
+            className="text-gray-400 hover:text-gray-600 dark:hover:text-gray-100">
+            <X className="h-4 w-4" />
+          </button>
+        </div>
+
+        <div className="max-h-56 overflow-y-auto">
+          {files.map((file, index) => (
+            <button
+              key={file.id}
+              type="button"
+              onClick={() => onSelectFile?.(file)}
+              className={`w-full text-left p-3 hover:bg-gray-100 dark:hover:bg-gray-600 flex items-center gap-3 transition-colors ${
+                index === selectedIndex
+                  ? "bg-gray-100 dark:bg-gray-600 border-r-2 border-blue-500"
+                  : ""
+              }`}>
+              <FileText className="flex-shrink-0 w-4 h-4 text-gray-400" />
+              <div className="flex-1 min-w-0 text-sm font-medium text-gray-900 dark:text-gray-100 truncate">
+                {file.filename}
+              </div>
+            </button>
+          ))}
+        </div>
+      </div>
+    )
+  }
 
   return (
     <div
@@ -108,7 +175,7 @@ export const MentionsDropdown: React.FC<MentionsDropdownProps> = ({
     >
       <div className="p-2 border-b border-gray-200 dark:border-gray-600 flex items-center justify-between">
         <span className="text-sm font-medium text-gray-700 dark:text-gray-100">
-          Select Tab
+          {t("mentions.selectTab")}
         </span>
         <div className="flex items-center gap-2">
           <button
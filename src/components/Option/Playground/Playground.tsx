@@ -1,6 +1,7 @@
 import React from "react"
 import { PlaygroundForm } from "./PlaygroundForm"
 import { PlaygroundChat } from "./PlaygroundChat"
+import { AgentConfirmModal } from "./AgentConfirmModal"
 import { useMessageOption } from "@/hooks/useMessageOption"
 import { webUIResumeLastChat } from "@/services/app"
 import {
@@ -211,6 +212,7 @@ export const Playground = () => {
         )}
         <PlaygroundForm dropedFile={dropedFile} />
       </div>
+      <AgentConfirmModal />
     </div>
   )
 }
@@ -17,25 +17,32 @@ import {
   FileIcon,
   FileText,
   PaperclipIcon,
-  Brain
+  Brain,
+  MousePointerClick,
+  Telescope
 } from "lucide-react"
 import { getVariable } from "@/utils/select-variable"
 import { useTranslation } from "react-i18next"
 import { KnowledgeSelect } from "../Knowledge/KnowledgeSelect"
+import { McpSelect } from "./McpSelect"
 import { useSpeechRecognition } from "@/hooks/useSpeechRecognition"
 import { PiGlobe } from "react-icons/pi"
 import { handleChatInputKeyDown } from "@/utils/key-down"
 import { getIsSimpleInternetSearch } from "@/services/search"
 import { useStorage } from "@plasmohq/storage/hook"
 import { useTabMentions } from "~/hooks/useTabMentions"
+import { useKnowledgeMentions } from "~/hooks/useKnowledgeMentions"
 import { useFocusShortcuts } from "~/hooks/keyboard"
 import { MentionsDropdown } from "./MentionsDropdown"
 import { DocumentChip } from "./DocumentChip"
 import { otherUnsupportedTypes } from "../Knowledge/utils/unsupported-types"
+import { officeFileExtensions } from "@/loader/office"
 import { PASTED_TEXT_CHAR_LIMIT } from "@/utils/constant"
 import { PlaygroundFile } from "./PlaygroundFile"
 import { isThinkingCapableModel, isGptOssModel } from "~/libs/model-utils"
 import { useStoreChatModelSettings } from "~/store/model"
+import { CODE_OUTPUT_INSERT } from "@/libs/code-interpreter"
+import PubSub from "pubsub-js"
 type Props = {
   dropedFile: File | undefined
 }
@@ -56,6 +63,10 @@ export const PlaygroundForm = ({ dropedFile }: Props) => {
     streaming: isSending,
     webSearch,
     setWebSearch,
+    deepResearch,
+    setDeepResearch,
+    agentMode,
+    setAgentMode,
     selectedQuickPrompt,
     textareaRef,
     setSelectedQuickPrompt,
@@ -98,6 +109,14 @@ export const PlaygroundForm = ({ dropedFile }: Props) => {
     handleMentionsOpen
   } = useTabMentions(textareaRef)
 
+  const {
+    showFileMentions,
+    filteredFiles,
+    handleFileMentionChange,
+    insertFileMention,
+    closeFileMentions
+  } = useKnowledgeMentions(textareaRef, selectedKnowledge?.id)
+
   // Enable focus shortcuts (Shift+Esc to focus textarea)
   useFocusShortcuts(textareaRef, true)
 
@@ -259,6 +278,20 @@ export const PlaygroundForm = ({ dropedFile }: Props) => {
     }
   }, [selectedQuickPrompt])
 
+  React.useEffect(() => {
+    // output of a code block sent with "Add to chat"
+    const token = PubSub.subscribe(CODE_OUTPUT_INSERT, (_, text: string) => {
+      form.setFieldValue(
+        "message",
+        form.values.message ? `${form.values.message}\n\n${text}` : text
+      )
+      textareaRef.current?.focus()
+    })
+    return () => {
+      PubSub.unsubscribe(token)
+    }
+  }, [form.values.message])
+
   const queryClient = useQueryClient()
 
   const { mutateAsync: sendMessage } = useMutation({
@@ -290,7 +323,7 @@ export const PlaygroundForm = ({ dropedFile }: Props) => {
         return
       }
 
-      if (webSearch) {
+      if (webSearch && !agentMode) {
         const simpleSearch = await getIsSimpleInternetSearch()
         if (!defaultEM && !simpleSearch) {
           form.setFieldError("message", t("formError.noEmbeddingModel"))
@@ -321,7 +354,7 @@ export const PlaygroundForm = ({ dropedFile }: Props) => {
     }
 
     if (
-      showMentions &&
+      (showMentions || showFileMentions) &&
       (e.key === "ArrowDown" ||
         e.key === "ArrowUp" ||
         e.key === "Enter" ||
@@ -432,7 +465,7 @@ export const PlaygroundForm = ({ dropedFile }: Props) => {
                     }
                     const defaultEM = await defaultEmbeddingModelForRag()
 
-                    if (webSearch) {
+                    if (webSearch && !agentMode) {
                       const simpleSearch = await getIsSimpleInternetSearch()
                       if (!defaultEM && !simpleSearch) {
                         form.setFieldError(
@@ -482,7 +515,7 @@ export const PlaygroundForm = ({ dropedFile }: Props) => {
                     type="file"
                     className="sr-only"
                     ref={fileInputRef}
-                    accept=".pdf,.doc,.docx,.txt,.csv"
+                    accept={`.pdf,.doc,.docx,.txt,.csv,${officeFileExtensions}`}
                     multiple={false}
                     onChange={onFileInputChange}
                   />
@@ -518,6 +551,12 @@ export const PlaygroundForm = ({ dropedFile }: Props) => {
                               textareaRef.current.selectionStart || 0
                             )
                           }
+                          if (selectedKnowledge && textareaRef.current) {
+                            handleFileMentionChange(
+                              e.target.value,
+                              textareaRef.current.selectionStart || 0
+                            )
+                          }
                         }}
                         onSelect={(e) => {
                           if (tabMentionsEnabled && textareaRef.current) {
@@ -530,15 +569,26 @@ export const PlaygroundForm = ({ dropedFile }: Props) => {
                       />
 
                       <MentionsDropdown
-                        show={showMentions}
+                        show={showMentions || showFileMentions}
                         tabs={filteredTabs}
+                        files={showFileMentions ? filteredFiles : undefined}
+                        onSelectFile={(file) =>
+                          insertFileMention(
+                            file,
+                            form.values.message,
+                            (value) => form.setFieldValue("message", value)
+                          )
+                        }
                         mentionPosition={mentionPosition}
                         onSelectTab={(tab) =>
                           insertMention(tab, form.values.message, (value) =>
                             form.setFieldValue("message", value)
                           )
                         }
-                        onClose={closeMentions}
+                        onClose={() => {
+                          closeMentions()
+                          closeFileMentions()
+                        }}
                         textareaRef={textareaRef}
                         refetchTabs={async () => {
                           await reloadTabs()
@@ -548,7 +598,18 @@ export const PlaygroundForm = ({ dropedFile }: Props) => {
                     </div>
                     <div className="mt-2 flex justify-between items-center">
                       <div className="flex gap-3">
-                        {!selectedKnowledge && (
+                        <Tooltip title={t("tooltip.agent")}>
+                          <div className="inline-flex items-center gap-2">
+                            <MousePointerClick className="h-5 w-5 dark:text-gray-300" />
+                            <Switch
+                              value={agentMode}
+                              onChange={(e) => setAgentMode(e)}
+                              checkedChildren={t("form.agent.on")}
+                              unCheckedChildren={t("form.agent.off")}
+                            />
+                          </div>
+                        </Tooltip>
+                        {!selectedKnowledge && !agentMode && (
                           <Tooltip title={t("tooltip.searchInternet")}>
                             <div className="inline-flex items-center gap-2">
                               <PiGlobe
@@ -563,6 +624,19 @@ export const PlaygroundForm = ({ dropedFile }: Props) => {
                             </div>
                           </Tooltip>
                         )}
+                        {!selectedKnowledge && !agentMode && webSearch && (
+                          <Tooltip title={t("tooltip.deepResearch")}>
+                            <div className="inline-flex items-center gap-2">
+                              <Telescope className="h-5 w-5 dark:text-gray-300" />
+                              <Switch
+                                value={deepResearch}
+                                onChange={(e) => setDeepResearch(e)}
+                                checkedChildren={t("form.deepResearch.on")}
+                                unCheckedChildren={t("form.deepResearch.off")}
+                              />
+                            </div>
+                          </Tooltip>
+                        )}
                         {defaultThinkingMode && isThinkingCapableModel(selectedModel) &&
                           (isGptOssModel(selectedModel) ? (
                             // For gpt-oss: Only show level selector (no on/off toggle)
@@ -686,6 +760,17 @@ export const PlaygroundForm = ({ dropedFile }: Props) => {
                             </button>
                           </Tooltip>
                         )}
+                        <McpSelect
+                          onInsert={(text) => {
+                            form.setFieldValue(
+                              "message",
+                              form.values.message
+                                ? `${form.values.message}\n\n${text}`
+                                : text
+                            )
+                            textAreaFocus()
+                          }}
+                        />
                         <KnowledgeSelect />
 
                         {!isSending ? (
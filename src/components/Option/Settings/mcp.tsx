@@ -0,0 +1,435 @@
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
+import {
+  Empty,
+  Form,
+  Input,
+  Modal,
+  Select,
+  Skeleton,
+  Switch,
+  Table,
+  Tabs,
+  Tag,
+  Tooltip,
+  message
+} from "antd"
+import { PlugZap, Pencil, Trash2 } from "lucide-react"
+import { useState } from "react"
+import { useTranslation } from "react-i18next"
+import {
+  addMcpServer,
+  deleteMcpServer,
+  getAllMcpServers,
+  setMcpServerEnabled,
+  updateMcpServer
+} from "@/db/dexie/mcp"
+import type { McpServer } from "@/db/dexie/types"
+import { closeMcpClient, getMcpClient } from "@/libs/mcp-client"
+
+type ServerForm = {
+  name: string
+  url: string
+  transport: McpServer["transport"]
+  headers?: { key: string; value: string }[]
+}
+
+const ServerCapabilities = ({ server }: { server: McpServer }) => {
+  const { t } = useTranslation("settings")
+  const { data, status, error } = useQuery({
+    queryKey: ["fetchMcpCapabilities", server.id, server.url],
+    queryFn: async () => {
+      const client = await getMcpClient(server)
+      // resources and prompts are optional, a server without them errors
+      const [tools, resources, prompts] = await Promise.all([
+        client.listTools(),
+        client.listResources().catch(() => []),
+        client.listPrompts().catch(() => [])
+      ])
+      return { tools, resources, prompts }
+    },
+    retry: false
+  })
+
+  if (status === "pending") {
+    return <Skeleton paragraph={{ rows: 4 }} active />
+  }
+  if (status === "error") {
+    return (
+      <p className="text-sm text-red-500">
+        {t("tools.mcp.connectError", { error: error.message })}
+      </p>
+    )
+  }
+
+  const list = (
+    items: { key: string; title: string; description?: string }[]
+  ) =>
+    items.length === 0 ? (
+      <Empty image={Empty.PRESENTED_IMAGE_SIMPLE} />
+    ) : (
+      <ul className="flex flex-col gap-2 max-h-96 overflow-y-auto">
+        {items.map((item) => (
+          <li key={item.key} className="text-sm">
+            <span className="font-mono font-medium">{item.title}</span>
+            {item.description && (
+              <p className="text-gray-500 dark:text-gray-400">
+                {item.description}
+              </p>
+            )}
+          </li>
+        ))}
+      </ul>
+    )
+
+  return (
+    <Tabs
+      items={[
+        {
+          key: "tools",
+          label: t("tools.mcp.capabilities.tools", {
+            count: data.tools.length
+          }),
+          children: list(
+            data.tools.map((tool) => ({
+              key: tool.name,
+              title: tool.name,
+              description: tool.description
+            }))
+          )
+        },
+        {
+          key: "resources",
+          label: t("tools.mcp.capabilities.resources", {
+            count: data.resources.length
+          }),
+          children: list(
+            data.resources.map((resource) => ({
+              key: resource.uri,
+              title: resource.name,
+              description: resource.description || resource.uri
+            }))
+          )
+        },
+        {
+          key: "prompts",
+          label: t("tools.mcp.capabilities.prompts", {
+            count: data.prompts.length
+          }),
+          children: list(
+            data.prompts.map((prompt) => ({
+              key: prompt.name,
+              title: prompt.name,
+              description: prompt.description
+            }))
+          )
+        }
+      ]}
+    />
+  )
+}
+
+export const McpSettings = () => {
+  const { t } = useTranslation("settings")
+  const queryClient = useQueryClient()
+  const [form] = Form.useForm()
+  const [open, setOpen] = useState(false)
+  const [editing, setEditing] = useState<McpServer | null>(null)
+  const [inspecting, setInspecting] = useState<McpServer | null>(null)
+
+  const { data: servers, isLoading } = useQuery({
+    queryKey: ["fetchMcpServers"],
+    queryFn: getAllMcpServers
+  })
+
+  const refresh = () => {
+    queryClient.invalidateQueries({
+      queryKey: ["fetchMcpServers"]
+    })
+    queryClient.invalidateQueries({
+      queryKey: ["fetchEnabledMcpServers"]
+    })
+  }
+
+  const { mutate: saveServer, isPending: isSaving } = useMutation({
+    mutationFn: async (values: ServerForm) => {
+      if (editing) {
+        closeMcpClient(editing.id)
+        await updateMcpServer({ id: editing.id, ...values })
+      } else {
+        await addMcpServer(values)
+      }
+    },
+    onSuccess: () => {
+      refresh()
+      setOpen(false)
+      setEditing(null)
+      form.resetFields()
+      message.success(t("tools.mcp.saved"))
+    }
+  })
+
+  const { mutate: toggleServer } = useMutation({
+    mutationFn: ({ id, enabled }: { id: string; enabled: boolean }) => {
+      if (!enabled) {
+        closeMcpClient(id)
+      }
+      return setMcpServerEnabled(id, enabled)
+    },
+    onSuccess: refresh
+  })
+
+  const { mutate: removeServer } = useMutation({
+    mutationFn: (id: string) => {
+      closeMcpClient(id)
+      return deleteMcpServer(id)
+    },
+    onSuccess: () => {
+      refresh()
+      message.success(t("tools.mcp.deleted"))
+    }
+  })
+
+  const openEditor = (server?: McpServer) => {
+    setEditing(server || null)
+    form.setFieldsValue(
+      server
+        ? { ...server, headers: server.headers || [] }
+        : { name: "", url: "", transport: "streamable-http", headers: [] }
+    )
+    setOpen(true)
+  }
+
+  return (
+    <div className="mt-10">
+      <div className="flex flex-col sm:flex-row sm:items-center sm:justify-between gap-3">
+        <div className="flex-1">
+          <h2 className="text-base font-semibold leading-7 text-gray-900 dark:text-white">
+            {t("tools.mcp.heading")}
+          </h2>
+          <p className="mt-1 text-sm leading-6 text-gray-600 dark:text-gray-400">
+            {t("tools.mcp.subheading")}
+          </p>
+        </div>
+        <button
+          onClick={() => openEditor()}
+          className="inline-flex items-center justify-center rounded-md border border-transparent bg-black px-3 py-2 text-sm font-medium leading-4 text-white shadow-sm hover:bg-gray-800 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-white dark:text-gray-800 dark:hover:bg-gray-100 dark:focus:ring-gray-500 dark:focus:ring-offset-gray-100 w-full sm:w-auto">
+          {t("tools.mcp.addBtn")}
+        </button>
+      </div>
+      <div className="border border-b border-gray-200 dark:border-gray-600 mt-3 mb-6"></div>
+
+      <Table
+        rowKey="id"
+        loading={isLoading}
+        dataSource={servers}
+        pagination={false}
+        columns={[
+          {
+            title: t("tools.mcp.table.name"),
+            dataIndex: "name",
+            key: "name",
+            ellipsis: true
+          },
+          {
+            title: t("tools.mcp.table.url"),
+            key: "url",
+            render: (_, server: McpServer) => (
+              <div className="flex flex-col gap-1">
+                <span className="truncate block" title={server.url}>
+                  {server.url}
+                </span>
+                <div>
+                  <Tag>{t(`tools.mcp.transport.${server.transport}`)}</Tag>
+                </div>
+              </div>
+            )
+          },
+          {
+            title: t("tools.mcp.table.enabled"),
+            key: "enabled",
+            width: 100,
+            render: (_, server: McpServer) => (
+              <Switch
+                checked={server.enabled}
+                onChange={(enabled) =>
+                  toggleServer({ id: server.id, enabled })
+                }
+              />
+            )
+          },
+          {
+            title: t("tools.mcp.table.actions"),
+            key: "actions",
+            width: 140,
+            render: (_, server: McpServer) => (
+              <div className="flex gap-2 sm:gap-4">
+                <Tooltip title={t("tools.mcp.inspect")}>
+                  <button
+                    className="text-gray-700 dark:text-gray-400 p-1"
+                    onClick={() => setInspecting(server)}>
+                    <PlugZap className="size-4" />
+                  </button>
+                </Tooltip>
+                <Tooltip title={t("tools.mcp.edit")}>
+                  <button
+                    className="text-gray-700 dark:text-gray-400 p-1"
+                    onClick={() => openEditor(server)}>
+                    <Pencil className="size-4" />
+                  </button>
+                </Tooltip>
+                <Tooltip title={t("tools.mcp.delete")}>
+                  <button
+                    className="text-red-500 dark:text-red-400 p-1"
+                    onClick={() => {
+                      if (
+                        confirm(
+                          t("tools.mcp.deleteConfirm", { name: server.name })
+                        )
+                      ) {
+                        removeServer(server.id)
+                      }
+                    }}>
+                    <Trash2 className="size-4" />
+                  </button>
+                </Tooltip>
+              </div>
+            )
+          }
+        ]}
+        locale={{
+          emptyText: t("tools.mcp.empty")
+        }}
+        bordered
+        scroll={{ x: 600 }}
+        className="[&_.ant-table]:text-sm"
+      />
+
+      <Modal
+        open={open}
+        title={editing ? t("tools.mcp.edit") : t("tools.mcp.addBtn")}
+        onCancel={() => {
+          setOpen(false)
+          setEditing(null)
+          form.resetFields()
+        }}
+        footer={null}>
+        <Form form={form} layout="vertical" onFinish={saveServer}>
+          <Form.Item
+            name="name"
+            label={t("tools.mcp.form.name.label")}
+            rules={[
+              {
+                required: true,
+                message: t("tools.mcp.form.name.required")
+              }
+            ]}>
+            <Input
+              size="large"
+              placeholder={t("tools.mcp.form.name.placeholder")}
+            />
+          </Form.Item>
+
+          <Form.Item
+            name="url"
+            label={t("tools.mcp.form.url.label")}
+            rules={[
+              {
+                required: true,
+                type: "url",
+                message: t("tools.mcp.form.url.required")
+              }
+            ]}>
+            <Input size="large" placeholder="http://localhost:3001/mcp" />
+          </Form.Item>
+
+          <Form.Item
+            name="transport"
+            label={t("tools.mcp.form.transport.label")}
+            help={t("tools.mcp.form.transport.help")}>
+            <Select
+              size="large"
+              options={[
+                {
+                  value: "streamable-http",
+                  label: t("tools.mcp.transport.streamable-http")
+                },
+                {
+                  value: "sse",
+                  label: t("tools.mcp.transport.sse")
+                }
+              ]}
+            />
+          </Form.Item>
+
+          <Form.List name="headers">
+            {(fields, { add, remove }) => (
+              <div className="flex flex-col mt-6">
+                <div className="flex justify-between items-center mb-3">
+                  <h3 className="text-sm font-semibold">
+                    {t("ollamaSettings.settings.advanced.headers.label")}
+                  </h3>
+                  <button
+                    type="button"
+                    className="dark:bg-white dark:text-black text-white bg-black px-2 py-1 text-xs rounded-md"
+                    onClick={() => add()}>
+                    {t("ollamaSettings.settings.advanced.headers.add")}
+                  </button>
+                </div>
+                {fields.map((field) => (
+                  <div key={field.key} className="flex items-end gap-2 mb-3">
+                    <Form.Item
+                      label={t(
+                        "ollamaSettings.settings.advanced.headers.key.label"
+                      )}
+                      name={[field.name, "key"]}
+                      className="flex-1 mb-0">
+                      <Input
+                        placeholder={t(
+                          "ollamaSettings.settings.advanced.headers.key.placeholder"
+                        )}
+                      />
+                    </Form.Item>
+                    <Form.Item
+                      label={t(
+                        "ollamaSettings.settings.advanced.headers.value.label"
+                      )}
+                      name={[field.name, "value"]}
+                      className="flex-1 mb-0">
+                      <Input.Password
+                        placeholder={t(
+                          "ollamaSettings.settings.advanced.headers.value.placeholder"
+                        )}
+                      />
+                    </Form.Item>
+                    <button
+                      type="button"
+                      onClick={() => remove(field.name)}
+                      className="text-red-500 dark:text-red-400 mb-2">
+                      <Trash2 className="size-4" />
+                    </button>
+                  </div>
+                ))}
+              </div>
+            )}
+          </Form.List>
+
+          <button
+            type="submit"
+            disabled={isSaving}
+            className="inline-flex justify-center w-full text-center mt-4 items-center rounded-md border border-transparent bg-black px-2 py-2 text-sm font-medium leading-4 text-white shadow-sm hover:bg-gray-800 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-white dark:text-gray-800 dark:hover:bg-gray-100 dark:focus:ring-gray-500 dark:focus:ring-offset-gray-100 disabled:opacity-50">
+            {t("tools.mcp.form.save")}
+          </button>
+        </Form>
+      </Modal>
+
+      <Modal
+        open={!!inspecting}
+        title={inspecting?.name}
+        onCancel={() => setInspecting(null)}
+        footer={null}
+        destroyOnClose>
+        {inspecting && <ServerCapabilities server={inspecting} />}
+      </Modal>
+    </div>
+  )
+}
@@ -2,9 +2,11 @@ import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
 import { Avatar, Form, Input, InputNumber, Select, Skeleton } from "antd"
 import { SaveButton } from "~/components/Common/SaveButton"
 import {
+  defaultChunkSizeUnit,
   defaultEmbeddingChunkOverlap,
   defaultEmbeddingChunkSize,
   defaultEmbeddingModelForRag,
+  defaultSemanticBreakpointPercentile,
   defaultSplittingStrategy,
   defaultSsplttingSeparator,
   getEmbeddingModels,
@@ -12,9 +14,52 @@ import {
 } from "~/services/ollama"
 import { SettingPrompt } from "./prompt"
 import { useTranslation } from "react-i18next"
-import { getNoOfRetrievedDocs, getTotalFilePerKB } from "@/services/app"
+import {
+  getMultiQueryCount,
+  getNoOfRetrievedDocs,
+  getTotalFilePerKB,
+  setMultiQueryCount
+} from "@/services/app"
 import { SidepanelRag } from "./sidepanel-rag"
+import { RerankSettings } from "./rerank-settings"
 import { ProviderIcons } from "@/components/Common/ProviderIcon"
+import { SPLITTING_STRATEGIES } from "@/utils/text-splitter"
+import {
+  getEmbeddingQuantization,
+  getPdfExtractionMode,
+  setEmbeddingQuantization,
+  setPdfExtractionMode,
+  type PdfExtractionMode
+} from "@/services/kb"
+import { runVectorStorageMigration } from "@/db/dexie/migration"
+import type { EmbeddingQuantization } from "@/db/dexie/types"
+import {
+  getAllKnowledge,
+  isKnowledgeMigrating,
+  setKnowledgeMigration
+} from "@/db/dexie/knowledge"
+import { KNOWLEDGE_REEMBED_QUEUE } from "@/queue"
+import PubSub from "pubsub-js"
+
+// re-embedding with the same model stores the embeddings at the current
+// quantization again
+const reEmbedAllKnowledge = async () => {
+  const knowledge = await getAllKnowledge("finished")
+  for (const kb of knowledge) {
+    if (isKnowledgeMigrating(kb)) {
+      continue
+    }
+    await setKnowledgeMigration(kb.id, {
+      model: kb.embedding_model,
+      status: "pending",
+      progress: 0
+    })
+    PubSub.publish(KNOWLEDGE_REEMBED_QUEUE, {
+      id: kb.id,
+      model: kb.embedding_model
+    })
+  }
+}
 
 export const RagSettings = () => {
   const { t } = useTranslation("settings")
@@ -33,7 +78,12 @@ export const RagSettings = () => {
         totalFilePerKB,
         noOfRetrievedDocs,
         splittingStrategy,
-        splittingSeparator
+        splittingSeparator,
+        chunkSizeUnit,
+        semanticBreakpoint,
+        multiQueryCount,
+        embeddingQuantization,
+        pdfExtractionMode
       ] = await Promise.all([
         getEmbeddingModels({ returnEmpty: true }),
         defaultEmbeddingChunkOverlap(),
@@ -42,7 +92,12 @@ export const RagSettings = () => {
         getTotalFilePerKB(),
         getNoOfRetrievedDocs(),
         defaultSplittingStrategy(),
-        defaultSsplttingSeparator()
+        defaultSsplttingSeparator(),
+        defaultChunkSizeUnit(),
+        defaultSemanticBreakpointPercentile(),
+        getMultiQueryCount(),
+        getEmbeddingQuantization(),
+        getPdfExtractionMode()
       ])
       return {
         models: allModels,
@@ -52,7 +107,12 @@ export const RagSettings = () => {
         totalFilePerKB,
         noOfRetrievedDocs,
         splittingStrategy,
-        splittingSeparator
+        splittingSeparator,
+        chunkSizeUnit,
+        semanticBreakpoint,
+        multiQueryCount,
+        embeddingQuantization,
+        pdfExtractionMode
       }
     }
   })
@@ -66,7 +126,25 @@ export const RagSettings = () => {
       noOfRetrievedDocs: number
       strategy: string
       separator: string
+      chunkSizeUnit: string
+      semanticBreakpoint: number
+      multiQueryCount: number
+      embeddingQuantization: EmbeddingQuantization
+      pdfExtractionMode: PdfExtractionMode
     }) => {
+      const previousQuantization = ollamaInfo?.embeddingQuantization ?? "none"
+      const quantization = data.embeddingQuantization ?? "none"
+      if (
+        previousQuantization === "none" &&
+        quantization !== "none" &&
+        !window.confirm(
+          t("rag.ragSettings.embeddingQuantization.confirmQuantize", {
+            quantization
+          })
+        )
+      ) {
+        return false
+      }
       await saveForRag(
         data.model,
         data.chunkSize,
@@ -74,8 +152,25 @@ export const RagSettings = () => {
         data.totalFilePerKB,
         data.noOfRetrievedDocs,
         data.strategy,
-        data.separator
+        data.separator,
+        data.chunkSizeUnit,
+        data.semanticBreakpoint
       )
+      await setMultiQueryCount(data.multiQueryCount ?? 0)
+      await setEmbeddingQuantization(quantization)
+      await setPdfExtractionMode(data.pdfExtractionMode ?? "text")
+      if (quantization !== previousQuantization) {
+        // stored vectors are converted to the new format
+        await runVectorStorageMigration()
+        if (
+          quantization === "none" &&
+          window.confirm(
+            t("rag.ragSettings.embeddingQuantization.confirmReEmbed")
+          )
+        ) {
+          await reEmbedAllKnowledge()
+        }
+      }
       return true
     },
     onSuccess: () => {
@@ -108,7 +203,12 @@ export const RagSettings = () => {
                   totalFilePerKB: data.totalFilePerKB,
                   noOfRetrievedDocs: data.noOfRetrievedDocs,
                   separator: data.splittingSeparator,
-                  strategy: data.splittingStrategy
+                  strategy: data.splittingStrategy,
+                  chunkSizeUnit: data.chunkSizeUnit,
+                  semanticBreakpoint: data.semanticBreakpoint,
+                  multiQueryCount: data.multiQueryCount,
+                  embeddingQuantization: data.embeddingQuantization,
+                  pdfExtractionMode: data.pdfExtractionMode
                 })
               }}
               initialValues={{
@@ -118,7 +218,12 @@ export const RagSettings = () => {
                 totalFilePerKB: ollamaInfo?.totalFilePerKB,
                 noOfRetrievedDocs: ollamaInfo?.noOfRetrievedDocs,
                 splittingStrategy: ollamaInfo?.splittingStrategy,
-                splittingSeparator: ollamaInfo?.splittingSeparator
+                splittingSeparator: ollamaInfo?.splittingSeparator,
+                chunkSizeUnit: ollamaInfo?.chunkSizeUnit,
+                semanticBreakpoint: ollamaInfo?.semanticBreakpoint,
+                multiQueryCount: ollamaInfo?.multiQueryCount,
+                embeddingQuantization: ollamaInfo?.embeddingQuantization,
+                pdfExtractionMode: ollamaInfo?.pdfExtractionMode
               }}>
               <Form.Item
                 name="defaultEM"
@@ -171,6 +276,7 @@ export const RagSettings = () => {
               <Form.Item
                 name="splittingStrategy"
                 label={t("rag.ragSettings.splittingStrategy.label")}
+                help={t("rag.ragSettings.splittingStrategy.help")}
                 rules={[
                   {
                     required: true,
@@ -182,17 +288,14 @@ export const RagSettings = () => {
                   showSearch
                   style={{ width: "100%" }}
                   className="mt-4"
-                  options={[
-                    "RecursiveCharacterTextSplitter",
-                    "CharacterTextSplitter"
-                  ].map((e) => ({
+                  options={SPLITTING_STRATEGIES.map((e) => ({
                     label: e,
                     value: e
                   }))}
                 />
               </Form.Item>
 
-              {splittingStrategy !== "RecursiveCharacterTextSplitter" && (
+              {splittingStrategy === "CharacterTextSplitter" && (
                 <Form.Item
                   name="splittingSeparator"
                   label={t("rag.ragSettings.splittingSeparator.label")}
@@ -212,6 +315,35 @@ export const RagSettings = () => {
                 </Form.Item>
               )}
 
+              {splittingStrategy === "SemanticTextSplitter" && (
+                <Form.Item
+                  name="semanticBreakpoint"
+                  label={t("rag.ragSettings.semanticBreakpoint.label")}
+                  help={t("rag.ragSettings.semanticBreakpoint.help")}
+                  rules={[
+                    {
+                      required: true,
+                      message: t("rag.ragSettings.semanticBreakpoint.required")
+                    }
+                  ]}>
+                  <InputNumber style={{ width: "100%" }} min={1} max={99} />
+                </Form.Item>
+              )}
+
+              <Form.Item
+                name="chunkSizeUnit"
+                label={t("rag.ragSettings.chunkSizeUnit.label")}
+                help={t("rag.ragSettings.chunkSizeUnit.help")}>
+                <Select
+                  size="large"
+                  style={{ width: "100%" }}
+                  options={["characters", "tokens"].map((e) => ({
+                    label: t(`rag.ragSettings.chunkSizeUnit.options.${e}`),
+                    value: e
+                  }))}
+                />
+              </Form.Item>
+
               <Form.Item
                 name="chunkSize"
                 label={t("rag.ragSettings.chunkSize.label")}
@@ -258,6 +390,43 @@ export const RagSettings = () => {
                 />
               </Form.Item>
 
+              <Form.Item
+                name="multiQueryCount"
+                label={t("rag.ragSettings.multiQueryCount.label")}
+                help={t("rag.ragSettings.multiQueryCount.help")}>
+                <InputNumber style={{ width: "100%" }} min={0} max={5} />
+              </Form.Item>
+
+              <Form.Item
+                name="embeddingQuantization"
+                label={t("rag.ragSettings.embeddingQuantization.label")}
+                help={t("rag.ragSettings.embeddingQuantization.help")}>
+                <Select
+                  size="large"
+                  style={{ width: "100%" }}
+                  options={["none", "int8", "binary"].map((e) => ({
+                    label: t(
+                      `rag.ragSettings.embeddingQuantization.options.${e}`
+                    ),
+                    value: e
+                  }))}
+                />
+              </Form.Item>
+
+              <Form.Item
+                name="pdfExtractionMode"
+                label={t("rag.ragSettings.pdfExtractionMode.label")}
+                help={t("rag.ragSettings.pdfExtractionMode.help")}>
+                <Select
+                  size="large"
+                  style={{ width: "100%" }}
+                  options={["text", "layout"].map((e) => ({
+                    label: t(`rag.ragSettings.pdfExtractionMode.options.${e}`),
+                    value: e
+                  }))}
+                />
+              </Form.Item>
+
               <Form.Item
                 name="totalFilePerKB"
                 label={t("rag.ragSettings.totalFilePerKB.label")}
@@ -280,6 +449,8 @@ export const RagSettings = () => {
             </Form>
           </div>
 
+          <RerankSettings />
+
           <SidepanelRag />
 
           <div>
@@ -0,0 +1,135 @@
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
+import { Form, Input, InputNumber, Select, Skeleton } from "antd"
+import { useTranslation } from "react-i18next"
+import { SaveButton } from "~/components/Common/SaveButton"
+import {
+  RERANKER_TYPES,
+  getRerankSettings,
+  setRerankSettings
+} from "@/services/rerank"
+
+export const RerankSettings = () => {
+  const { t } = useTranslation("settings")
+  const [form] = Form.useForm()
+  const rerankerType = Form.useWatch("type", form)
+  const queryClient = useQueryClient()
+
+  const { data, status } = useQuery({
+    queryKey: ["fetchRerankSettings"],
+    queryFn: getRerankSettings
+  })
+
+  const { mutate: saveRerank, isPending } = useMutation({
+    mutationFn: setRerankSettings,
+    onSuccess: () => {
+      queryClient.invalidateQueries({
+        queryKey: ["fetchRerankSettings"]
+      })
+    }
+  })
+
+  return (
+    <div>
+      <div>
+        <h2 className="text-base font-semibold leading-7 text-gray-900 dark:text-white">
+          {t("rag.rerank.label")}
+        </h2>
+        <div className="border border-b border-gray-200 dark:border-gray-600 mt-3 mb-6"></div>
+      </div>
+      {status === "pending" && <Skeleton paragraph={{ rows: 4 }} active />}
+      {status === "success" && (
+        <Form
+          form={form}
+          layout="vertical"
+          onFinish={(values) => {
+            // hidden fields are not part of `values`, keep their stored value
+            saveRerank({
+              ...data,
+              ...values,
+              thresholds: { ...data.thresholds, ...values.thresholds }
+            })
+          }}
+          initialValues={data}>
+          <Form.Item
+            name="type"
+            label={t("rag.rerank.type.label")}
+            help={t("rag.rerank.type.help")}>
+            <Select
+              size="large"
+              options={RERANKER_TYPES.map((type) => ({
+                label: t(`rag.rerank.type.options.${type}`),
+                value: type
+              }))}
+            />
+          </Form.Item>
+
+          {rerankerType === "endpoint" && (
+            <>
+              <Form.Item
+                name="baseUrl"
+                label={t("rag.rerank.baseUrl.label")}
+                help={t("rag.rerank.baseUrl.help")}
+                rules={[
+                  {
+                    required: true,
+                    message: t("rag.rerank.baseUrl.required")
+                  }
+                ]}>
+                <Input
+                  size="large"
+                  placeholder={t("rag.rerank.baseUrl.placeholder")}
+                />
+              </Form.Item>
+              <Form.Item name="model" label={t("rag.rerank.model.label")}>
+                <Input
+                  size="large"
+                  placeholder={t("rag.rerank.model.placeholder")}
+                />
+              </Form.Item>
+              <Form.Item name="apiKey" label={t("rag.rerank.apiKey.label")}>
+                <Input.Password
+                  size="large"
+                  placeholder={t("rag.rerank.apiKey.placeholder")}
+                />
+              </Form.Item>
+            </>
+          )}
+
+          {rerankerType !== "none" && (
+            <>
+              <Form.Item
+                // each reranker keeps its own threshold
+                name={["thresholds", rerankerType]}
+                label={t("rag.rerank.threshold.label")}
+                help={t("rag.rerank.threshold.help")}
+                rules={[
+                  {
+                    required: true,
+                    message: t("rag.rerank.threshold.required")
+                  }
+                ]}>
+                <InputNumber style={{ width: "100%" }} step={0.05} />
+              </Form.Item>
+              <Form.Item
+                name="topN"
+                label={t("rag.rerank.topN.label")}
+                help={t("rag.rerank.topN.help")}
+                rules={[
+                  {
+                    required: true,
+                    message: t("rag.rerank.topN.required")
+                  }
+                ]}>
+                <InputNumber style={{ width: "100%" }} min={1} />
+              </Form.Item>
+            </>
+          )}
+
+          <div className="flex justify-end">
+            <SaveButton disabled={isPending} btnType="submit" />
+          </div>
+        </Form>
+      )}
+    </div>
+  )
+}
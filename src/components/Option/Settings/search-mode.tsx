@@ -25,7 +25,10 @@ export const SearchModeSettings = () => {
       exaAPIKey: "",
       firecrawlAPIKey: "",
       ollamaSearchApiKey: "",
-      kagiApiKey: ""
+      kagiApiKey: "",
+      deepResearchProviders: [] as string[],
+      deepResearchMaxRounds: 3,
+      deepResearchMaxSources: 12
     }
   })
 
@@ -296,6 +299,66 @@ export const SearchModeSettings = () => {
             />
           </div>
         </div>
+
+        <div className="mb-5 pt-4">
+          <h2 className="text-base font-semibold leading-7 text-gray-900 dark:text-white">
+            {t("generalSettings.webSearch.deepResearch.heading")}
+          </h2>
+          <div className="border border-b border-gray-200 dark:border-gray-600 mt-3"></div>
+        </div>
+        <div className="flex sm:flex-row flex-col space-y-4 sm:space-y-0 sm:justify-between">
+          <span className="text-gray-700 dark:text-neutral-50 ">
+            {t("generalSettings.webSearch.deepResearch.providers.label")}
+          </span>
+          <div>
+            <Select
+              mode="multiple"
+              allowClear
+              placeholder={t(
+                "generalSettings.webSearch.deepResearch.providers.placeholder"
+              )}
+              className="w-full mt-4 sm:mt-0 sm:w-[200px]"
+              options={SUPPORTED_SEARCH_PROVIDERS}
+              filterOption={(input, option) =>
+                option!.label.toLowerCase().indexOf(input.toLowerCase()) >= 0 ||
+                option!.value.toLowerCase().indexOf(input.toLowerCase()) >= 0
+              }
+              {...form.getInputProps("deepResearchProviders")}
+            />
+          </div>
+        </div>
+        <div className="flex sm:flex-row flex-col space-y-4 sm:space-y-0 sm:justify-between">
+          <span className="text-gray-700 dark:text-neutral-50 ">
+            {t("generalSettings.webSearch.deepResearch.maxRounds.label")}
+          </span>
+          <div>
+            <InputNumber
+              min={1}
+              max={10}
+              placeholder={t(
+                "generalSettings.webSearch.deepResearch.maxRounds.placeholder"
+              )}
+              {...form.getInputProps("deepResearchMaxRounds")}
+              className="!w-full mt-4 sm:mt-0 sm:w-[200px]"
+            />
+          </div>
+        </div>
+        <div className="flex sm:flex-row flex-col space-y-4 sm:space-y-0 sm:justify-between">
+          <span className="text-gray-700 dark:text-neutral-50 ">
+            {t("generalSettings.webSearch.deepResearch.maxSources.label")}
+          </span>
+          <div>
+            <InputNumber
+              min={1}
+              max={50}
+              placeholder={t(
+                "generalSettings.webSearch.deepResearch.maxSources.placeholder"
+              )}
+              {...form.getInputProps("deepResearchMaxSources")}
+              className="!w-full mt-4 sm:mt-0 sm:w-[200px]"
+            />
+          </div>
+        </div>
         <div className="flex justify-end">
           <SaveButton btnType="submit" />
         </div>
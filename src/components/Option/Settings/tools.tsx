@@ -0,0 +1,154 @@
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
+import { Checkbox, Form, InputNumber, Skeleton, Switch } from "antd"
+import { useTranslation } from "react-i18next"
+import { SaveButton } from "~/components/Common/SaveButton"
+import {
+  filterEnabledTools,
+  getToolSettings,
+  OPT_IN_TOOLS,
+  setToolSettings
+} from "@/services/tools"
+import { BUILTIN_TOOLS } from "@/tools"
+
+export const ToolSettings = () => {
+  const { t } = useTranslation("settings")
+  const [form] = Form.useForm()
+  const enabled = Form.useWatch("enabled", form)
+  const queryClient = useQueryClient()
+
+  const { data, status } = useQuery({
+    queryKey: ["fetchToolSettings"],
+    queryFn: async () => {
+      const settings = await getToolSettings()
+      return {
+        enabled: settings.enabled,
+        maxRounds: settings.maxRounds,
+        agentMaxSteps: settings.agentMaxSteps,
+        pythonPackageDownload: settings.pythonPackageDownload,
+        // the form lists the enabled tools, the settings the disabled ones
+        // and the opt-in tools that were turned on
+        tools: await filterEnabledTools(
+          BUILTIN_TOOLS.map((tool) => tool.name)
+        )
+      }
+    }
+  })
+
+  const { mutate: saveTools, isPending } = useMutation({
+    mutationFn: async (values: {
+      enabled: boolean
+      maxRounds: number
+      agentMaxSteps: number
+      pythonPackageDownload: boolean
+      tools: string[]
+    }) =>
+      setToolSettings({
+        enabled: values.enabled,
+        maxRounds: values.maxRounds,
+        agentMaxSteps: values.agentMaxSteps,
+        pythonPackageDownload: values.pythonPackageDownload,
+        disabledTools: BUILTIN_TOOLS.map((tool) => tool.name).filter(
+          (name) =>
+            !OPT_IN_TOOLS.includes(name) && !values.tools.includes(name)
+        ),
+        enabledOptInTools: OPT_IN_TOOLS.filter((name) =>
+          values.tools.includes(name)
+        )
+      }),
+    onSuccess: () => {
+      queryClient.invalidateQueries({
+        queryKey: ["fetchToolSettings"]
+      })
+    }
+  })
+
+  return (
+    <div>
+      <div>
+        <h2 className="text-base font-semibold leading-7 text-gray-900 dark:text-white">
+          {t("tools.heading")}
+        </h2>
+        <div className="border border-b border-gray-200 dark:border-gray-600 mt-3 mb-6"></div>
+      </div>
+      {status === "pending" && <Skeleton paragraph={{ rows: 4 }} active />}
+      {status === "success" && (
+        <Form
+          form={form}
+          layout="vertical"
+          onFinish={(values) => {
+            // hidden fields are not part of `values`, keep their stored value
+            saveTools({ ...data, ...values })
+          }}
+          initialValues={data}>
+          <Form.Item
+            name="enabled"
+            label={t("tools.enabled.label")}
+            help={t("tools.enabled.help")}
+            valuePropName="checked">
+            <Switch />
+          </Form.Item>
+
+          {enabled && (
+            <>
+              <Form.Item
+                name="tools"
+                label={t("tools.builtin.label")}
+                help={t("tools.builtin.help")}>
+                <Checkbox.Group className="flex flex-col gap-2">
+                  {BUILTIN_TOOLS.map((tool) => (
+                    <Checkbox key={tool.name} value={tool.name}>
+                      <span className="font-medium">
+                        {t(`tools.builtin.options.${tool.name}.label`)}
+                      </span>
+                      <span className="text-gray-500 dark:text-gray-400">
+                        {" "}
+                        — {t(`tools.builtin.options.${tool.name}.help`)}
+                      </span>
+                    </Checkbox>
+                  ))}
+                </Checkbox.Group>
+              </Form.Item>
+              <Form.Item
+                name="maxRounds"
+                label={t("tools.maxRounds.label")}
+                help={t("tools.maxRounds.help")}
+                rules={[
+                  {
+                    required: true,
+                    message: t("tools.maxRounds.required")
+                  }
+                ]}>
+                <InputNumber style={{ width: "100%" }} min={1} max={20} />
+              </Form.Item>
+            </>
+          )}
+
+          <Form.Item
+            name="agentMaxSteps"
+            label={t("tools.agentMaxSteps.label")}
+            help={t("tools.agentMaxSteps.help")}
+            rules={[
+              {
+                required: true,
+                message: t("tools.agentMaxSteps.required")
+              }
+            ]}>
+            <InputNumber style={{ width: "100%" }} min={1} max={50} />
+          </Form.Item>
+
+          <Form.Item
+            name="pythonPackageDownload"
+            label={t("tools.pythonPackageDownload.label")}
+            help={t("tools.pythonPackageDownload.help")}
+            valuePropName="checked">
+            <Switch />
+          </Form.Item>
+
+          <div className="flex justify-end">
+            <SaveButton disabled={isPending} btnType="submit" />
+          </div>
+        </Form>
+      )}
+    </div>
+  )
+}
@@ -28,6 +28,8 @@ import { useFocusShortcuts } from "@/hooks/keyboard"
 import { isThinkingCapableModel, isGptOssModel } from "~/libs/model-utils"
 import { useStoreChatModelSettings } from "~/store/model"
 import { getVariable } from "@/utils/select-variable"
+import { CODE_OUTPUT_INSERT } from "@/libs/code-interpreter"
+import PubSub from "pubsub-js"
 
 type Props = {
   dropedFile: File | undefined
@@ -190,6 +192,20 @@ export const SidepanelForm = ({ dropedFile }: Props) => {
       }
     }
   }, [selectedQuickPrompt])
+
+  React.useEffect(() => {
+    // output of a code block sent with "Add to chat"
+    const token = PubSub.subscribe(CODE_OUTPUT_INSERT, (_, text: string) => {
+      form.setFieldValue(
+        "message",
+        form.values.message ? `${form.values.message}\n\n${text}` : text
+      )
+      textareaRef.current?.focus()
+    })
+    return () => {
+      PubSub.unsubscribe(token)
+    }
+  }, [form.values.message])
   const { mutateAsync: sendMessage, isPending: isSending } = useMutation({
     mutationFn: onSubmit,
     onSuccess: () => {
@@ -0,0 +1,46 @@
+import { db } from "./schema"
+import { AnnIndexData } from "./types"
+
+export class PageAssistAnnDb {
+  async getIndex(id: string): Promise<AnnIndexData | undefined> {
+    return await db.annIndexes.get(id)
+  }
+
+  async saveIndex(data: AnnIndexData): Promise<void> {
+    await db.annIndexes.put(data)
+  }
+
+  async deleteIndex(id: string): Promise<void> {
+    await db.annIndexes.delete(id)
+  }
+
+  // reads only the compound index key, so the graph itself is not loaded
+  async getIndexVersion(id: string): Promise<number | undefined> {
+    const keys = await db.annIndexes
+      .where("[id+version]")
+      .between([id, -Infinity], [id, Infinity])
+      .keys()
+    const key = keys[0] as [string, number] | undefined
+    return key?.[1]
+  }
+}
+
+export const getAnnIndex = async (id: string) => {
+  const annDb = new PageAssistAnnDb()
+  return annDb.getIndex(id)
+}
+
+export const saveAnnIndex = async (data: AnnIndexData) => {
+  const annDb = new PageAssistAnnDb()
+  return annDb.saveIndex(data)
+}
+
+export const deleteAnnIndex = async (id: string) => {
+  const annDb = new PageAssistAnnDb()
+  return annDb.deleteIndex(id)
+}
+
+export const getAnnIndexVersion = async (id: string) => {
+  const annDb = new PageAssistAnnDb()
+  return annDb.getIndexVersion(id)
+}
@@ -3,6 +3,7 @@ import {
   type Message as MessageType
 } from "~/store/option"
 import { ChatDocuments } from "@/models/ChatTypes"
+import type { ToolCallStep } from "@/tools/types"
 import {
   type HistoryInfo,
   type MessageHistory,
@@ -87,7 +88,8 @@ export const saveMessage = async ({
   modelName,
   reasoning_time_taken,
   time,
-  documents
+  documents,
+  tool_calls
 }: {
   history_id: string
   name: string
@@ -102,6 +104,7 @@ export const saveMessage = async ({
   modelName?: string
   modelImage?: string
   documents?: ChatDocuments
+  tool_calls?: ToolCallStep[]
 }) => {
   const id = generateID()
   let createdAt = Date.now()
@@ -122,7 +125,8 @@ export const saveMessage = async ({
     reasoning_time_taken,
     modelName,
     modelImage,
-    documents
+    documents,
+    toolCalls: tool_calls
   }
   const db = new PageAssistDatabase()
   await db.addMessage(message)
@@ -156,7 +160,8 @@ export const formatToMessage = (messages: MessageHistory): MessageType[] => {
       modelName: message?.modelName,
       modelImage: message?.modelImage,
       id: message.id,
-      documents: message?.documents
+      documents: message?.documents,
+      toolCalls: message?.toolCalls
     }
   })
 }
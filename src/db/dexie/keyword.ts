@@ -0,0 +1,253 @@
+import {
+  createBM25Document,
+  createBM25Stats,
+  updateBM25Stats
+} from "@/libs/bm25"
+import { db } from "./schema"
+import {
+  KeywordChunk,
+  KeywordDocument,
+  KeywordIndexData,
+  KeywordStats
+} from "./types"
+
+export type KeywordIndex = {
+  documents: KeywordDocument[]
+  stats: KeywordStats
+}
+
+const toChunks = (id: string, documents: KeywordDocument[]): KeywordChunk[] =>
+  documents.map((document) => ({ ...document, keyword_id: id }))
+
+const toStats = (id: string, documents: KeywordDocument[]): KeywordStats => ({
+  id,
+  ...createBM25Stats(documents)
+})
+
+// chunks without a file can not be looked up by the compound index
+const getFileChunks = (id: string, file_id?: string) =>
+  file_id === undefined
+    ? db.keywordChunks
+        .where("keyword_id")
+        .equals(id)
+        .filter((chunk) => chunk.file_id === undefined)
+    : db.keywordChunks.where("[keyword_id+file_id]").equals([id, file_id])
+
+export class PageAssistKeywordDb {
+  /**
+   * Moves an index stored as a single row into chunk rows and statistics.
+   */
+  async migrateLegacyIndex(id: string): Promise<void> {
+    if ((await db.keywordIndexes.where("id").equals(id).count()) === 0) {
+      return
+    }
+    await db.transaction(
+      "rw",
+      db.keywordIndexes,
+      db.keywordChunks,
+      db.keywordStats,
+      async () => {
+        const legacy = await db.keywordIndexes.get(id)
+        if (!legacy) {
+          return
+        }
+        await db.keywordChunks.where("keyword_id").equals(id).delete()
+        await db.keywordChunks.bulkAdd(toChunks(id, legacy.documents))
+        await db.keywordStats.put(toStats(id, legacy.documents))
+        await db.keywordIndexes.delete(id)
+      }
+    )
+  }
+
+  private async updateStats(
+    id: string,
+    added: KeywordDocument[],
+    removed: KeywordDocument[]
+  ): Promise<void> {
+    const stats = (await db.keywordStats.get(id)) || toStats(id, [])
+    updateBM25Stats(stats, added)
+    updateBM25Stats(stats, removed, -1)
+    await db.keywordStats.put(stats)
+  }
+
+  /**
+   * Adds documents to an index. An index that was never built, for a
+   * knowledge base embedded before keyword search existed, is first built
+   * from all the stored chunks of its vector set, so older chunks are not
+   * left out.
+   */
+  async insertDocuments(id: string, documents: KeywordDocument[]): Promise<void> {
+    await this.migrateLegacyIndex(id)
+    await db.transaction(
+      "rw",
+      db.vectorChunks,
+      db.keywordChunks,
+      db.keywordStats,
+      async () => {
+        if (await db.keywordStats.get(id)) {
+          await db.keywordChunks.bulkAdd(toChunks(id, documents))
+          await this.updateStats(id, documents, [])
+          return
+        }
+        const stored: KeywordDocument[] = (
+          await db.vectorChunks
+            .where("vector_id")
+            .equals(id.replace(/^keyword:/, "vector:"))
+            .toArray()
+        ).map((chunk) => ({
+          file_id: chunk.file_id,
+          content: chunk.content,
+          metadata: chunk.metadata,
+          ...createBM25Document(chunk.content)
+        }))
+        // the new chunks are usually stored as vectors already
+        const keys = new Set(
+          stored.map((document) => `${document.file_id}\n${document.content}`)
+        )
+        const all = stored.concat(
+          documents.filter(
+            (document) => !keys.has(`${document.file_id}\n${document.content}`)
+          )
+        )
+        await db.keywordChunks.where("keyword_id").equals(id).delete()
+        await db.keywordChunks.bulkAdd(toChunks(id, all))
+        await db.keywordStats.put(toStats(id, all))
+      }
+    )
+  }
+
+  async deleteIndex(id: string): Promise<void> {
+    await db.transaction(
+      "rw",
+      db.keywordIndexes,
+      db.keywordChunks,
+      db.keywordStats,
+      async () => {
+        await db.keywordChunks.where("keyword_id").equals(id).delete()
+        await db.keywordStats.delete(id)
+        await db.keywordIndexes.delete(id)
+      }
+    )
+  }
+
+  async deleteDocumentsByFileId(id: string, file_id: string): Promise<void> {
+    await this.migrateLegacyIndex(id)
+    await db.transaction("rw", db.keywordChunks, db.keywordStats, async () => {
+      const chunks = await getFileChunks(id, file_id).toArray()
+      if (chunks.length === 0) {
+        return
+      }
+      await db.keywordChunks.bulkDelete(chunks.map((chunk) => chunk.id!))
+      await this.updateStats(id, [], chunks)
+    })
+  }
+
+  /**
+   * Replaces the document of a chunk, matched by file and content, or
+   * removes it when no replacement is given.
+   */
+  async replaceDocument(
+    id: string,
+    match: { file_id?: string; content: string },
+    document?: KeywordDocument
+  ): Promise<void> {
+    await this.migrateLegacyIndex(id)
+    await db.transaction("rw", db.keywordChunks, db.keywordStats, async () => {
+      const chunk = await getFileChunks(id, match.file_id)
+        .filter((chunk) => chunk.content === match.content)
+        .first()
+      if (!chunk) {
+        return
+      }
+      if (document) {
+        await db.keywordChunks.put({
+          ...document,
+          id: chunk.id,
+          keyword_id: id
+        })
+        await this.updateStats(id, [document], [chunk])
+      } else {
+        await db.keywordChunks.delete(chunk.id!)
+        await this.updateStats(id, [], [chunk])
+      }
+    })
+  }
+
+  /**
+   * The chunks and statistics of an index, undefined when it was never
+   * built.
+   */
+  async getIndex(id: string): Promise<KeywordIndex | undefined> {
+    await this.migrateLegacyIndex(id)
+    const [documents, stats] = await Promise.all([
+      db.keywordChunks.where("keyword_id").equals(id).toArray(),
+      db.keywordStats.get(id)
+    ])
+    if (!stats && documents.length === 0) {
+      return undefined
+    }
+    return { documents, stats: stats || toStats(id, documents) }
+  }
+
+  async saveIndex(data: KeywordIndexData): Promise<void> {
+    await db.transaction(
+      "rw",
+      db.keywordIndexes,
+      db.keywordChunks,
+      db.keywordStats,
+      async () => {
+        await db.keywordChunks.where("keyword_id").equals(data.id).delete()
+        await db.keywordChunks.bulkAdd(toChunks(data.id, data.documents))
+        await db.keywordStats.put(toStats(data.id, data.documents))
+        await db.keywordIndexes.delete(data.id)
+      }
+    )
+  }
+}
+
+export const migrateLegacyKeywordIndex = async (id: string): Promise<void> => {
+  const db = new PageAssistKeywordDb()
+  return db.migrateLegacyIndex(id)
+}
+
+export const insertKeywordDocuments = async (
+  id: string,
+  documents: KeywordDocument[]
+): Promise<void> => {
+  const db = new PageAssistKeywordDb()
+  return db.insertDocuments(id, documents)
+}
+
+export const getKeywordIndex = async (
+  id: string
+): Promise<KeywordIndex | undefined> => {
+  const db = new PageAssistKeywordDb()
+  return db.getIndex(id)
+}
+
+export const saveKeywordIndex = async (data: KeywordIndexData) => {
+  const db = new PageAssistKeywordDb()
+  return db.saveIndex(data)
+}
+
+export const deleteKeywordIndex = async (id: string): Promise<void> => {
+  const db = new PageAssistKeywordDb()
+  return db.deleteIndex(id)
+}
+
+export const deleteKeywordDocumentsByFileId = async (
+  id: string,
+  file_id: string
+): Promise<void> => {
+  const db = new PageAssistKeywordDb()
+  return db.deleteDocumentsByFileId(id, file_id)
+}
+
+export const replaceKeywordDocument = async (
+  id: string,
+  match: { file_id?: string; content: string },
+  document?: KeywordDocument
+): Promise<void> => {
+  const db = new PageAssistKeywordDb()
+  return db.replaceDocument(id, match, document)
+}
@@ -1,6 +1,21 @@
 import { db } from "./schema"
-import { Knowledge, Source } from "./types"
+import {
+  EvaluationQuestion,
+  EvaluationRun,
+  Knowledge,
+  KnowledgeIndexStats,
+  KnowledgeMigration,
+  KnowledgeRefresh,
+  RetrievalMode,
+  Source
+} from "./types"
+import type { SplitterSettings } from "@/utils/text-splitter"
 import { deleteVector, deleteVectorByFileId } from "./vector"
+import {
+  deleteKeywordDocumentsByFileId,
+  deleteKeywordIndex,
+  migrateLegacyKeywordIndex
+} from "./keyword"
 
 export const generateID = () => {
   return "pa_knowledge_xxxx-xxxx-xxx-xxxx".replace(/[x]/g, () => {
@@ -40,6 +55,93 @@ export class PageAssistKnowledge {
     }
   }
 
+  async setMigration(
+    id: string,
+    migration: KnowledgeMigration | undefined
+  ): Promise<void> {
+    await db.knowledge.update(id, { migration })
+  }
+
+  /**
+   * Replaces the live vectors of a knowledge base with its shadow set and
+   * switches the embedding model in a single transaction. `splitterSettings`
+   * is recorded when the shadow set was split again.
+   */
+  async swapEmbeddingModel(
+    id: string,
+    embedding_model: string,
+    splitterSettings?: SplitterSettings
+  ): Promise<void> {
+    await migrateLegacyKeywordIndex(`keyword:${id}:shadow`)
+    await db.transaction(
+      "rw",
+      db.knowledge,
+      db.vectors,
+      db.vectorChunks,
+      db.parentChunks,
+      db.keywordIndexes,
+      db.keywordChunks,
+      db.keywordStats,
+      db.annIndexes,
+      async () => {
+        const knowledge = await db.knowledge.get(id)
+        if (!knowledge) {
+          throw new Error(`No knowledge found for ${id}`)
+        }
+
+        // an empty shadow set is valid for a knowledge base without chunks
+        await db.vectorChunks.where("vector_id").equals(`vector:${id}`).delete()
+        await db.vectors.delete(`vector:${id}`)
+        await db.vectorChunks
+          .where("vector_id")
+          .equals(`vector:${id}:shadow`)
+          .modify({ vector_id: `vector:${id}` })
+        await db.parentChunks.where("vector_id").equals(`vector:${id}`).delete()
+        await db.parentChunks
+          .where("vector_id")
+          .equals(`vector:${id}:shadow`)
+          .modify({ vector_id: `vector:${id}` })
+
+        await db.keywordIndexes.delete(`keyword:${id}`)
+        await db.keywordChunks
+          .where("keyword_id")
+          .equals(`keyword:${id}`)
+          .delete()
+        await db.keywordChunks
+          .where("keyword_id")
+          .equals(`keyword:${id}:shadow`)
+          .modify({ keyword_id: `keyword:${id}` })
+        const shadowStats = await db.keywordStats.get(`keyword:${id}:shadow`)
+        if (shadowStats) {
+          await db.keywordStats.put({ ...shadowStats, id: `keyword:${id}` })
+          await db.keywordStats.delete(`keyword:${id}:shadow`)
+        } else {
+          await db.keywordStats.delete(`keyword:${id}`)
+        }
+
+        // shadow chunks keep their ids, so the shadow graph stays valid
+        const shadowAnn = await db.annIndexes.get(`vector:${id}:shadow`)
+        if (shadowAnn) {
+          await db.annIndexes.put({
+            ...shadowAnn,
+            id: `vector:${id}`,
+            version: Date.now()
+          })
+          await db.annIndexes.delete(`vector:${id}:shadow`)
+        } else {
+          await db.annIndexes.delete(`vector:${id}`)
+        }
+
+        await db.knowledge.put({
+          ...knowledge,
+          embedding_model,
+          migration: undefined,
+          splitterSettings: splitterSettings ?? knowledge.splitterSettings
+        })
+      }
+    )
+  }
+
   async importDataV2(
     data: Knowledge[],
     options: {
@@ -121,6 +223,10 @@ export const importKnowledgeV2 = async (
     console.warn(e)
   }
 }
+
+// retrieval mode of new knowledge bases and of those saved without one
+export const DEFAULT_RETRIEVAL_MODE: RetrievalMode = "hybrid"
+
 // Helper functions that match the original API
 export const createKnowledge = async ({
   source,
@@ -137,10 +243,11 @@ export const createKnowledge = async ({
     id,
     title,
     db_type: "knowledge",
-    source,
+    source: source.map((s) => ({ ...s, updatedAt: Date.now() })),
     status: "pending",
     knownledge: {},
     embedding_model,
+    retrievalMode: DEFAULT_RETRIEVAL_MODE,
     createdAt: Date.now()
   }
   await db.create(knowledge)
@@ -157,10 +264,10 @@ export const updateKnowledgeStatus = async (id: string, status: string) => {
   const knowledge = await db.getById(id)
   if (knowledge) {
     if (status === "finished") {
-      knowledge.source = knowledge?.source?.map((e) => ({
-        ...e,
-        content: undefined
-      }))
+      // failed sources are indexed again with the next update
+      knowledge.source = knowledge?.source?.map((e) =>
+        e.error ? e : { ...e, content: undefined }
+      )
     }
     await db.update({
       ...knowledge,
@@ -169,14 +276,236 @@ export const updateKnowledgeStatus = async (id: string, status: string) => {
   }
 }
 
+// A source with the same filename replaces the existing one so that only
+// its changed chunks get re-embedded
+const mergeSources = (existing: Source[], source: Source[]): Source[] => {
+  const isSameFile = (a: Source, b: Source) =>
+    !!a.filename && a.filename === b.filename
+  const updatedSources = existing.map((e) => {
+    const replacement = source.find((s) => isSameFile(s, e))
+    return replacement
+      ? { ...e, ...replacement, source_id: e.source_id }
+      : e
+  })
+  const newSources = source.filter(
+    (s) => !existing.some((e) => isSameFile(s, e))
+  )
+  return [...updatedSources, ...newSources]
+}
+
+/**
+ * Re-embedding swaps in a copy of the sources taken when it started, so
+ * sources can not be added or deleted until it is done.
+ */
+export const isKnowledgeMigrating = (knowledge?: Knowledge) =>
+  knowledge?.migration?.status === "pending" ||
+  knowledge?.migration?.status === "processing"
+
+const assertNotMigrating = (knowledge?: Knowledge) => {
+  if (isKnowledgeMigrating(knowledge)) {
+    throw new Error(
+      `Knowledge ${knowledge!.id} is being re-embedded, try again when it is done`
+    )
+  }
+}
+
 export const addNewSources = async (id: string, source: Source[]) => {
+  const db = new PageAssistKnowledge()
+  assertNotMigrating(await db.getById(id))
   await updateKnowledgeStatus(id, "processing")
+  const knowledge = await db.getById(id)
+  if (knowledge) {
+    source = source.map((s) => ({ ...s, updatedAt: Date.now() }))
+    await db.update({
+      ...knowledge,
+      source: mergeSources(knowledge.source, source)
+    })
+  }
+}
+
+/**
+ * Replaces a crawl source with the pages it found. Pages that are already
+ * in the knowledge base (same url) keep their source id.
+ */
+export const replaceCrawlSource = async (
+  id: string,
+  source_id: string,
+  pages: Source[]
+): Promise<Source[]> => {
+  const db = new PageAssistKnowledge()
+  const knowledge = await db.getById(id)
+  if (!knowledge) {
+    return []
+  }
+  const source = mergeSources(
+    knowledge.source.filter((s) => s.source_id !== source_id),
+    pages.map((s) => ({ ...s, updatedAt: Date.now() }))
+  )
+  await db.update({
+    ...knowledge,
+    source
+  })
+  return source
+}
+
+export const updateSourceContentHash = async (
+  id: string,
+  source_id: string,
+  content_hash: string,
+  emptyPages?: number[]
+) => {
   const db = new PageAssistKnowledge()
   const knowledge = await db.getById(id)
   if (knowledge) {
     await db.update({
       ...knowledge,
-      source: [...knowledge.source, ...source]
+      source: knowledge.source.map((s) =>
+        s.source_id === source_id
+          ? {
+              ...s,
+              content_hash,
+              emptyPages: emptyPages?.length ? emptyPages : undefined,
+              error: undefined
+            }
+          : s
+      )
+    })
+  }
+}
+
+export const setSourceError = async (
+  id: string,
+  source_id: string,
+  error: string
+) => {
+  const db = new PageAssistKnowledge()
+  const knowledge = await db.getById(id)
+  if (knowledge) {
+    await db.update({
+      ...knowledge,
+      source: knowledge.source.map((s) =>
+        s.source_id === source_id ? { ...s, error } : s
+      )
+    })
+  }
+}
+
+export const setKnowledgeMigration = async (
+  id: string,
+  migration: KnowledgeMigration | undefined
+) => {
+  const db = new PageAssistKnowledge()
+  return db.setMigration(id, migration)
+}
+
+export const swapKnowledgeEmbeddingModel = async (
+  id: string,
+  embedding_model: string,
+  splitterSettings?: SplitterSettings
+) => {
+  const db = new PageAssistKnowledge()
+  return db.swapEmbeddingModel(id, embedding_model, splitterSettings)
+}
+
+export const setKnowledgeRefresh = async (
+  id: string,
+  refresh: Partial<KnowledgeRefresh>
+) => {
+  const db = new PageAssistKnowledge()
+  const knowledge = await db.getById(id)
+  if (knowledge) {
+    await db.update({
+      ...knowledge,
+      refresh: {
+        interval: 0,
+        ...knowledge.refresh,
+        ...refresh
+      }
+    })
+  }
+}
+
+/**
+ * Marks a queued refresh as being indexed. Returns false when another page
+ * already took it.
+ */
+export const claimQueuedRefresh = async (id: string): Promise<boolean> => {
+  return db.transaction("rw", db.knowledge, async () => {
+    const knowledge = await db.knowledge.get(id)
+    if (knowledge?.refresh?.status !== "queued") {
+      return false
+    }
+    await db.knowledge.update(id, {
+      refresh: { ...knowledge.refresh, status: "processing" }
+    })
+    return true
+  })
+}
+
+// older runs are dropped so the knowledge record stays small
+const MAX_EVALUATION_RUNS = 20
+
+export const setEvaluationQuestions = async (
+  id: string,
+  questions: EvaluationQuestion[]
+) => {
+  const db = new PageAssistKnowledge()
+  const knowledge = await db.getById(id)
+  if (knowledge) {
+    await db.update({
+      ...knowledge,
+      evaluation: {
+        runs: [],
+        ...knowledge.evaluation,
+        questions
+      }
+    })
+  }
+}
+
+export const addEvaluationRun = async (id: string, run: EvaluationRun) => {
+  const db = new PageAssistKnowledge()
+  const knowledge = await db.getById(id)
+  if (knowledge) {
+    await db.update({
+      ...knowledge,
+      evaluation: {
+        questions: knowledge.evaluation?.questions || [],
+        runs: [run, ...(knowledge.evaluation?.runs || [])].slice(
+          0,
+          MAX_EVALUATION_RUNS
+        )
+      }
+    })
+  }
+}
+
+export const deleteEvaluationRun = async (id: string, runId: string) => {
+  const db = new PageAssistKnowledge()
+  const knowledge = await db.getById(id)
+  if (knowledge?.evaluation) {
+    await db.update({
+      ...knowledge,
+      evaluation: {
+        ...knowledge.evaluation,
+        runs: knowledge.evaluation.runs.filter((run) => run.id !== runId)
+      }
+    })
+  }
+}
+
+export const updateKnowledgeIndexStats = async (
+  id: string,
+  indexStats: KnowledgeIndexStats,
+  splitterSettings?: SplitterSettings
+) => {
+  const db = new PageAssistKnowledge()
+  const knowledge = await db.getById(id)
+  if (knowledge) {
+    await db.update({
+      ...knowledge,
+      indexStats,
+      splitterSettings: splitterSettings ?? knowledge.splitterSettings
     })
   }
 }
@@ -217,12 +546,17 @@ export const deleteKnowledge = async (id: string) => {
   const db = new PageAssistKnowledge()
   await db.delete(id)
   await deleteVector(`vector:${id}`)
+  await deleteKeywordIndex(`keyword:${id}`)
+  await deleteVector(`vector:${id}:shadow`)
+  await deleteKeywordIndex(`keyword:${id}:shadow`)
 }
 
 export const deleteSource = async (id: string, source_id: string) => {
   const db = new PageAssistKnowledge()
+  assertNotMigrating(await db.getById(id))
   await db.deleteSource(id, source_id)
   await deleteVectorByFileId(`vector:${id}`, source_id)
+  await deleteKeywordDocumentsByFileId(`keyword:${id}`, source_id)
 }
 
 export const exportKnowledge = async () => {
@@ -238,25 +572,54 @@ export const importKnowledge = async (data: Knowledge[]) => {
   }
 }
 
+/**
+ * Saves the settings of a knowledge base. Returns true when the passage
+ * size or OCR changed, the knowledge base is then pending and has to be
+ * indexed again.
+ */
 export const updateKnowledgebase = async ({
   id,
   title,
   systemPrompt,
-  followupPrompt
+  followupPrompt,
+  retrievalMode,
+  parentChunkSize,
+  refreshInterval,
+  ocr
 }: {
   id: string
   title: string
   systemPrompt?: string
   followupPrompt?: string
+  retrievalMode?: RetrievalMode
+  parentChunkSize?: number
+  refreshInterval?: number
+  ocr?: boolean
 }) => {
   const kb = new PageAssistKnowledge()
   const knowledgeBase = await kb.getById(id)
-  if (knowledgeBase) {
-    await kb.update({
-      ...knowledgeBase,
-      title,
-      systemPrompt,
-      followupPrompt,
-    })
+  if (!knowledgeBase) {
+    return false
   }
+  const reindex =
+    (knowledgeBase.parentChunkSize || 0) !== (parentChunkSize || 0) ||
+    (knowledgeBase.ocr !== false) !== (ocr !== false)
+  if (reindex) {
+    assertNotMigrating(knowledgeBase)
+  }
+  await kb.update({
+    ...knowledgeBase,
+    status: reindex ? "pending" : knowledgeBase.status,
+    title,
+    systemPrompt,
+    followupPrompt,
+    retrievalMode,
+    parentChunkSize,
+    refresh: {
+      ...knowledgeBase.refresh,
+      interval: refreshInterval || 0
+    },
+    ocr
+  })
+  return reindex
 }
@@ -0,0 +1,146 @@
+import { db } from "./schema"
+import { McpServer, McpServers } from "./types"
+
+export const generateID = () => {
+  return "mcp-xxxx-xxx-xxxx".replace(/[x]/g, () => {
+    const r = Math.floor(Math.random() * 16)
+    return r.toString(16)
+  })
+}
+
+export class McpServerDb {
+  getAll = async (): Promise<McpServers> => {
+    return await db.mcpServers.orderBy("createdAt").reverse().toArray()
+  }
+
+  create = async (server: McpServer): Promise<void> => {
+    await db.mcpServers.add(server)
+  }
+
+  getById = async (id: string): Promise<McpServer> => {
+    return await db.mcpServers.get(id)
+  }
+
+  update = async (server: McpServer): Promise<void> => {
+    await db.mcpServers.put(server)
+  }
+
+  delete = async (id: string): Promise<void> => {
+    return await db.mcpServers.delete(id)
+  }
+
+  async importDataV2(
+    data: McpServers,
+    options: {
+      replaceExisting?: boolean
+    } = {}
+  ): Promise<void> {
+    const { replaceExisting = false } = options
+
+    for (const server of data) {
+      const existing = await this.getById(server.id)
+      if (existing && !replaceExisting) {
+        continue
+      }
+      await this.update(server)
+    }
+  }
+}
+
+export const addMcpServer = async ({
+  name,
+  url,
+  transport,
+  headers
+}: {
+  name: string
+  url: string
+  transport: McpServer["transport"]
+  headers?: { key: string; value: string }[]
+}) => {
+  const mcpDb = new McpServerDb()
+  const id = generateID()
+  await mcpDb.create({
+    id,
+    name,
+    url: url.trim(),
+    transport,
+    headers: headers || [],
+    enabled: true,
+    createdAt: Date.now(),
+    db_type: "mcp_server"
+  })
+  return id
+}
+
+export const getAllMcpServers = async () => {
+  try {
+    const mcpDb = new McpServerDb()
+    const servers = await mcpDb.getAll()
+    return servers.filter((server) => server?.db_type === "mcp_server")
+  } catch (e) {
+    console.error(e)
+    return []
+  }
+}
+
+export const getEnabledMcpServers = async () => {
+  const servers = await getAllMcpServers()
+  return servers.filter((server) => server.enabled)
+}
+
+export const getMcpServerById = async (id: string) => {
+  const mcpDb = new McpServerDb()
+  return await mcpDb.getById(id)
+}
+
+export const updateMcpServer = async ({
+  id,
+  name,
+  url,
+  transport,
+  headers
+}: {
+  id: string
+  name: string
+  url: string
+  transport: McpServer["transport"]
+  headers?: { key: string; value: string }[]
+}) => {
+  const mcpDb = new McpServerDb()
+  const oldData = await mcpDb.getById(id)
+  const server: McpServer = {
+    ...oldData,
+    id,
+    name,
+    url: url.trim(),
+    transport,
+    headers: headers || []
+  }
+  await mcpDb.update(server)
+  return server
+}
+
+export const setMcpServerEnabled = async (id: string, enabled: boolean) => {
+  await db.mcpServers.update(id, { enabled })
+}
+
+export const deleteMcpServer = async (id: string) => {
+  const mcpDb = new McpServerDb()
+  await mcpDb.delete(id)
+}
+
+export const exportMcpServers = async () => {
+  const mcpDb = new McpServerDb()
+  return await mcpDb.getAll()
+}
+
+export const importMcpServersV2 = async (
+  data: McpServers,
+  options: {
+    replaceExisting?: boolean
+  } = {}
+) => {
+  const mcpDb = new McpServerDb()
+  await mcpDb.importDataV2(data, options)
+}
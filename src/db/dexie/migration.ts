@@ -5,6 +5,7 @@ import { getAllVector,  } from "../vector"
 import { PageAssistDatabase as DexieDB, } from "./chat"
 import { PageAssistKnowledge as DexieDBK } from "./knowledge"
 import { PageAssistVectorDb as DexieDBV } from "./vector"
+import { PageAssistKeywordDb } from "./keyword"
 import { OpenAIModelDb as DexieDBOAI } from "./openai"
 import {ModelNickname as DexieDBNick} from "./nickname"
 import { ModelDb as DexieDBM } from "./models"
@@ -12,6 +13,17 @@ import { getAllOpenAIConfig } from "../openai"
 import { getAllModelsExT } from "../models"
 import { getAllModelNicknamesMig } from "../nickname"
 import { notification } from "antd"
+import { Storage } from "@plasmohq/storage"
+import { db } from "./schema"
+import type { EmbeddingQuantization } from "./types"
+import { getEmbeddingQuantization } from "@/services/kb"
+import {
+  decodeEmbedding,
+  encodeEmbedding,
+  getQuantization
+} from "@/libs/quantization"
+
+const storage = new Storage()
 
 export class DatabaseMigration {
   private chromeDB: ChromeDB
@@ -249,6 +261,139 @@ export class DatabaseMigration {
     }
   }
 
+  /**
+   * Moves vector sets stored as a single row into one row per chunk. Chunks
+   * keep their order, so only the ANN graphs need to be rebuilt.
+   */
+  async migrateVectorChunks(): Promise<{
+    success: boolean
+    migratedCount: number
+    errors: string[]
+  }> {
+    const errors: string[] = []
+    let migratedCount = 0
+
+    // rows are moved one at a time, a vector set can be large
+    const ids = (await db.vectors.toCollection().primaryKeys()) as string[]
+    for (const id of ids) {
+      try {
+        await db.transaction(
+          "rw",
+          db.vectors,
+          db.vectorChunks,
+          db.annIndexes,
+          async () => {
+            const data = await db.vectors.get(id)
+            if (!data) {
+              return
+            }
+            await db.vectorChunks.bulkAdd(
+              data.vectors.map((vector) => ({ ...vector, vector_id: id }))
+            )
+            await db.vectors.delete(id)
+            await db.annIndexes.delete(id)
+          }
+        )
+        migratedCount++
+      } catch (error) {
+        errors.push(`Failed to migrate vector chunks for ${id}: ${error}`)
+      }
+    }
+
+    return {
+      success: errors.length === 0,
+      migratedCount,
+      errors
+    }
+  }
+
+  /**
+   * Moves keyword indexes stored as a single row into chunk rows and corpus
+   * statistics.
+   */
+  async migrateKeywordChunks(): Promise<{
+    success: boolean
+    migratedCount: number
+    errors: string[]
+  }> {
+    const errors: string[] = []
+    let migratedCount = 0
+    const keywordDb = new PageAssistKeywordDb()
+
+    const ids = (await db.keywordIndexes.toCollection().primaryKeys()) as string[]
+    for (const id of ids) {
+      try {
+        await keywordDb.migrateLegacyIndex(id)
+        migratedCount++
+      } catch (error) {
+        errors.push(`Failed to migrate keyword chunks for ${id}: ${error}`)
+      }
+    }
+
+    return {
+      success: errors.length === 0,
+      migratedCount,
+      errors
+    }
+  }
+
+  /**
+   * Converts stored embeddings to the given quantization. Chunks written
+   * before quantization existed hold plain arrays and are converted as well.
+   */
+  async migrateVectorStorage(quantization: EmbeddingQuantization): Promise<{
+    success: boolean
+    migratedCount: number
+    errors: string[]
+  }> {
+    const errors: string[] = []
+    let migratedCount = 0
+
+    // sets are converted one at a time, a vector set can be large
+    const ids = (await db.vectorChunks
+      .orderBy("vector_id")
+      .uniqueKeys()) as string[]
+    for (const id of ids) {
+      try {
+        const chunks = await db.vectorChunks
+          .where("vector_id")
+          .equals(id)
+          .toArray()
+        const converted = chunks
+          .filter(
+            (chunk) =>
+              getQuantization(chunk) !== quantization ||
+              Array.isArray(chunk.embedding)
+          )
+          .map((chunk) => ({
+            ...chunk,
+            ...encodeEmbedding(decodeEmbedding(chunk), quantization)
+          }))
+        if (converted.length > 0) {
+          await db.transaction(
+            "rw",
+            db.vectorChunks,
+            db.annIndexes,
+            async () => {
+              await db.vectorChunks.bulkPut(converted)
+              // rebuilt on next search from the converted vectors
+              await db.annIndexes.delete(id)
+            }
+          )
+          migratedCount++
+        }
+      } catch (error) {
+        errors.push(`Failed to migrate vector storage for ${id}: ${error}`)
+      }
+    }
+
+    return {
+      success: errors.length === 0,
+      migratedCount,
+      errors
+    }
+  }
+
   async verifyMigration(): Promise<{
     isValid: boolean
     counts: {
@@ -396,7 +541,60 @@ export const runSessionFilesMigration = async (): Promise<void> => {
   }
 }
 
+// quantization the stored vectors were last converted to
+const VECTOR_STORAGE_KEY = "vectorStorageQuantization"
+
+/**
+ * Moves vector sets and keyword indexes that are still stored as a single
+ * row into chunk rows, then converts stored vectors when the embedding quantization setting
+ * differs from the format they were last converted to.
+ */
+export const runVectorStorageMigration = async (): Promise<void> => {
+  const migration = new DatabaseMigration()
+
+  if ((await db.vectors.count()) > 0) {
+    const result = await migration.migrateVectorChunks()
+    if (result.success) {
+      console.log(`Moved ${result.migratedCount} vector sets to chunk rows`)
+    } else {
+      console.error("Vector chunk migration completed with errors:")
+      console.error(result.errors)
+    }
+  }
+
+  if ((await db.keywordIndexes.count()) > 0) {
+    const result = await migration.migrateKeywordChunks()
+    if (result.success) {
+      console.log(`Moved ${result.migratedCount} keyword indexes to chunk rows`)
+    } else {
+      console.error("Keyword chunk migration completed with errors:")
+      console.error(result.errors)
+    }
+  }
+
+  const quantization = await getEmbeddingQuantization()
+  const migrated = await storage.get<EmbeddingQuantization | undefined>(
+    VECTOR_STORAGE_KEY
+  )
+  if (migrated === quantization) {
+    return
+  }
+
+  const result = await migration.migrateVectorStorage(quantization)
+
+  if (result.success) {
+    console.log(
+      `Converted ${result.migratedCount} vector sets to ${quantization} storage`
+    )
+    await storage.set(VECTOR_STORAGE_KEY, quantization)
+  } else {
+    console.error("Vector storage migration completed with errors:")
+    console.error(result.errors)
+  }
+}
+
 export const runAllMigrations = async (): Promise<void> => {
   await runMigration()
   await runSessionFilesMigration()
+  await runVectorStorageMigration()
 }
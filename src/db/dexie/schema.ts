@@ -9,10 +9,17 @@ import {
   Webshare,
   Knowledge,
   VectorData,
+  VectorChunk,
+  ParentChunk,
+  KeywordIndexData,
+  KeywordChunk,
+  KeywordStats,
+  AnnIndexData,
   Document,
   OpenAIModelConfig,
   Model,
-  ModelNickname
+  ModelNickname,
+  McpServer
 } from "./types"
 
 export class PageAssistDexieDB extends Dexie {
@@ -26,13 +33,24 @@ export class PageAssistDexieDB extends Dexie {
   // Knowledge management tables
   knowledge!: Table<Knowledge>;
   documents!: Table<Document>;
+  // legacy rows holding whole sets, moved to vectorChunks by DatabaseMigration
   vectors!: Table<VectorData>;
+  vectorChunks!: Table<VectorChunk, number>;
+  parentChunks!: Table<ParentChunk, number>;
+  // legacy rows holding whole indexes, moved to keywordChunks on first use
+  keywordIndexes!: Table<KeywordIndexData>;
+  keywordChunks!: Table<KeywordChunk, number>;
+  keywordStats!: Table<KeywordStats>;
+  annIndexes!: Table<AnnIndexData>;
 
   // Openai config
   openaiConfigs!: Table<OpenAIModelConfig>;
   customModels!: Table<Model>;
   modelNickname!: Table<ModelNickname>
 
+  // MCP servers
+  mcpServers!: Table<McpServer>;
+
   constructor() {
     super('PageAssistDatabase');
 
@@ -52,6 +70,36 @@ export class PageAssistDexieDB extends Dexie {
       customModels: 'id, model_id, name, model_name, model_image, provider_id, lookup, model_type, db_type',
       modelNickname: 'id, model_id, model_name, model_avatar'
     });
+
+    this.version(2).stores({
+      keywordIndexes: 'id'
+    });
+
+    this.version(3).stores({
+      annIndexes: 'id, [id+version]'
+    });
+
+    this.version(4).stores({
+      vectorChunks: '++id, vector_id, [vector_id+file_id]'
+    });
+
+    this.version(5).stores({
+      mcpServers: 'id, name, url, transport, enabled, createdAt, db_type'
+    });
+
+    this.version(6).stores({
+      parentChunks: '++id, vector_id, [vector_id+parent_id], [vector_id+file_id]'
+    });
+
+    this.version(7).stores({
+      keywordChunks: '++id, keyword_id, [keyword_id+file_id]',
+      keywordStats: 'id'
+    });
+
+    // graphs are keyed by chunk id from here on, older ones used positions
+    this.version(8).stores({
+      annIndexes: 'id, [id+version]'
+    }).upgrade((tx) => tx.table('annIndexes').clear());
   }
 }
 
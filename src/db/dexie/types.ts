@@ -1,4 +1,7 @@
 import { ChatDocuments } from '@/models/ChatTypes';
+import type { HNSWGraph } from '@/libs/hnsw';
+import type { SplitterSettings } from '@/utils/text-splitter';
+import type { ToolCallStep } from '@/tools/types';
 
 export type LastUsedModelType = { prompt_id?: string; prompt_content?: string }
 
@@ -59,6 +62,7 @@ export type Message = {
   modelName?: string;
   modelImage?: string;
   documents?: ChatDocuments;
+  toolCalls?: ToolCallStep[];
 };
 
 export type Webshare = {
@@ -84,11 +88,39 @@ export type UserSettings = {
   user_id: string;
 };
 
+export type CrawlOptions = {
+  maxDepth: number;
+  maxPages: number;
+  sameOrigin: boolean;
+  // only pages whose path starts with this prefix are crawled
+  pathPrefix?: string;
+};
+
 export type Source = {
   source_id: string;
   type: string;
   filename?: string;
   content: string;
+  sourceType?: string;
+  content_hash?: string;
+  updatedAt?: number;
+  // page url of "url" sources
+  url?: string;
+  // set on "crawl" sources, which are replaced by one "url" source per page
+  crawl?: CrawlOptions;
+  // pdf pages that yielded no text when the source was indexed
+  emptyPages?: number[];
+  // why the source could not be indexed, it keeps its content for a retry
+  error?: string;
+};
+
+export type KnowledgeIndexStats = {
+  added: number;
+  removed: number;
+  unchanged: number;
+  // sources that could not be indexed
+  failed?: number;
+  updatedAt: number;
 };
 
 export type Knowledge = {
@@ -102,13 +134,108 @@ export type Knowledge = {
   createdAt: number;
   systemPrompt?: string;
   followupPrompt?: string;
+  retrievalMode?: RetrievalMode;
+  indexStats?: KnowledgeIndexStats;
+  migration?: KnowledgeMigration;
+  // size of the parent passages returned for matching child chunks,
+  // unset or 0 returns the matching chunks themselves
+  parentChunkSize?: number;
+  refresh?: KnowledgeRefresh;
+  // OCR for pdf pages without a text layer, on unless set to false
+  ocr?: boolean;
+  evaluation?: KnowledgeEvaluation;
+  // splitter settings the chunks were last indexed with
+  splitterSettings?: SplitterSettings;
+};
+
+// question/expected source pairs and the retrieval evaluations run on them
+export type KnowledgeEvaluation = {
+  questions: EvaluationQuestion[];
+  // newest first
+  runs: EvaluationRun[];
+};
+
+export type EvaluationQuestion = {
+  id: string;
+  question: string;
+  // source_id of the sources that answer the question
+  expectedSources: string[];
 };
 
+// retrieval settings at the time of a run, to tell runs apart
+export type EvaluationSettings = {
+  embedding_model: string;
+  retrievalMode: RetrievalMode;
+  k: number;
+  // unset for knowledge bases indexed before their settings were recorded
+  chunkSize?: number;
+  chunkOverlap?: number;
+  splittingStrategy?: string;
+  parentChunkSize?: number;
+  reranker: string;
+  multiQueryCount: number;
+};
+
+export type EvaluationResult = {
+  questionId: string;
+  // 1-based rank of the first expected source, null when not retrieved
+  rank: number | null;
+  // milliseconds
+  latency: number;
+  retrieved: string[];
+  error?: string;
+};
+
+export type EvaluationRun = {
+  id: string;
+  createdAt: number;
+  settings: EvaluationSettings;
+  hitAtK: number;
+  mrr: number;
+  avgLatency: number;
+  p95Latency: number;
+  results: EvaluationResult[];
+};
+
+// periodic re-fetching of "url" sources
+export type KnowledgeRefresh = {
+  // hours between refreshes, 0 disables them
+  interval: number;
+  lastRefreshedAt?: number;
+  // "queued" when the changed pages wait for an extension page to index them
+  status?: "processing" | "queued" | "finished" | "failed";
+  // pages whose content changed in the last refresh
+  changed?: number;
+  error?: string;
+};
+
+export type KnowledgeMigration = {
+  model: string;
+  status: "pending" | "processing" | "failed";
+  progress: number;
+  error?: string;
+};
+
+export type RetrievalMode = "vector" | "keyword" | "hybrid";
+
+
+export type EmbeddingQuantization = "none" | "int8" | "binary";
+
+// int8 values are scaled by `scale`, `bits` holds the packed sign bits of
+// binary quantized embeddings
+export type QuantizedEmbedding = {
+  type: Exclude<EmbeddingQuantization, "none">;
+  int8: Int8Array;
+  scale: number;
+  bits?: Uint8Array;
+};
 
 export interface PageAssistVector {
   file_id: string;
   content: string;
-  embedding: number[];
+  // unset when only the quantized embedding is stored
+  embedding?: number[] | Float32Array;
+  quantized?: QuantizedEmbedding;
   metadata: Record<string, any>;
 }
 
@@ -117,6 +244,61 @@ export type VectorData = {
   vectors: PageAssistVector[];
 };
 
+// one row per chunk, `vector_id` is the id of the set the chunk belongs to
+// (`vector:${knowledgeId}`, or its `:shadow` set while re-embedding)
+export type VectorChunk = PageAssistVector & {
+  id?: number;
+  vector_id: string;
+};
+
+// a parent passage, stored once for all the chunks that were split from it
+export type ParentChunk = {
+  id?: number;
+  vector_id: string;
+  file_id?: string;
+  parent_id: string;
+  content: string;
+};
+
+export type KeywordDocument = {
+  file_id: string;
+  content: string;
+  metadata: Record<string, any>;
+  terms: Record<string, number>;
+  length: number;
+};
+
+export type KeywordIndexData = {
+  id: string;
+  documents: KeywordDocument[];
+};
+
+// one row per chunk, `keyword_id` is the id of the index the chunk belongs to
+// (`keyword:${knowledgeId}`, or its `:shadow` index while re-embedding)
+export type KeywordChunk = KeywordDocument & {
+  id?: number;
+  keyword_id: string;
+};
+
+// BM25 corpus statistics of a keyword index, keyed by the index id and
+// updated with every insert and delete of its chunks
+export type KeywordStats = {
+  id: string;
+  count: number;
+  totalLength: number;
+  df: Record<string, number>;
+};
+
+// HNSW graph over the chunks of a vector set, keyed by the set id. Its nodes
+// are chunk ids, `count` is the number of chunks in the graph and `version`
+// changes on every write so that cached copies can be invalidated.
+export type AnnIndexData = {
+  id: string;
+  version: number;
+  count: number;
+  graph: HNSWGraph;
+};
+
 
 // Types for Document
 export type DocumentSource = {
@@ -152,6 +334,19 @@ export type OpenAIModelConfig = {
   headers?: { key: string; value: string }[]
 }
 
+// a Model Context Protocol server whose tools the chat model can call
+export type McpServer = {
+  id: string
+  name: string
+  url: string
+  // "sse" is the older HTTP with SSE transport
+  transport: "streamable-http" | "sse"
+  headers?: { key: string; value: string }[]
+  enabled: boolean
+  createdAt: number
+  db_type: string
+}
+
 export type Model = {
   id: string
   model_id: string
@@ -176,5 +371,6 @@ export type MessageHistory = Message[];
 export type ChatHistory = HistoryInfo[];
 export type Prompts = Prompt[];
 export type OpenAIModelConfigs = OpenAIModelConfig[]
+export type McpServers = McpServer[]
 export type Models = Model[]
 export type ModelNicknames = ModelNickname[]
\ No newline at end of file
@@ -1,112 +1,290 @@
- 
 import { db } from "./schema";
-import { PageAssistVector, VectorData } from "./types";
+import {
+  PageAssistVector,
+  ParentChunk,
+  VectorChunk,
+  VectorData
+} from "./types";
+import { deleteKeywordIndex } from "./keyword";
+import { annUpdate } from "@/libs/ann-client";
+import {
+  decodeEmbedding,
+  encodeEmbedding,
+  fromSerializableVector,
+  toSerializableVector
+} from "@/libs/quantization";
+import { getEmbeddingQuantization } from "@/services/kb";
+
+const restoreVectorData = (data: VectorData): VectorData => ({
+  ...data,
+  vectors: data.vectors.map(fromSerializableVector)
+});
+
+const toChunks = (id: string, vectors: PageAssistVector[]): VectorChunk[] =>
+  vectors.map((vector) => ({ ...vector, vector_id: id }));
+
+const toVector = ({ id, vector_id, ...vector }: VectorChunk): PageAssistVector =>
+  vector;
 
 export class PageAssistVectorDb {
+  /**
+   * Moves a set that is still stored as a single row (from before chunks had
+   * their own rows) into chunk rows, so that reads and writes never see only
+   * part of the set.
+   */
+  async migrateLegacyVector(id: string): Promise<void> {
+    if ((await db.vectors.where("id").equals(id).count()) === 0) {
+      return;
+    }
+    await db.transaction(
+      "rw",
+      db.vectors,
+      db.vectorChunks,
+      db.annIndexes,
+      async () => {
+        const legacy = await db.vectors.get(id);
+        if (!legacy) {
+          return;
+        }
+        await db.vectorChunks.bulkAdd(toChunks(id, legacy.vectors));
+        await db.vectors.delete(id);
+        await db.annIndexes.delete(id);
+      }
+    );
+  }
+
   async insertVector(id: string, vector: PageAssistVector[]): Promise<void> {
-    const existingData = await db.vectors.get(id);
-    
-    if (!existingData) {
-      await db.vectors.add({ id, vectors: vector });
-    } else {
-      const updatedData = {
-        ...existingData,
-        vectors: existingData.vectors.concat(vector)
-      };
-      await db.vectors.put(updatedData);
+    await this.migrateLegacyVector(id);
+    const quantization = await getEmbeddingQuantization();
+    const chunks = toChunks(
+      id,
+      vector.map((v) => ({
+        ...v,
+        ...encodeEmbedding(decodeEmbedding(v), quantization)
+      }))
+    );
+    await db.vectorChunks.bulkAdd(chunks);
+    await this.updateAnnIndex(id);
+  }
+
+  /**
+   * Updates the ANN graph for added, deleted and `changed` chunks. The graph
+   * is optional, one that can not be updated is rebuilt on the next search.
+   */
+  private async updateAnnIndex(id: string, changed: number[] = []) {
+    try {
+      await annUpdate(id, changed);
+    } catch (e) {
+      console.error("Failed to update ANN index", e);
+      await db.annIndexes.delete(id);
     }
   }
 
+  /**
+   * Stores the parent passages of a file's chunks. Passages that are already
+   * stored for the file are skipped, chunks of one passage can be added in
+   * several batches.
+   */
+  async insertParentChunks(
+    id: string,
+    file_id: string | undefined,
+    parents: Map<string, string>
+  ): Promise<void> {
+    if (parents.size === 0) {
+      return;
+    }
+    const stored = await db.parentChunks
+      .where("[vector_id+parent_id]")
+      .anyOf(Array.from(parents.keys()).map((parent_id) => [id, parent_id]))
+      .toArray();
+    const rows: ParentChunk[] = [];
+    for (const [parent_id, content] of parents) {
+      if (
+        !stored.some((p) => p.parent_id === parent_id && p.file_id === file_id)
+      ) {
+        rows.push({ vector_id: id, file_id, parent_id, content });
+      }
+    }
+    await db.parentChunks.bulkAdd(rows);
+  }
+
+  // passage text by parent id, for the given ids or all passages of the set
+  async getParentContents(
+    id: string,
+    parentIds?: string[]
+  ): Promise<Map<string, string>> {
+    const rows = parentIds
+      ? await db.parentChunks
+          .where("[vector_id+parent_id]")
+          .anyOf(parentIds.map((parent_id) => [id, parent_id]))
+          .toArray()
+      : await db.parentChunks.where("vector_id").equals(id).toArray();
+    return new Map(rows.map((row) => [row.parent_id, row.content]));
+  }
+
   async deleteVector(id: string): Promise<void> {
+    await db.vectorChunks.where("vector_id").equals(id).delete();
+    await db.parentChunks.where("vector_id").equals(id).delete();
     await db.vectors.delete(id);
+    await db.annIndexes.delete(id);
   }
 
   async deleteVectorByFileId(id: string, file_id: string): Promise<void> {
-    const data = await db.vectors.get(id);
-    if (data) {
-      data.vectors = data.vectors.filter((v) => v.file_id !== file_id);
-      await db.vectors.put(data);
+    await this.migrateLegacyVector(id);
+    await db.parentChunks
+      .where("[vector_id+file_id]")
+      .equals([id, file_id])
+      .delete();
+    const deleted = await db.vectorChunks
+      .where("[vector_id+file_id]")
+      .equals([id, file_id])
+      .delete();
+    if (deleted > 0) {
+      await this.updateAnnIndex(id);
     }
   }
 
+  async getVectorsByFileId(
+    id: string,
+    file_id: string
+  ): Promise<PageAssistVector[]> {
+    await this.migrateLegacyVector(id);
+    const chunks = await db.vectorChunks
+      .where("[vector_id+file_id]")
+      .equals([id, file_id])
+      .toArray();
+    return chunks.map(toVector);
+  }
+
+  async getChunks(id: string): Promise<VectorChunk[]> {
+    await this.migrateLegacyVector(id);
+    return await db.vectorChunks.where("vector_id").equals(id).toArray();
+  }
+
+  async updateChunk(
+    chunkId: number,
+    update: { content: string; embedding: number[]; metadata: any }
+  ): Promise<VectorChunk | undefined> {
+    const chunk = await db.vectorChunks.get(chunkId);
+    if (!chunk) {
+      return undefined;
+    }
+    const quantization = await getEmbeddingQuantization();
+    const updated = {
+      ...chunk,
+      ...encodeEmbedding(update.embedding, quantization),
+      content: update.content,
+      metadata: update.metadata
+    };
+    await db.vectorChunks.put(updated);
+    // the graph node was linked using the old embedding
+    await this.updateAnnIndex(chunk.vector_id, [chunkId]);
+    return updated;
+  }
+
+  async deleteChunk(chunkId: number): Promise<VectorChunk | undefined> {
+    const chunk = await db.vectorChunks.get(chunkId);
+    if (chunk) {
+      await db.vectorChunks.delete(chunkId);
+      await this.updateAnnIndex(chunk.vector_id);
+    }
+    return chunk;
+  }
+
   async getVector(id: string): Promise<VectorData | undefined> {
-    return await db.vectors.get(id);  
+    await this.migrateLegacyVector(id);
+    // chunks come back in insertion order
+    const chunks = await db.vectorChunks.where("vector_id").equals(id).toArray();
+    if (chunks.length === 0) {
+      return undefined;
+    }
+    return { id, vectors: chunks.map(toVector) };
   }
 
   async getAll(): Promise<VectorData[]> {
-    return await db.vectors.toArray();
+    const data = new Map<string, VectorData>();
+    await db.vectorChunks.each((chunk) => {
+      if (!data.has(chunk.vector_id)) {
+        data.set(chunk.vector_id, { id: chunk.vector_id, vectors: [] });
+      }
+      data.get(chunk.vector_id)!.vectors.push(toVector(chunk));
+    });
+    for (const legacy of await db.vectors.toArray()) {
+      if (!data.has(legacy.id)) {
+        data.set(legacy.id, legacy);
+      }
+    }
+    return Array.from(data.values());
   }
 
   async saveImportedData(data: VectorData[]): Promise<void> {
-    await db.vectors.bulkPut(data);
+    for (const vectorData of data.map(restoreVectorData)) {
+      await db.vectorChunks.where("vector_id").equals(vectorData.id).delete();
+      await db.vectorChunks.bulkAdd(toChunks(vectorData.id, vectorData.vectors));
+    }
+    await db.annIndexes.bulkDelete(data.map((d) => d.id));
   }
 async saveImportedDataV2(data: VectorData[], options: {
   replaceExisting?: boolean;
   mergeData?: boolean;
 } = {}): Promise<void> {
   const { replaceExisting = false, mergeData = true } = options;
-  
+
   if (!mergeData && !replaceExisting) {
+    await db.vectorChunks.clear();
     await db.vectors.clear();
+    await db.annIndexes.clear();
   }
-  
-  for (const vectorData of data) {
-    const existingVector = await db.vectors.get(vectorData.id);
-    
-    if (existingVector && !replaceExisting) {
+
+  for (const vectorData of data.map(restoreVectorData)) {
+    const existingChunks = await db.vectorChunks
+      .where("vector_id")
+      .equals(vectorData.id)
+      .toArray();
+    await db.annIndexes.delete(vectorData.id);
+
+    if (existingChunks.length > 0 && !replaceExisting) {
       if (mergeData) {
-        const mergedVectors = [...existingVector.vectors];
-        for (const newVector of vectorData.vectors) {
-          if (!mergedVectors.find(v => v.file_id === newVector.file_id && v.content === newVector.content)) {
-            mergedVectors.push(newVector);
-          }
-        }
-        await db.vectors.put({
-          ...existingVector,
-          vectors: mergedVectors
-        });
+        // Only the new chunks are written, existing rows stay untouched
+        const newVectors = vectorData.vectors.filter(
+          (newVector) =>
+            !existingChunks.find(
+              (v) =>
+                v.file_id === newVector.file_id &&
+                v.content === newVector.content
+            )
+        );
+        await db.vectorChunks.bulkAdd(toChunks(vectorData.id, newVectors));
       }
       continue;
     }
-    
-    await db.vectors.put(vectorData);
+
+    await db.vectorChunks.where("vector_id").equals(vectorData.id).delete();
+    await db.vectorChunks.bulkAdd(toChunks(vectorData.id, vectorData.vectors));
   }
 }
-  
+
 }
 export const importVectorsV2 = async (data: VectorData[], options: {
   replaceExisting?: boolean;
   mergeData?: boolean;
 } = {}) => {
   const { replaceExisting = false, mergeData = true } = options;
-  
+
   if (!mergeData && !replaceExisting) {
-    await db.vectors.clear();
+    await db.keywordIndexes.clear();
+    await db.keywordChunks.clear();
+    await db.keywordStats.clear();
   }
-  
+
   for (const vectorData of data) {
-    const existingVector = await db.vectors.get(vectorData.id);
-    
-    if (existingVector && !replaceExisting) {
-      if (mergeData) {
-        // Merge vectors arrays, avoiding duplicates
-        const mergedVectors = [...existingVector.vectors];
-        for (const newVector of vectorData.vectors) {
-          if (!mergedVectors.find(v => v.file_id === newVector.file_id && v.content === newVector.content)) {
-            mergedVectors.push(newVector);
-          }
-        }
-        await db.vectors.put({
-          ...existingVector,
-          vectors: mergedVectors
-        });
-      }
-      continue;
-    }
-    
-    await db.vectors.put(vectorData);
+    // Keyword index is rebuilt from the imported vectors on next search
+    await deleteKeywordIndex(vectorData.id.replace(/^vector:/, "keyword:"));
   }
-  
+
+  // ANN graphs are rebuilt on next search
+  const vectorDb = new PageAssistVectorDb();
+  await vectorDb.saveImportedDataV2(data, options);
 }
 
 export const saveImportedDataV2 = async (data: VectorData[], options: {
@@ -131,6 +309,51 @@ export const getVector = async (id: string): Promise<VectorData | undefined> =>
   return db.getVector(id);
 };
 
+export const getVectorsByFileId = async (
+  id: string,
+  file_id: string
+): Promise<PageAssistVector[]> => {
+  const db = new PageAssistVectorDb();
+  return db.getVectorsByFileId(id, file_id);
+};
+
+export const getVectorChunks = async (id: string): Promise<VectorChunk[]> => {
+  const db = new PageAssistVectorDb();
+  return db.getChunks(id);
+};
+
+export const updateVectorChunk = async (
+  chunkId: number,
+  update: { content: string; embedding: number[]; metadata: any }
+): Promise<VectorChunk | undefined> => {
+  const db = new PageAssistVectorDb();
+  return db.updateChunk(chunkId, update);
+};
+
+export const deleteVectorChunk = async (
+  chunkId: number
+): Promise<VectorChunk | undefined> => {
+  const db = new PageAssistVectorDb();
+  return db.deleteChunk(chunkId);
+};
+
+export const insertParentChunks = async (
+  id: string,
+  file_id: string | undefined,
+  parents: Map<string, string>
+): Promise<void> => {
+  const db = new PageAssistVectorDb();
+  return db.insertParentChunks(id, file_id, parents);
+};
+
+export const getParentContents = async (
+  id: string,
+  parentIds?: string[]
+): Promise<Map<string, string>> => {
+  const db = new PageAssistVectorDb();
+  return db.getParentContents(id, parentIds);
+};
+
 export const deleteVector = async (id: string): Promise<void> => {
   const db = new PageAssistVectorDb();
   return db.deleteVector(id);
@@ -146,8 +369,33 @@ export const deleteVectorByFileId = async (
 
 export const exportVectors = async () => {
   const db = new PageAssistVectorDb();
+  // chunks are exported grouped per set like the single row sets were.
+  // Quantized embeddings are exported as they are stored, versions without
+  // quantization can only import sets stored at full precision.
   const data = await db.getAll();
-  return data;
+  const exported = [];
+  for (const d of data) {
+    // the chunks carry their parent passage again, which imports read
+    const parents = await db.getParentContents(d.id);
+    exported.push({
+      ...d,
+      // typed arrays are not JSON serializable
+      vectors: d.vectors.map((vector) =>
+        toSerializableVector(
+          parents.has(vector.metadata?.parent_id)
+            ? {
+                ...vector,
+                metadata: {
+                  ...vector.metadata,
+                  parent_content: parents.get(vector.metadata.parent_id)
+                }
+              }
+            : vector
+        )
+      )
+    });
+  }
+  return exported;
 };
 
 export const importVectors = async (data: VectorData[]) => {
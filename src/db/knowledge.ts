@@ -7,6 +7,7 @@ export type Source = {
   content: string
   // Optional metadata to indicate how this source was added (e.g., 'text_input' or 'file_upload')
   sourceType?: string
+  content_hash?: string
 }
 
 export type Knowledge = {
@@ -4,6 +4,9 @@ import { clearBadge, streamDownload, cancelDownload } from "@/utils/pull-ollama"
 import { Storage } from "@plasmohq/storage"
 import { getInitialConfig } from "@/services/action"
 import { getCustomCopilotPrompts, getCopilotPromptsEnabledState, type CustomCopilotPrompt } from "@/services/application"
+import { refreshDueKnowledge } from "@/libs/refresh-knowledge"
+
+const KNOWLEDGE_REFRESH_ALARM = "knowledge-refresh"
 
 export default defineBackground({
   main() {
@@ -316,6 +319,24 @@ export default defineBackground({
       }
     })
 
+    browser.alarms.onAlarm.addListener(async (alarm) => {
+      if (alarm.name !== KNOWLEDGE_REFRESH_ALARM) {
+        return
+      }
+      try {
+        await refreshDueKnowledge()
+      } catch (e) {
+        console.error("[knowledge-refresh]", e)
+      }
+    })
+
+    // creating the alarm again on every background start would reset its period
+    browser.alarms.get(KNOWLEDGE_REFRESH_ALARM).then((alarm) => {
+      if (!alarm) {
+        browser.alarms.create(KNOWLEDGE_REFRESH_ALARM, { periodInMinutes: 30 })
+      }
+    })
+
     initialize()
   },
   persistent: true
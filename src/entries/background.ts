@@ -4,6 +4,13 @@ import { clearBadge, streamDownload, cancelDownload } from "@/utils/pull-ollama"
 import { Storage } from "@plasmohq/storage"
 import { getInitialConfig } from "@/services/action"
 import { getCustomCopilotPrompts, getCopilotPromptsEnabledState, type CustomCopilotPrompt } from "@/services/application"
+import {
+  KNOWLEDGE_REFRESH_QUEUED,
+  refreshDueKnowledge
+} from "@/libs/refresh-knowledge"
+import type { PageExtractor } from "@/libs/extract-page"
+
+const KNOWLEDGE_REFRESH_ALARM = "knowledge-refresh"
 
 export default defineBackground({
   main() {
@@ -335,6 +342,56 @@ export default defineBackground({
       }
     })
 
+    // pages are parsed in an offscreen document, the worker has no DOMParser
+    const extractPageOffscreen: PageExtractor = async (url, html) => {
+      const response = await chrome.runtime.sendMessage({
+        type: "extract_page",
+        target: "offscreen",
+        url,
+        html
+      })
+      return response?.page ?? null
+    }
+
+    browser.alarms.onAlarm.addListener(async (alarm) => {
+      if (alarm.name !== KNOWLEDGE_REFRESH_ALARM) {
+        return
+      }
+      try {
+        await chrome.offscreen.createDocument({
+          url: "offscreen.html",
+          reasons: [chrome.offscreen.Reason.DOM_PARSER],
+          justification: "Parse pages of knowledge base url sources"
+        })
+      } catch (e) {
+        // already open from a previous run
+      }
+      try {
+        const queued = await refreshDueKnowledge({
+          extract: extractPageOffscreen,
+          queueIndexing: true
+        })
+        if (queued) {
+          // an open extension page indexes them now, otherwise the next
+          // one that starts
+          await browser.runtime
+            .sendMessage({ type: KNOWLEDGE_REFRESH_QUEUED })
+            .catch(() => {})
+        }
+      } catch (e) {
+        console.error("[knowledge-refresh]", e)
+      } finally {
+        await chrome.offscreen.closeDocument().catch(() => {})
+      }
+    })
+
+    // creating the alarm again on every worker start would reset its period
+    browser.alarms.get(KNOWLEDGE_REFRESH_ALARM).then((alarm) => {
+      if (!alarm) {
+        browser.alarms.create(KNOWLEDGE_REFRESH_ALARM, { periodInMinutes: 30 })
+      }
+    })
+
     initialize()
   },
   persistent: true
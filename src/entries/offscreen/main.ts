@@ -0,0 +1,16 @@
+import { extractPageFromHtml } from "@/libs/extract-page"
+
+// The service worker has no DOM, so pages fetched by the knowledge refresh
+// are parsed here.
+chrome.runtime.onMessage.addListener((message, _sender, sendResponse) => {
+  if (message?.target !== "offscreen" || message?.type !== "extract_page") {
+    return false
+  }
+  extractPageFromHtml(message.url, message.html)
+    .then((page) => sendResponse({ page }))
+    .catch((e) => {
+      console.log("[offscreen] extract_page", e)
+      sendResponse({ page: null })
+    })
+  return true
+})
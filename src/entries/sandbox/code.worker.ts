@@ -0,0 +1,214 @@
+import type { CodeLanguage, CodeResult } from "@/libs/code-interpreter"
+import { loadPyodide, version, type PyodideInterface } from "pyodide"
+
+// Runs the code for the sandbox page, which terminates the worker when a
+// run does not finish in time.
+
+export type WorkerRequest = {
+  id: number
+  language: CodeLanguage
+  code: string
+  // the worker is loaded from a blob URL, so the page passes where the
+  // bundled Pyodide files are
+  pyodideURL: string
+  downloadPackages: boolean
+}
+
+const MAX_OUTPUT = 20000
+// packages are not bundled, they are only downloaded when the user turned
+// it on in the settings
+const PYODIDE_PACKAGES = `https://cdn.jsdelivr.net/pyodide/v${version}/full/`
+const PACKAGE_DOWNLOAD_HINT =
+  "Python packages are not bundled. Turn on Download Python packages in " +
+  "Settings > Tools to download them from the Pyodide CDN."
+
+const AsyncFunction = Object.getPrototypeOf(async () => {}).constructor
+
+// saves the open matplotlib figures as base64 PNGs and closes them
+const COLLECT_FIGURES = `
+def _pa_collect_figures():
+    import sys
+    if "matplotlib.pyplot" not in sys.modules:
+        return []
+    import base64, io
+    plt = sys.modules["matplotlib.pyplot"]
+    images = []
+    for number in plt.get_fignums():
+        buffer = io.BytesIO()
+        plt.figure(number).savefig(buffer, format="png", bbox_inches="tight")
+        images.append(base64.b64encode(buffer.getvalue()).decode())
+    plt.close("all")
+    return images
+`
+
+const format = (value: unknown) => {
+  if (typeof value === "string") {
+    return value
+  }
+  if (value instanceof Error) {
+    return value.stack || value.message
+  }
+  try {
+    return JSON.stringify(value, null, 2) ?? String(value)
+  } catch (e) {
+    return String(value)
+  }
+}
+
+const createOutput = () => {
+  const lines: string[] = []
+  const images: string[] = []
+  let length = 0
+  return {
+    images,
+    write: (text: string) => {
+      if (length < MAX_OUTPUT) {
+        lines.push(text)
+      }
+      length += text.length
+    },
+    text: () => {
+      const text = lines.join("\n")
+      return length > MAX_OUTPUT
+        ? `${text.slice(0, MAX_OUTPUT)}\n... (output truncated)`
+        : text
+    }
+  }
+}
+
+const toDataURL = async (canvas: OffscreenCanvas) =>
+  new FileReaderSync().readAsDataURL(
+    await canvas.convertToBlob({ type: "image/png" })
+  )
+
+const runJavaScript = async (code: string): Promise<CodeResult> => {
+  const output = createOutput()
+  const charts: Promise<void>[] = []
+  const log = (...args: unknown[]) => output.write(args.map(format).join(" "))
+  const sandboxConsole = {
+    ...console,
+    log,
+    info: log,
+    debug: log,
+    warn: log,
+    error: log,
+    table: log
+  }
+  // shows an OffscreenCanvas, an SVG string or an image URL as a chart
+  const display = (value: unknown) => {
+    if (value instanceof OffscreenCanvas) {
+      charts.push(
+        toDataURL(value).then((url) => {
+          output.images.push(url)
+        })
+      )
+    } else if (typeof value === "string" && /^\s*<svg[\s>]/i.test(value)) {
+      output.images.push(
+        `data:image/svg+xml;charset=utf-8,${encodeURIComponent(value)}`
+      )
+    } else if (typeof value === "string" && value.startsWith("data:image/")) {
+      output.images.push(value)
+    } else {
+      log(value)
+    }
+  }
+  try {
+    const result = await new AsyncFunction("console", "display", code)(
+      sandboxConsole,
+      display
+    )
+    await Promise.all(charts)
+    return {
+      stdout: output.text(),
+      result: result === undefined ? undefined : format(result),
+      images: output.images
+    }
+  } catch (e) {
+    return { stdout: output.text(), error: format(e), images: output.images }
+  }
+}
+
+let pyodide: Promise<PyodideInterface> | null = null
+
+const getPyodide = (pyodideURL: string) => {
+  if (!pyodide) {
+    pyodide = loadPyodide({
+      indexURL: pyodideURL,
+      packageBaseUrl: PYODIDE_PACKAGES
+    }).then(async (instance) => {
+      await instance.runPythonAsync(
+        `import os\nos.environ["MPLBACKEND"] = "AGG"\n${COLLECT_FIGURES}`
+      )
+      return instance
+    })
+    pyodide.catch(() => {
+      pyodide = null
+    })
+  }
+  return pyodide
+}
+
+const runPython = async (
+  code: string,
+  pyodideURL: string,
+  downloadPackages: boolean
+): Promise<CodeResult> => {
+  const output = createOutput()
+  let python: PyodideInterface
+  try {
+    python = await getPyodide(pyodideURL)
+  } catch (e) {
+    return {
+      stdout: "",
+      error: `Failed to load Python: ${format(e)}`,
+      images: []
+    }
+  }
+  python.setStdout({ batched: output.write })
+  python.setStderr({ batched: output.write })
+  // every run starts with fresh globals
+  const globals = python.globals.get("dict")()
+  let result: unknown
+  let error: string | undefined
+  try {
+    if (downloadPackages) {
+      await python.loadPackagesFromImports(code, { messageCallback: () => {} })
+    }
+    result = await python.runPythonAsync(code, { globals })
+    if ((result as any)?.toJs) {
+      const proxy = result as any
+      result = proxy.toString()
+      proxy.destroy()
+    }
+  } catch (e) {
+    error = format(e)
+    if (!downloadPackages && error.includes("ModuleNotFoundError")) {
+      error = `${error}\n${PACKAGE_DOWNLOAD_HINT}`
+    }
+  }
+  const figures = python.globals.get("_pa_collect_figures")()
+  for (const image of figures.toJs()) {
+    output.images.push(`data:image/png;base64,${image}`)
+  }
+  figures.destroy()
+  globals.destroy()
+  return {
+    stdout: output.text(),
+    result: result === undefined ? undefined : format(result),
+    error,
+    images: output.images
+  }
+}
+
+self.onmessage = async (event: MessageEvent<WorkerRequest>) => {
+  const request = event.data
+  const result =
+    request.language === "python"
+      ? await runPython(
+          request.code,
+          request.pyodideURL,
+          request.downloadPackages
+        )
+      : await runJavaScript(request.code)
+  self.postMessage({ id: request.id, result })
+}
@@ -0,0 +1,79 @@
+import type {
+  CodeLanguage,
+  CodeRequest,
+  CodeResult
+} from "@/libs/code-interpreter"
+// inlined as a blob URL, the sandbox has an opaque origin and can not start
+// a worker from an extension URL
+import CodeWorker from "./code.worker?worker&inline"
+import type { WorkerRequest } from "./code.worker"
+
+// The sandbox page has no extension APIs and an opaque origin, so code from
+// the model runs here. It only talks to the page that embeds it. The code
+// itself runs in a worker per language, which is terminated when a run does
+// not finish in time.
+
+const PYODIDE_URL = new URL("/pyodide/", location.href).href
+
+const workers = new Map<CodeLanguage, Worker>()
+
+const getWorker = (language: CodeLanguage) => {
+  let worker = workers.get(language)
+  if (!worker) {
+    worker = new CodeWorker()
+    workers.set(language, worker)
+  }
+  return worker
+}
+
+const stopWorker = (language: CodeLanguage) => {
+  workers.get(language)?.terminate()
+  workers.delete(language)
+}
+
+const run = (request: CodeRequest) =>
+  new Promise<CodeResult>((resolve) => {
+    const worker = getWorker(request.language)
+    const timer = setTimeout(() => {
+      // also discards the loaded Python packages
+      stopWorker(request.language)
+      resolve({
+        stdout: "",
+        error: `The code did not finish in ${request.timeout / 1000}s and was stopped`,
+        images: []
+      })
+    }, request.timeout)
+    worker.onmessage = (event: MessageEvent<{ result: CodeResult }>) => {
+      clearTimeout(timer)
+      resolve(event.data.result)
+    }
+    worker.onerror = (event) => {
+      clearTimeout(timer)
+      stopWorker(request.language)
+      resolve({ stdout: "", error: event.message, images: [] })
+    }
+    const message: WorkerRequest = {
+      id: request.id,
+      language: request.language,
+      code: request.code,
+      pyodideURL: PYODIDE_URL,
+      downloadPackages: request.downloadPackages
+    }
+    worker.postMessage(message)
+  })
+
+let queue = Promise.resolve()
+
+window.addEventListener("message", (event: MessageEvent<CodeRequest>) => {
+  const request = event.data
+  if (event.source !== window.parent || request?.type !== "run") {
+    return
+  }
+  // a worker runs one program at a time
+  queue = queue.then(async () => {
+    const result = await run(request)
+    window.parent.postMessage({ type: "result", id: request.id, result }, "*")
+  })
+})
+
+window.parent.postMessage({ type: "ready" }, "*")
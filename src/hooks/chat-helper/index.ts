@@ -8,6 +8,7 @@ import {
   updateChatHistoryCreatedAt
 } from "@/db/dexie/helpers"
 import { ChatDocuments } from "@/models/ChatTypes"
+import type { ToolCallStep } from "@/tools/types"
 import { generateTitle } from "@/services/title"
 import { ChatHistory } from "@/store/option"
 import { updatePageTitle } from "@/utils/update-page-title"
@@ -164,7 +165,8 @@ export const saveMessageOnSuccess = async ({
   prompt_content,
   reasoning_time_taken = 0,
   isContinue,
-  documents = []
+  documents = [],
+  tool_calls
 }: {
   historyId: string | null
   setHistoryId: (historyId: string) => void
@@ -182,6 +184,7 @@ export const saveMessageOnSuccess = async ({
   reasoning_time_taken?: number
   isContinue?: boolean
   documents?: ChatDocuments
+  tool_calls?: ToolCallStep[]
 }) => {
   if (historyId) {
     if (!isRegenerate && !isContinue) {
@@ -216,7 +219,8 @@ export const saveMessageOnSuccess = async ({
           time: 2,
           message_type,
           generationInfo,
-          reasoning_time_taken
+          reasoning_time_taken,
+          tool_calls
         }
         // historyId,
         // selectedModel!,
@@ -283,7 +287,8 @@ export const saveMessageOnSuccess = async ({
         time: 2,
         message_type,
         generationInfo,
-        reasoning_time_taken
+        reasoning_time_taken,
+        tool_calls
       }
       // newHistoryId.id,
       // selectedModel!,
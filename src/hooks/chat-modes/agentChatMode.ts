@@ -0,0 +1,351 @@
+import { cleanUrl } from "~/libs/clean-url"
+import { getOllamaURL } from "~/services/ollama"
+import { type ChatHistory, type Message } from "~/store/option"
+import { generateID } from "@/db/dexie/helpers"
+import { generateHistory } from "@/utils/generate-history"
+import { pageAssistModel } from "@/models"
+import { humanMessageFormatter } from "@/utils/human-message"
+import { streamWithTools } from "@/libs/tool-calling"
+import { getAgentMaxSteps } from "@/services/tools"
+import { useAgentStore } from "@/store/agent"
+import { BROWSER_TOOLS } from "@/tools/browser"
+import { getCurrentTab } from "@/tools/current-tab"
+import type { ToolCallStep } from "@/tools/types"
+import {
+  isReasoningEnded,
+  isReasoningStarted,
+  mergeReasoningContent
+} from "@/libs/reasoning"
+import { getModelNicknameByID } from "@/db/dexie/nickname"
+import { systemPromptFormatter } from "@/utils/system-message"
+
+const agentSystemPrompt = ({
+  title,
+  url,
+  maxSteps
+}: {
+  title: string
+  url: string
+  maxSteps: number
+}) => `You are a browser agent. You complete the user's task by acting on their browser tab with the browser tools.
+
+The tab shows "${title}" (${url}).
+
+- Start with browser_snapshot to see the page. Elements are listed as [ref] role "name", act on them by ref.
+- Take one action at a time and check the snapshot it returns before the next one. Refs change with every snapshot, only use refs from the latest one.
+- Use browser_extract to read long text.
+- You have ${maxSteps} actions. When the task is done or can not be done, stop calling tools and answer with what you did and found.
+- The user confirms actions that submit forms. When an action is declined, do not retry it.
+- Never enter passwords, payment details or other personal data the user did not give you.`
+
+export const agentChatMode = async (
+  message: string,
+  image: string,
+  isRegenerate: boolean,
+  messages: Message[],
+  history: ChatHistory,
+  signal: AbortSignal,
+  {
+    selectedModel,
+    useOCR,
+    setMessages,
+    saveMessageOnSuccess,
+    saveMessageOnError,
+    setHistory,
+    setIsProcessing,
+    setStreaming,
+    setAbortController,
+    historyId,
+    setHistoryId
+  }: {
+    selectedModel: string
+    useOCR: boolean
+    setMessages: (messages: Message[] | ((prev: Message[]) => Message[])) => void
+    saveMessageOnSuccess: (data: any) => Promise<string | null>
+    saveMessageOnError: (data: any) => Promise<string | null>
+    setHistory: (history: ChatHistory) => void
+    setIsProcessing: (value: boolean) => void
+    setStreaming: (value: boolean) => void
+    setAbortController: (controller: AbortController | null) => void
+    historyId: string | null
+    setHistoryId: (id: string) => void
+  }
+) => {
+  console.log("Using agentChatMode")
+  const url = await getOllamaURL()
+
+  if (image.length > 0) {
+    image = `data:image/jpeg;base64,${image.split(",")[1]}`
+  }
+
+  const ollama = await pageAssistModel({
+    model: selectedModel!,
+    baseUrl: cleanUrl(url)
+  })
+
+  let newMessage: Message[] = []
+  let generateMessageId = generateID()
+  const modelInfo = await getModelNicknameByID(selectedModel)
+
+  if (!isRegenerate) {
+    newMessage = [
+      ...messages,
+      {
+        isBot: false,
+        name: "You",
+        message,
+        sources: [],
+        images: image ? [image] : []
+      },
+      {
+        isBot: true,
+        name: selectedModel,
+        message: "▋",
+        sources: [],
+        id: generateMessageId,
+        modelImage: modelInfo?.model_avatar,
+        modelName: modelInfo?.model_name || selectedModel
+      }
+    ]
+  } else {
+    newMessage = [
+      ...messages,
+      {
+        isBot: true,
+        name: selectedModel,
+        message: "▋",
+        sources: [],
+        id: generateMessageId,
+        modelImage: modelInfo?.model_avatar,
+        modelName: modelInfo?.model_name || selectedModel
+      }
+    ]
+  }
+  setMessages(newMessage)
+  let fullText = ""
+  let contentToSave = ""
+  let timetaken = 0
+
+  // an open confirmation is declined when the user stops the agent
+  const declinePending = () =>
+    useAgentStore.getState().respondConfirmation(false)
+  signal.addEventListener("abort", declinePending)
+
+  try {
+    const tab = await getCurrentTab()
+    if (!tab?.id) {
+      throw new Error("Open a web page for the agent to act on")
+    }
+    const maxSteps = await getAgentMaxSteps()
+
+    let humanMessage = await humanMessageFormatter({
+      content: [
+        {
+          text: message,
+          type: "text"
+        }
+      ],
+      model: selectedModel,
+      useOCR: useOCR
+    })
+    if (image.length > 0) {
+      humanMessage = await humanMessageFormatter({
+        content: [
+          {
+            text: message,
+            type: "text"
+          },
+          {
+            image_url: image,
+            type: "image_url"
+          }
+        ],
+        model: selectedModel,
+        useOCR: useOCR
+      })
+    }
+
+    const applicationChatHistory = generateHistory(history, selectedModel)
+    applicationChatHistory.unshift(
+      await systemPromptFormatter({
+        content: agentSystemPrompt({
+          title: tab.title || "",
+          url: tab.url || "",
+          maxSteps
+        })
+      })
+    )
+
+    let generationInfo: any | undefined = undefined
+    let toolCalls: ToolCallStep[] = []
+    let toolSources: any[] = []
+
+    const chunks = streamWithTools(
+      ollama,
+      [...applicationChatHistory, humanMessage],
+      {
+        signal: signal,
+        tools: BROWSER_TOOLS,
+        maxSteps,
+        context: {
+          selectedModel,
+          signal,
+          tabId: tab.id,
+          confirm: (request) =>
+            useAgentStore.getState().requestConfirmation(request)
+        },
+        onToolStep: (steps) => {
+          toolCalls = steps
+          setMessages((prev) =>
+            prev.map((message) =>
+              message.id === generateMessageId
+                ? { ...message, toolCalls: steps }
+                : message
+            )
+          )
+        },
+        onSources: (sources) => {
+          toolSources = sources
+        },
+        callbacks: [
+          {
+            handleLLMEnd(output: any): any {
+              try {
+                generationInfo = output?.generations?.[0][0]?.generationInfo
+              } catch (e) {
+                console.error("handleLLMEnd error", e)
+              }
+            }
+          }
+        ]
+      }
+    )
+
+    let count = 0
+    let reasoningStartTime: Date | null = null
+    let reasoningEndTime: Date | null = null
+    let apiReasoning: boolean = false
+
+    for await (const chunk of chunks) {
+      if (chunk?.additional_kwargs?.reasoning_content) {
+        const reasoningContent = mergeReasoningContent(
+          fullText,
+          chunk?.additional_kwargs?.reasoning_content || ""
+        )
+        contentToSave = reasoningContent
+        fullText = reasoningContent
+        apiReasoning = true
+      } else {
+        if (apiReasoning) {
+          fullText += "</think>"
+          contentToSave += "</think>"
+          apiReasoning = false
+        }
+      }
+
+      contentToSave += chunk?.content
+      fullText += chunk?.content
+
+      if (isReasoningStarted(fullText) && !reasoningStartTime) {
+        reasoningStartTime = new Date()
+      }
+
+      if (
+        reasoningStartTime &&
+        !reasoningEndTime &&
+        isReasoningEnded(fullText)
+      ) {
+        reasoningEndTime = new Date()
+        const reasoningTime =
+          reasoningEndTime.getTime() - reasoningStartTime.getTime()
+        timetaken = reasoningTime
+      }
+
+      if (count === 0) {
+        setIsProcessing(true)
+      }
+      setMessages((prev) => {
+        return prev.map((message) => {
+          if (message.id === generateMessageId) {
+            return {
+              ...message,
+              message: fullText + "▋",
+              reasoning_time_taken: timetaken
+            }
+          }
+          return message
+        })
+      })
+      count++
+    }
+
+    setMessages((prev) => {
+      return prev.map((message) => {
+        if (message.id === generateMessageId) {
+          return {
+            ...message,
+            message: fullText,
+            sources: toolSources,
+            generationInfo,
+            reasoning_time_taken: timetaken
+          }
+        }
+        return message
+      })
+    })
+
+    setHistory([
+      ...history,
+      {
+        role: "user",
+        content: message,
+        image
+      },
+      {
+        role: "assistant",
+        content: fullText
+      }
+    ])
+
+    await saveMessageOnSuccess({
+      historyId,
+      setHistoryId,
+      isRegenerate,
+      selectedModel: selectedModel,
+      message,
+      image,
+      fullText,
+      source: toolSources,
+      generationInfo,
+      reasoning_time_taken: timetaken,
+      tool_calls: toolCalls
+    })
+
+    setIsProcessing(false)
+    setStreaming(false)
+  } catch (e) {
+    console.log(e)
+
+    const errorSave = await saveMessageOnError({
+      e,
+      botMessage: fullText,
+      history,
+      historyId,
+      image,
+      selectedModel,
+      setHistory,
+      setHistoryId,
+      userMessage: message,
+      isRegenerating: isRegenerate
+    })
+
+    if (!errorSave) {
+      throw e // Re-throw to be handled by the calling function
+    }
+    setIsProcessing(false)
+    setStreaming(false)
+  } finally {
+    signal.removeEventListener("abort", declinePending)
+    setAbortController(null)
+  }
+}
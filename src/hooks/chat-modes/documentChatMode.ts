@@ -17,7 +17,7 @@ import {
   removeReasoning
 } from "@/libs/reasoning"
 import { getModelNicknameByID } from "@/db/dexie/nickname"
-import { formatDocs } from "@/chain/chat-with-x"
+import { formatDocsWithCitations, getUniqueDocs } from "@/chain/chat-with-x"
 import { getAllDefaultModelSettings } from "@/services/model-settings"
 import { getNoOfRetrievedDocs } from "@/services/app"
 import { pageAssistEmbeddingModel } from "@/models/embedding"
@@ -25,6 +25,8 @@ import { UploadedFile } from "@/db/dexie/types"
 import { getSystemPromptForWeb, isQueryHaveWebsite } from "@/web/web"
 import { PAMemoryVectorStore } from "@/libs/PAMemoryVectorStore"
 import { getMaxContextSize } from "@/services/kb"
+import { getPageAssistReranker, rerankDocs } from "@/utils/rerank"
+import { getPageAssistTextSplitter } from "@/utils/text-splitter"
 
 export const documentChatMode = async (
   message: string,
@@ -270,7 +272,7 @@ export const documentChatMode = async (
           }
         }))
 
-        const textSplitter = await getPageAssistTextSplitter()
+        const textSplitter = await getPageAssistTextSplitter(ollamaEmbedding)
         const chunks = await textSplitter.splitDocuments(documents)
 
         const vectorstore = await PAMemoryVectorStore.fromDocuments(
@@ -278,17 +280,36 @@ export const documentChatMode = async (
           ollamaEmbedding
         )
         setActionInfo("semanticSearch")
-        const docs = await vectorstore.similaritySearch(query, docSize)
-        context += formatDocs(docs)
+        const { reranker, threshold, topN } = await getPageAssistReranker({
+          embedding: ollamaEmbedding,
+          selectedModel
+        })
+        let docs = await vectorstore.hybridSearch(
+          query,
+          reranker ? docSize * 2 : docSize
+        )
+        if (reranker) {
+          docs = await rerankDocs({
+            query,
+            docs,
+            embedding: ollamaEmbedding,
+            reranker,
+            threshold,
+            topN: Math.min(topN, docSize)
+          })
+        }
+        docs = getUniqueDocs(docs)
+        context += formatDocsWithCitations(docs)
         source = [
           ...source,
-          ...docs.map((doc) => {
+          ...docs.map((doc, i) => {
             return {
               ...doc,
               name: doc?.metadata?.source || "untitled",
               type: doc?.metadata?.type || "unknown",
               mode: "rag",
-              url: ""
+              url: "",
+              citation: i + 1
             }
           })
         ]
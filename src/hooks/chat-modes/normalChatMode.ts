@@ -8,6 +8,8 @@ import { generateID, getPromptById } from "@/db/dexie/helpers"
 import { generateHistory } from "@/utils/generate-history"
 import { pageAssistModel } from "@/models"
 import { humanMessageFormatter } from "@/utils/human-message"
+import { streamWithTools } from "@/libs/tool-calling"
+import type { ToolCallStep } from "@/tools/types"
 import {
   isReasoningEnded,
   isReasoningStarted,
@@ -184,11 +186,28 @@ export const normalChatMode = async (
     }
 
     let generationInfo: any | undefined = undefined
+    let toolCalls: ToolCallStep[] = []
+    let toolSources: any[] = []
 
-    const chunks = await ollama.stream(
+    const chunks = streamWithTools(
+      ollama,
       [...applicationChatHistory, humanMessage],
       {
         signal: signal,
+        context: { selectedModel, signal },
+        onToolStep: (steps) => {
+          toolCalls = steps
+          setMessages((prev) =>
+            prev.map((message) =>
+              message.id === generateMessageId
+                ? { ...message, toolCalls: steps }
+                : message
+            )
+          )
+        },
+        onSources: (sources) => {
+          toolSources = sources
+        },
         callbacks: [
           {
             handleLLMEnd(output: any): any {
@@ -267,6 +286,7 @@ export const normalChatMode = async (
           return {
             ...message,
             message: fullText,
+            sources: toolSources,
             generationInfo,
             reasoning_time_taken: timetaken
           }
@@ -296,11 +316,12 @@ export const normalChatMode = async (
       message,
       image,
       fullText,
-      source: [],
+      source: toolSources,
       generationInfo,
       prompt_content: promptContent,
       prompt_id: promptId,
-      reasoning_time_taken: timetaken
+      reasoning_time_taken: timetaken,
+      tool_calls: toolCalls
     })
 
     setIsProcessing(false)
@@ -9,6 +9,8 @@ import { generateID } from "@/db/dexie/helpers"
 import { generateHistory } from "@/utils/generate-history"
 import { pageAssistModel } from "@/models"
 import { humanMessageFormatter } from "@/utils/human-message"
+import { streamWithTools } from "@/libs/tool-calling"
+import type { ToolCallStep } from "@/tools/types"
 import {
   isReasoningEnded,
   isReasoningStarted,
@@ -17,12 +19,19 @@ import {
 } from "@/libs/reasoning"
 import { getModelNicknameByID } from "@/db/dexie/nickname"
 import { PageAssistVectorStore } from "@/libs/PageAssistVectorStore"
-import { formatDocs } from "@/chain/chat-with-x"
+import { formatDocsWithCitations } from "@/chain/chat-with-x"
 import { getAllDefaultModelSettings } from "@/services/model-settings"
-import { getNoOfRetrievedDocs } from "@/services/app"
 import { pageAssistEmbeddingModel } from "@/models/embedding"
 import { isChatWithWebsiteEnabled } from "@/services/kb"
 import { getKnowledgeById } from "@/db/dexie/knowledge"
+import { retrieveKnowledge } from "@/libs/retrieve-knowledge"
+import {
+  createKnowledgeFilter,
+  getFilteredSources,
+  parseKnowledgeFilter
+} from "@/utils/knowledge-filter"
+import { notification } from "antd"
+import i18n from "i18next"
 
 export const ragMode = async (
   message: string,
@@ -112,18 +121,19 @@ export const ragMode = async (
   let fullText = ""
   let contentToSave = ""
 
+  const kbInfo = await getKnowledgeById(selectedKnowledge.id)
+
   const embeddingModle = await defaultEmbeddingModelForRag()
   const ollamaUrl = await getOllamaURL()
   const ollamaEmbedding = await pageAssistEmbeddingModel({
-    model: embeddingModle || selectedModel,
+    // queries must be embedded with the model the knowledge base was built with
+    model: kbInfo?.embedding_model || embeddingModle || selectedModel,
     baseUrl: cleanUrl(ollamaUrl),
     keepAlive:
       currentChatModelSettings?.keepAlive ??
       userDefaultModelSettings?.keepAlive
   })
 
-  const kbInfo = await getKnowledgeById(selectedKnowledge.id)
-
   let vectorstore = await PageAssistVectorStore.fromExistingIndex(
     ollamaEmbedding,
     {
@@ -133,7 +143,23 @@ export const ragMode = async (
   )
   let timetaken = 0
   try {
-    let query = message
+    // @file:, @type:, @after: and @before: scope the search to some sources
+    const { filter: kbFilter, query: question } = parseKnowledgeFilter(message)
+    const filter = kbInfo ? createKnowledgeFilter(kbFilter, kbInfo) : undefined
+    // tell the user instead of silently answering without context
+    if (
+      kbFilter &&
+      kbInfo &&
+      getFilteredSources(kbFilter, kbInfo).length === 0
+    ) {
+      notification.warning({
+        message: i18n.t("knowledge:filter.noMatch.title"),
+        description: i18n.t("knowledge:filter.noMatch.description", {
+          name: kbInfo.title
+        })
+      })
+    }
+    let query = question
     let { ragPrompt: systemPrompt, ragQuestionPrompt: questionPrompt } =
       await promptForRag()
 
@@ -157,7 +183,7 @@ export const ragMode = async (
         .join("\n")
       const promptForQuestion = questionPrompt
         .replaceAll("{chat_history}", chat_history)
-        .replaceAll("{question}", message)
+        .replaceAll("{question}", question)
       const questionOllama = await pageAssistModel({
         model: selectedModel!,
         baseUrl: cleanUrl(url)
@@ -166,20 +192,27 @@ export const ragMode = async (
       query = response.content.toString()
       query = removeReasoning(query)
     }
-    const docSize = await getNoOfRetrievedDocs()
     // const useVS = await isChatWithWebsiteEnabled()
     let context: string = ""
     let source: any[] = []
     // if (useVS) {
-    const docs = await vectorstore.similaritySearchKB(query, docSize)
-    context = formatDocs(docs)
-    source = docs.map((doc) => {
+    const docs = await retrieveKnowledge({
+      vectorstore,
+      embedding: ollamaEmbedding,
+      query,
+      selectedModel,
+      retrievalMode: kbInfo?.retrievalMode,
+      filter
+    })
+    context = formatDocsWithCitations(docs)
+    source = docs.map((doc, i) => {
       return {
         ...doc,
         name: doc?.metadata?.source || "untitled",
         type: doc?.metadata?.type || "unknown",
         mode: "rag",
-        url: ""
+        url: "",
+        citation: i + 1
       }
     })
     // } else {
@@ -202,7 +235,7 @@ export const ragMode = async (
         {
           text: systemPrompt
             .replace("{context}", context)
-            .replace("{question}", message),
+            .replace("{question}", question),
           type: "text"
         }
       ],
@@ -213,11 +246,28 @@ export const ragMode = async (
     const applicationChatHistory = generateHistory(history, selectedModel)
 
     let generationInfo: any | undefined = undefined
+    let toolCalls: ToolCallStep[] = []
+    let toolSources: any[] = []
 
-    const chunks = await ollama.stream(
+    const chunks = streamWithTools(
+      ollama,
       [...applicationChatHistory, humanMessage],
       {
         signal: signal,
+        context: { selectedModel, signal, knowledgeId: selectedKnowledge.id },
+        onToolStep: (steps) => {
+          toolCalls = steps
+          setMessages((prev) =>
+            prev.map((message) =>
+              message.id === generateMessageId
+                ? { ...message, toolCalls: steps }
+                : message
+            )
+          )
+        },
+        onSources: (sources) => {
+          toolSources = sources
+        },
         callbacks: [
           {
             handleLLMEnd(output: any): any {
@@ -293,7 +343,7 @@ export const ragMode = async (
           return {
             ...message,
             message: fullText,
-            sources: source,
+            sources: [...source, ...toolSources],
             generationInfo,
             reasoning_time_taken: timetaken
           }
@@ -323,9 +373,10 @@ export const ragMode = async (
       message,
       image,
       fullText,
-      source,
+      source: [...source, ...toolSources],
       generationInfo,
-      reasoning_time_taken: timetaken
+      reasoning_time_taken: timetaken,
+      tool_calls: toolCalls
     })
 
     setIsProcessing(false)
@@ -9,6 +9,8 @@ import { getSystemPromptForWeb, isQueryHaveWebsite } from "~/web/web"
 import { generateHistory } from "@/utils/generate-history"
 import { pageAssistModel } from "@/models"
 import { humanMessageFormatter } from "@/utils/human-message"
+import { streamWithTools } from "@/libs/tool-calling"
+import type { ToolCallStep } from "@/tools/types"
 import {
   isReasoningEnded,
   isReasoningStarted,
@@ -209,11 +211,28 @@ export const searchChatMode = async (
     }
 
     let generationInfo: any | undefined = undefined
+    let toolCalls: ToolCallStep[] = []
+    let toolSources: any[] = []
 
-    const chunks = await ollama.stream(
+    const chunks = streamWithTools(
+      ollama,
       [...applicationChatHistory, humanMessage],
       {
         signal: signal,
+        context: { selectedModel, signal },
+        onToolStep: (steps) => {
+          toolCalls = steps
+          setMessages((prev) =>
+            prev.map((message) =>
+              message.id === generateMessageId
+                ? { ...message, toolCalls: steps }
+                : message
+            )
+          )
+        },
+        onSources: (sources) => {
+          toolSources = sources
+        },
         callbacks: [
           {
             handleLLMEnd(output: any): any {
@@ -288,7 +307,7 @@ export const searchChatMode = async (
           return {
             ...message,
             message: fullText,
-            sources: source,
+            sources: [...source, ...toolSources],
             generationInfo,
             reasoning_time_taken: timetaken
           }
@@ -318,9 +337,10 @@ export const searchChatMode = async (
       message,
       image,
       fullText,
-      source,
+      source: [...source, ...toolSources],
       generationInfo,
-      reasoning_time_taken: timetaken
+      reasoning_time_taken: timetaken,
+      tool_calls: toolCalls
     })
 
     setIsProcessing(false)
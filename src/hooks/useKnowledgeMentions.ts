@@ -0,0 +1,123 @@
+import React from "react"
+import { useQuery } from "@tanstack/react-query"
+import { getKnowledgeById } from "@/db/dexie/knowledge"
+import { toFileMention } from "@/utils/knowledge-filter"
+import type { MentionPosition } from "./useTabMentions"
+
+export interface KnowledgeFileInfo {
+  id: string
+  filename: string
+  type: string
+}
+
+const FILE_MENTION = "@file:"
+
+export const useKnowledgeMentions = (
+  textareaRef: React.RefObject<HTMLTextAreaElement>,
+  knowledgeId?: string
+) => {
+  const [showFileMentions, setShowFileMentions] = React.useState(false)
+  const [fileMentionPosition, setFileMentionPosition] =
+    React.useState<MentionPosition | null>(null)
+
+  const { data: files = [] } = useQuery({
+    queryKey: ["fetchKnowledgeFiles", knowledgeId],
+    queryFn: async (): Promise<KnowledgeFileInfo[]> => {
+      const knowledge = await getKnowledgeById(knowledgeId!)
+      return (knowledge?.source || [])
+        .filter((source) => source.filename)
+        .map((source) => ({
+          id: source.source_id,
+          filename: source.filename!,
+          type: source.type
+        }))
+    },
+    enabled: !!knowledgeId
+  })
+
+  const detectFileMention = React.useCallback(
+    (text: string, cursorPosition: number): MentionPosition | null => {
+      const beforeCursor = text.substring(0, cursorPosition)
+      const start = beforeCursor.lastIndexOf(FILE_MENTION)
+      if (start === -1) return null
+
+      if (start > 0 && !/\s/.test(beforeCursor[start - 1])) return null
+
+      const afterMention = beforeCursor.substring(start + FILE_MENTION.length)
+      // a closed quote or a space outside quotes ends the mention
+      if (afterMention.startsWith('"')) {
+        if (afterMention.indexOf('"', 1) !== -1) return null
+      } else if (/\s/.test(afterMention)) {
+        return null
+      }
+
+      return {
+        start,
+        end: cursorPosition,
+        query: afterMention.replace(/^"/, "").toLowerCase()
+      }
+    },
+    []
+  )
+
+  const handleFileMentionChange = React.useCallback(
+    (text: string, cursorPosition: number) => {
+      if (!knowledgeId) {
+        setShowFileMentions(false)
+        return
+      }
+      const mention = detectFileMention(text, cursorPosition)
+      setFileMentionPosition(mention)
+      setShowFileMentions(!!mention)
+    },
+    [knowledgeId, detectFileMention]
+  )
+
+  const filteredFiles = React.useMemo(() => {
+    if (!fileMentionPosition) return files
+    return files.filter((file) =>
+      file.filename.toLowerCase().includes(fileMentionPosition.query)
+    )
+  }, [files, fileMentionPosition])
+
+  const insertFileMention = React.useCallback(
+    (
+      file: KnowledgeFileInfo,
+      currentText: string,
+      setValue: (value: string) => void
+    ) => {
+      if (!fileMentionPosition || !textareaRef.current) return
+
+      const mention = `${toFileMention(file.filename)} `
+      const before = currentText.substring(0, fileMentionPosition.start)
+      const after = currentText.substring(fileMentionPosition.end)
+      setValue(before + mention + after)
+
+      const cursor = fileMentionPosition.start + mention.length
+      setTimeout(() => {
+        if (textareaRef.current) {
+          textareaRef.current.focus()
+          textareaRef.current.setSelectionRange(cursor, cursor)
+        }
+      }, 0)
+
+      setShowFileMentions(false)
+      setFileMentionPosition(null)
+    },
+    [fileMentionPosition, textareaRef]
+  )
+
+  const closeFileMentions = React.useCallback(() => {
+    setShowFileMentions(false)
+    setFileMentionPosition(null)
+  }, [])
+
+  return {
+    showFileMentions: showFileMentions && filteredFiles.length > 0,
+    fileMentionPosition,
+    filteredFiles,
+    handleFileMentionChange,
+    insertFileMention,
+    closeFileMentions
+  }
+}
@@ -28,6 +28,8 @@ import {
 } from "./handlers/messageHandlers"
 import { tabChatMode } from "./chat-modes/tabChatMode"
 import { documentChatMode } from "./chat-modes/documentChatMode"
+import { agentChatMode } from "./chat-modes/agentChatMode"
+import { deepResearchChatMode } from "./chat-modes/deepResearchChatMode"
 import { generateID } from "@/db/dexie/helpers"
 import { UploadedFile } from "@/db/dexie/types"
 import { updatePageTitle } from "@/utils/update-page-title"
@@ -55,6 +57,10 @@ export const useMessageOption = () => {
     setChatMode,
     webSearch,
     setWebSearch,
+    deepResearch,
+    setDeepResearch,
+    agentMode,
+    setAgentMode,
     isSearchingInternet,
     setIsSearchingInternet,
     selectedQuickPrompt,
@@ -256,6 +262,18 @@ export const useMessageOption = () => {
         )
         return
       }
+      if (agentMode) {
+        await agentChatMode(
+          message,
+          image,
+          isRegenerate,
+          chatHistory || messages,
+          memory || history,
+          signal,
+          chatModeParams
+        )
+        return
+      }
       // console.log("contextFiles", contextFiles)
       if (contextFiles.length > 0) {
         await documentChatMode(
@@ -304,7 +322,17 @@ export const useMessageOption = () => {
           chatModeParams
         )
       } else {
-        if (webSearch) {
+        if (webSearch && deepResearch) {
+          await deepResearchChatMode(
+            message,
+            image,
+            isRegenerate,
+            chatHistory || messages,
+            memory || history,
+            signal,
+            chatModeParams
+          )
+        } else if (webSearch) {
           await searchChatMode(
             message,
             image,
@@ -403,6 +431,10 @@ export const useMessageOption = () => {
     regenerateLastMessage,
     webSearch,
     setWebSearch,
+    deepResearch,
+    setDeepResearch,
+    agentMode,
+    setAgentMode,
     isSearchingInternet,
     setIsSearchingInternet,
     selectedQuickPrompt,
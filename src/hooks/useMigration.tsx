@@ -1,6 +1,9 @@
 import { useEffect } from "react"
 import { useMutation } from "@tanstack/react-query"
-import { runAllMigrations } from "~/db/dexie/migration"
+import {
+  runAllMigrations,
+  runVectorStorageMigration
+} from "~/db/dexie/migration"
 import { Storage } from "@plasmohq/storage"
 import { message, notification } from "antd"
 
@@ -26,6 +29,8 @@ export const useMigration = () => {
       try {
         const isMigrated = await getIsMigrated()
         if (isMigrated) {
+          // moves and converts vectors stored by earlier versions
+          await runVectorStorageMigration()
           return { success: false }
         }
         message.loading(
@@ -3,11 +3,18 @@ import { similarity as ml_distance_similarity } from "ml-distance"
 import { VectorStore } from "@langchain/core/vectorstores"
 import type { EmbeddingsInterface } from "@langchain/core/embeddings"
 import { Document, DocumentInterface } from "@langchain/core/documents"
+import {
+    BM25Document,
+    createBM25Document,
+    reciprocalRankFusion,
+    searchBM25
+} from "./bm25"
 
 interface MemoryVector {
     content: string
     embedding: number[]
     metadata: Record<string, any>
+    keyword: BM25Document
 }
 
 interface MemoryVectorStoreArgs {
@@ -35,7 +42,8 @@ export class PAMemoryVectorStore extends VectorStore {
         const memoryVectors = documents.map((doc, index) => ({
             content: doc.pageContent,
             embedding: vectors[index],
-            metadata: doc.metadata
+            metadata: doc.metadata,
+            keyword: createBM25Document(doc.pageContent)
         }))
 
         this.memoryVectors.push(...memoryVectors)
@@ -91,6 +99,34 @@ export class PAMemoryVectorStore extends VectorStore {
         ])
     }
 
+    async keywordSearch(query: string, k = 4): Promise<Document[]> {
+        const results = searchBM25(
+            query,
+            this.memoryVectors.map((vector) => vector.keyword),
+            k
+        )
+
+        return results.map(({ index }) =>
+            new Document({
+                pageContent: this.memoryVectors[index].content,
+                metadata: this.memoryVectors[index].metadata
+            })
+        )
+    }
+
+    async hybridSearch(query: string, k = 4): Promise<Document[]> {
+        const [vectorDocs, keywordDocs] = await Promise.all([
+            this.similaritySearch(query, k * 2),
+            this.keywordSearch(query, k * 2)
+        ])
+
+        return reciprocalRankFusion(
+            [vectorDocs, keywordDocs],
+            (doc) => doc.pageContent,
+            k
+        ).map(([doc]) => doc)
+    }
+
     static async fromDocuments(
         docs: Document[],
         embeddings: EmbeddingsInterface,
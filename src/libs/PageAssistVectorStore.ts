@@ -2,8 +2,92 @@ import { similarity as ml_distance_similarity } from "ml-distance"
 import { VectorStore } from "@langchain/core/vectorstores"
 import type { EmbeddingsInterface } from "@langchain/core/embeddings"
 import { Document } from "@langchain/core/documents"
-import { getVector, insertVector } from "@/db/dexie/vector"
+import {
+  getParentContents,
+  getVector,
+  insertParentChunks,
+  insertVector
+} from "@/db/dexie/vector"
+import {
+  getKeywordIndex,
+  insertKeywordDocuments,
+  saveKeywordIndex
+} from "@/db/dexie/keyword"
+import type {
+  KeywordDocument,
+  QuantizedEmbedding,
+  RetrievalMode
+} from "@/db/dexie/types"
 import { getMaxContextSize } from "@/services/kb"
+import { annSearch } from "./ann-client"
+import { rankVectors } from "./quantization"
+import {
+  createBM25Document,
+  reciprocalRankFusion,
+  searchBM25,
+  type BM25Stats
+} from "./bm25"
+/**
+ * Keeps the `k` best matches. Chunks indexed with a parent passage are
+ * replaced by that passage, and each passage is returned once even when
+ * several of its chunks match. Passages are looked up in the parent chunks
+ * of `vectorId`; chunks from older versions and from imports carry theirs
+ * in `parent_content`.
+ */
+const toParentResults = async (
+  matches: { content: string; metadata: Record<string, any>; score: number }[],
+  k: number,
+  vectorId?: string
+): Promise<[Document, number][]> => {
+  const parentIds = Array.from(
+    new Set(
+      matches
+        .filter((m) => m.metadata?.parent_id && !m.metadata.parent_content)
+        .map((m) => m.metadata.parent_id as string)
+    )
+  )
+  const parents =
+    vectorId && parentIds.length
+      ? await getParentContents(vectorId, parentIds)
+      : new Map<string, string>()
+
+  const results: [Document, number][] = []
+  const seen = new Set<string>()
+  for (const match of matches) {
+    if (results.length >= k) {
+      break
+    }
+    const { parent_id, parent_content, ...metadata } = match.metadata || {}
+    const passage = parent_content ?? parents.get(parent_id)
+    if (!parent_id || passage === undefined) {
+      results.push([
+        new Document({ pageContent: match.content, metadata: match.metadata }),
+        match.score
+      ])
+      continue
+    }
+    if (seen.has(parent_id)) {
+      continue
+    }
+    seen.add(parent_id)
+    results.push([
+      new Document({
+        pageContent: passage,
+        metadata: { ...metadata, parent_id }
+      }),
+      match.score
+    ])
+  }
+  return results
+}
+
+// chunks stored before `file_id` was part of their metadata take it from
+// their row, knowledge filters and evaluations match on it
+const withFileId = (chunk: {
+  file_id?: string
+  metadata: Record<string, any>
+}) => ({ file_id: chunk.file_id, ...chunk.metadata })
+
 /**
  * Interface representing a vector in memory. It includes the content
  * (text), the corresponding embedding (vector), and any associated
@@ -11,7 +95,10 @@ import { getMaxContextSize } from "@/services/kb"
  */
 interface PageAssistVector {
   content: string
-  embedding: number[]
+  file_id?: string
+  // stored vectors may only hold a quantized embedding
+  embedding?: number[] | Float32Array
+  quantized?: QuantizedEmbedding
   metadata: Record<string, any>
 }
 
@@ -81,18 +168,47 @@ export class PageAssistVectorStore extends VectorStore {
    * @returns Promise that resolves when all vectors have been added.
    */
   async addVectors(vectors: number[][], documents: Document[]): Promise<void> {
-    const memoryVectors = vectors.map((embedding, idx) => ({
-      content: documents[idx].pageContent,
-      embedding,
-      metadata: documents[idx].metadata,
-      file_id: this.file_id
-    }))
-
     // If file_id is "temp_uploaded_files", store in memory instead of database
     if (this.file_id === "temp_uploaded_files") {
-      this.memoryVectors.push(...memoryVectors)
+      this.memoryVectors.push(
+        ...vectors.map((embedding, idx) => ({
+          content: documents[idx].pageContent,
+          embedding,
+          metadata: documents[idx].metadata,
+          file_id: this.file_id
+        }))
+      )
     } else {
+      // parent passages are stored once instead of with every child chunk
+      const parents = new Map<string, string>()
+      const memoryVectors = vectors.map((embedding, idx) => {
+        const { parent_content, ...metadata } = documents[idx].metadata
+        if (metadata.parent_id && parent_content !== undefined) {
+          parents.set(metadata.parent_id, parent_content)
+        }
+        return {
+          content: documents[idx].pageContent,
+          embedding,
+          metadata,
+          file_id: this.file_id
+        }
+      })
+      await insertParentChunks(
+        `vector:${this.knownledge_id}`,
+        this.file_id,
+        parents
+      )
       await insertVector(`vector:${this.knownledge_id}`, memoryVectors)
+      // Keep the lexical index in sync with the stored embeddings
+      await insertKeywordDocuments(
+        `keyword:${this.knownledge_id}`,
+        memoryVectors.map((vector) => ({
+          file_id: vector.file_id,
+          content: vector.content,
+          metadata: vector.metadata,
+          ...createBM25Document(vector.content)
+        }))
+      )
     }
   }
 
@@ -117,12 +233,28 @@ export class PageAssistVectorStore extends VectorStore {
       }
 
       const doc = new Document({
-        metadata: memoryVector.metadata,
+        metadata: withFileId(memoryVector),
         pageContent: memoryVector.content
       })
       return filter(doc)
     }
 
+    // Unfiltered searches over stored knowledge run in the ANN worker,
+    // which keeps the vectors in memory and uses an HNSW graph for large
+    // sets. Extra candidates leave room for chunks sharing a parent.
+    if (this.file_id !== "temp_uploaded_files" && !filter) {
+      try {
+        const matches = await annSearch(
+          `vector:${this.knownledge_id}`,
+          query,
+          k * 4
+        )
+        return toParentResults(matches, k, `vector:${this.knownledge_id}`)
+      } catch (e) {
+        console.error("ANN search failed, falling back to linear scan", e)
+      }
+    }
+
     let pgVector: PageAssistVector[]
 
     // Use memory vectors for temp uploaded files, otherwise get from database
@@ -138,21 +270,18 @@ export class PageAssistVectorStore extends VectorStore {
     }
 
     const filteredMemoryVectors = pgVector.filter(filterFunction)
-    const searches = filteredMemoryVectors
-      .map((vector, index) => ({
-        similarity: this.similarity(query, vector.embedding),
-        index
-      }))
-      .sort((a, b) => (a.similarity > b.similarity ? -1 : 0))
-      .slice(0, k)
-    const result: [Document, number][] = searches.map((search) => [
-      new Document({
-        metadata: filteredMemoryVectors[search.index].metadata,
-        pageContent: filteredMemoryVectors[search.index].content
-      }),
-      search.similarity
-    ])
-    return result
+    // quantized vectors are ranked in their compact form and the best
+    // candidates rescored, extra candidates leave room for shared parents
+    const searches = rankVectors(query, filteredMemoryVectors, k * 4)
+    return toParentResults(
+      searches.map((search) => ({
+        content: filteredMemoryVectors[search.index].content,
+        metadata: withFileId(filteredMemoryVectors[search.index]),
+        score: search.score
+      })),
+      k,
+      `vector:${this.knownledge_id}`
+    )
   }
 
   async getAllPageContent() {
@@ -258,50 +387,133 @@ export class PageAssistVectorStore extends VectorStore {
     this.memoryVectors = []
   }
 
-  async similaritySearchKB(queryTxt: string, k = 4, filter = undefined) {
-    const filterFunction = (memoryVector: PageAssistVector) => {
-      if (!filter) {
-        return true
+  /**
+   * Returns the BM25 documents and corpus statistics for this store, the
+   * statistics are counted on the fly for temporary files. Knowledge bases
+   * embedded before the keyword index existed get their index built from
+   * the stored vectors on first use.
+   */
+  private async getKeywordIndex(): Promise<{
+    documents: KeywordDocument[]
+    stats?: BM25Stats
+  }> {
+    if (this.file_id === "temp_uploaded_files") {
+      return {
+        documents: this.memoryVectors.map((vector) => ({
+          file_id: this.file_id,
+          content: vector.content,
+          metadata: vector.metadata,
+          ...createBM25Document(vector.content)
+        }))
       }
+    }
 
-      const doc = new Document({
-        metadata: memoryVector.metadata,
-        pageContent: memoryVector.content
+    const index = await getKeywordIndex(`keyword:${this.knownledge_id}`)
+    if (index) {
+      return index
+    }
+
+    const data = await getVector(`vector:${this.knownledge_id}`)
+    const documents: KeywordDocument[] = (data?.vectors || []).map(
+      (vector) => ({
+        file_id: vector.file_id,
+        content: vector.content,
+        metadata: vector.metadata,
+        ...createBM25Document(vector.content)
+      })
+    )
+    if (documents.length) {
+      await saveKeywordIndex({
+        id: `keyword:${this.knownledge_id}`,
+        documents
       })
-      return filter(doc)
     }
+    return { documents }
+  }
 
-    let pgVector: PageAssistVector[]
+  async keywordSearchKB(
+    queryTxt: string,
+    k = 4,
+    filter?: this["FilterType"]
+  ) {
+    const index = await this.getKeywordIndex()
+    // the statistics stay those of the whole index when a filter is applied
+    const documents = index.documents.filter((document) => {
+      if (!filter) {
+        return true
+      }
+      return filter(
+        new Document({
+          metadata: withFileId(document),
+          pageContent: document.content
+        })
+      )
+    })
+
+    return (
+      await toParentResults(
+        searchBM25(queryTxt, documents, documents.length, index.stats).map(
+          (search) => ({
+            content: documents[search.index].content,
+            metadata: withFileId(documents[search.index]),
+            score: search.score
+          })
+        ),
+        k,
+        `vector:${this.knownledge_id}`
+      )
+    ).map(([doc]) => doc)
+  }
 
-    // Use memory vectors for temp uploaded files, otherwise get from database
-    if (this.file_id === "temp_uploaded_files") {
-      pgVector = [...this.memoryVectors]
-    } else {
-      const data = await getVector(`vector:${this.knownledge_id}`)
-      pgVector = [...data.vectors]
-    }
+  /**
+   * Runs vector and keyword retrieval and merges both result lists with
+   * reciprocal rank fusion.
+   */
+  async hybridSearchKB(
+    queryTxt: string,
+    k = 4,
+    filter?: this["FilterType"]
+  ) {
+    const candidates = k * 2
+    const [vectorDocs, keywordDocs] = await Promise.all([
+      this.similaritySearchKB(queryTxt, candidates, filter),
+      this.keywordSearchKB(queryTxt, candidates, filter)
+    ])
 
-    if (!pgVector.length) {
-      return []
+    return reciprocalRankFusion(
+      [vectorDocs, keywordDocs],
+      (doc) => doc.pageContent,
+      k
+    ).map(([doc]) => doc)
+  }
+
+  async searchKB(
+    queryTxt: string,
+    k = 4,
+    mode: RetrievalMode = "vector",
+    filter?: this["FilterType"]
+  ) {
+    switch (mode) {
+      case "keyword":
+        return this.keywordSearchKB(queryTxt, k, filter)
+      case "hybrid":
+        return this.hybridSearchKB(queryTxt, k, filter)
+      default:
+        return this.similaritySearchKB(queryTxt, k, filter)
     }
+  }
 
+  async similaritySearchKB(
+    queryTxt: string,
+    k = 4,
+    filter?: this["FilterType"]
+  ) {
     const query = await this.embeddings.embedQuery(queryTxt)
-
-    const filteredMemoryVectors = pgVector.filter(filterFunction)
-    const searches = filteredMemoryVectors
-      .map((vector, index) => ({
-        similarity: this.similarity(query, vector.embedding),
-        index
-      }))
-      .sort((a, b) => (a.similarity > b.similarity ? -1 : 0))
-      .slice(0, k)
-    const result: [Document, number][] = searches.map((search) => [
-      new Document({
-        metadata: filteredMemoryVectors[search.index].metadata,
-        pageContent: filteredMemoryVectors[search.index].content
-      }),
-      search.similarity
-    ])
-    return result.map((result) => result[0])
+    const results = await this.similaritySearchVectorWithScore(
+      query,
+      k,
+      filter
+    )
+    return results.map((result) => result[0])
   }
 }
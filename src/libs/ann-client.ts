@@ -0,0 +1,94 @@
+import { searchAnn, updateAnnIndex, type AnnMatch } from "./ann"
+
+type PendingRequest = {
+  resolve: (value: any) => void
+  reject: (reason: any) => void
+}
+
+let worker: Worker | null | undefined
+let nextRequestId = 0
+const pending = new Map<number, PendingRequest>()
+
+const getWorker = () => {
+  if (worker !== undefined) {
+    return worker
+  }
+  // the background service worker cannot spawn workers
+  if (typeof Worker === "undefined") {
+    worker = null
+    return worker
+  }
+  try {
+    worker = new Worker(new URL("./ann.worker.ts", import.meta.url), {
+      type: "module"
+    })
+    worker.onmessage = (event) => {
+      const { requestId, result, error } = event.data
+      const request = pending.get(requestId)
+      if (!request) {
+        return
+      }
+      pending.delete(requestId)
+      if (error) {
+        request.reject(new Error(error))
+      } else {
+        request.resolve(result)
+      }
+    }
+    worker.onerror = (event) => {
+      console.error("ANN worker failed, searching in page instead", event)
+      for (const request of pending.values()) {
+        request.reject(new Error("ANN worker failed"))
+      }
+      pending.clear()
+      worker?.terminate()
+      worker = null
+    }
+  } catch (e) {
+    console.error("Failed to start ANN worker", e)
+    worker = null
+  }
+  return worker
+}
+
+/**
+ * Runs a request in the ANN worker so that large knowledge bases do not
+ * block the page. Falls back to running it in the current context when no
+ * worker is available or the worker fails.
+ */
+const run = async <T>(
+  request: Record<string, any>,
+  fallback: () => Promise<T>
+): Promise<T> => {
+  const annWorker = getWorker()
+  if (!annWorker) {
+    return fallback()
+  }
+  const requestId = nextRequestId++
+  try {
+    return await new Promise<T>((resolve, reject) => {
+      pending.set(requestId, { resolve, reject })
+      annWorker.postMessage({ ...request, requestId })
+    })
+  } catch (e) {
+    console.error("ANN worker request failed", e)
+    return fallback()
+  }
+}
+
+export const annSearch = (
+  id: string,
+  query: number[],
+  k: number
+): Promise<AnnMatch[]> => {
+  return run({ type: "search", id, query, k }, () => searchAnn(id, query, k))
+}
+
+export const annUpdate = (
+  id: string,
+  changed: number[] = []
+): Promise<void> => {
+  return run({ type: "update", id, changed }, () =>
+    updateAnnIndex(id, changed)
+  )
+}
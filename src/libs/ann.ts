@@ -0,0 +1,192 @@
+import { db } from "@/db/dexie/schema"
+import {
+  getAnnIndex,
+  getAnnIndexVersion,
+  saveAnnIndex
+} from "@/db/dexie/ann"
+import type {
+  AnnIndexData,
+  PageAssistVector,
+  VectorChunk
+} from "@/db/dexie/types"
+import {
+  createHNSWGraph,
+  getHNSWNodes,
+  insertHNSWNode,
+  removeHNSWNodes,
+  searchHNSW,
+  type HNSWGraph
+} from "./hnsw"
+import { decodeEmbedding, rankVectors } from "./quantization"
+
+// below this size a linear scan is fast enough and no graph is kept
+export const ANN_MIN_VECTORS = 2000
+
+export type AnnMatch = {
+  content: string
+  metadata: Record<string, any>
+  score: number
+}
+
+type CachedIndex = {
+  version: number
+  chunks: Map<number, VectorChunk>
+  graph: HNSWGraph
+  // decoded lazily, quantized embeddings are scaled back for the graph
+  decoded: Map<number, number[]>
+}
+
+// chunks and graphs stay in memory between queries until the stored
+// index version changes
+const cache = new Map<string, CachedIndex>()
+
+const getChunks = async (id: string) => {
+  const chunks = await db.vectorChunks.where("vector_id").equals(id).toArray()
+  return new Map(chunks.map((chunk) => [chunk.id!, chunk]))
+}
+
+// older chunks only have their file id on the row
+const toMatch = (vector: PageAssistVector, score: number): AnnMatch => ({
+  content: vector.content,
+  metadata: { file_id: vector.file_id, ...vector.metadata },
+  score
+})
+
+const bruteForceSearch = (
+  vectors: PageAssistVector[],
+  query: number[],
+  k: number
+): AnnMatch[] => {
+  return rankVectors(query, vectors, k).map(({ index, score }) =>
+    toMatch(vectors[index], score)
+  )
+}
+
+const createVectorAccessor = (
+  chunks: Map<number, VectorChunk>,
+  decoded: Map<number, number[]>
+) => {
+  return (id: number) => {
+    let vector = decoded.get(id)
+    if (!vector) {
+      vector = decodeEmbedding(chunks.get(id)!)
+      decoded.set(id, vector)
+    }
+    return vector
+  }
+}
+
+/**
+ * Brings a graph in line with the chunks of its set: nodes of deleted and
+ * `changed` chunks are removed and chunks that are not in the graph yet are
+ * inserted. Returns the existing index when nothing changed.
+ */
+const syncIndex = (
+  id: string,
+  chunks: Map<number, VectorChunk>,
+  decoded: Map<number, number[]>,
+  existing?: AnnIndexData,
+  changed: number[] = []
+): AnnIndexData => {
+  const graph = existing?.graph || createHNSWGraph()
+  const getVector = createVectorAccessor(chunks, decoded)
+  const stale = new Set(changed)
+  const removed = getHNSWNodes(graph).filter(
+    (node) => !chunks.has(node) || stale.has(node)
+  )
+  removeHNSWNodes(graph, getVector, removed)
+  let modified = !existing || removed.length > 0
+  for (const chunkId of chunks.keys()) {
+    if (graph.levels[chunkId] === undefined) {
+      insertHNSWNode(graph, getVector, chunkId)
+      modified = true
+    }
+  }
+  if (existing && !modified) {
+    return existing
+  }
+  return {
+    id,
+    version: Date.now(),
+    count: chunks.size,
+    graph
+  }
+}
+
+const isInSync = (index: AnnIndexData, chunkIds: number[]) =>
+  index.count === chunkIds.length &&
+  chunkIds.every((chunkId) => index.graph.levels[chunkId] !== undefined)
+
+/**
+ * Brings the graph of a vector set up to date after chunks were added,
+ * deleted or `changed`. Sets below `ANN_MIN_VECTORS` are left without a
+ * graph.
+ */
+export const updateAnnIndex = async (
+  id: string,
+  changed: number[] = []
+): Promise<void> => {
+  const chunkIds = (await db.vectorChunks
+    .where("vector_id")
+    .equals(id)
+    .primaryKeys()) as number[]
+  const existing = await getAnnIndex(id)
+
+  if (!existing && chunkIds.length < ANN_MIN_VECTORS) {
+    return
+  }
+  if (existing && changed.length === 0 && isInSync(existing, chunkIds)) {
+    return
+  }
+
+  const chunks = await getChunks(id)
+  const decoded = new Map<number, number[]>()
+  const index = syncIndex(id, chunks, decoded, existing, changed)
+  await saveAnnIndex(index)
+  cache.set(id, {
+    version: index.version,
+    chunks,
+    graph: index.graph,
+    decoded
+  })
+}
+
+/**
+ * Returns the `k` vectors most similar to `query`. Large sets are searched
+ * through their HNSW graph, which is built on first use when missing.
+ */
+export const searchAnn = async (
+  id: string,
+  query: number[],
+  k: number
+): Promise<AnnMatch[]> => {
+  const version = await getAnnIndexVersion(id)
+  let cached = cache.get(id)
+
+  if (!version || cached?.version !== version) {
+    const chunks = await getChunks(id)
+    if (chunks.size < ANN_MIN_VECTORS) {
+      cache.delete(id)
+      // sets not moved to chunk rows yet are searched from their legacy row
+      const vectors = chunks.size
+        ? Array.from(chunks.values())
+        : (await db.vectors.get(id))?.vectors || []
+      return bruteForceSearch(vectors, query, k)
+    }
+
+    const decoded = new Map<number, number[]>()
+    const existing = version ? await getAnnIndex(id) : undefined
+    const index = syncIndex(id, chunks, decoded, existing)
+    if (index !== existing) {
+      await saveAnnIndex(index)
+    }
+    cached = { version: index.version, chunks, graph: index.graph, decoded }
+    cache.set(id, cached)
+  }
+
+  const { chunks, graph, decoded } = cached
+  const getVector = createVectorAccessor(chunks, decoded)
+  return searchHNSW(graph, getVector, query, k).map((result) =>
+    toMatch(chunks.get(result.id)!, result.similarity)
+  )
+}
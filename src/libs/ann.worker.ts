@@ -0,0 +1,27 @@
+import { searchAnn, updateAnnIndex } from "./ann"
+
+type AnnRequest =
+  | {
+      requestId: number
+      type: "search"
+      id: string
+      query: number[]
+      k: number
+    }
+  | { requestId: number; type: "update"; id: string; changed: number[] }
+
+self.onmessage = async (event: MessageEvent<AnnRequest>) => {
+  const request = event.data
+  try {
+    const result =
+      request.type === "search"
+        ? await searchAnn(request.id, request.query, request.k)
+        : await updateAnnIndex(request.id, request.changed)
+    self.postMessage({ requestId: request.requestId, result })
+  } catch (error) {
+    self.postMessage({
+      requestId: request.requestId,
+      error: error?.message || String(error)
+    })
+  }
+}
@@ -0,0 +1,177 @@
+/**
+ * Minimal BM25 implementation used for lexical retrieval over knowledge
+ * base chunks. Exact identifiers (error codes, function names, SKUs) are
+ * kept as whole tokens in addition to their parts so they can be matched
+ * verbatim.
+ */
+
+export type BM25Document = {
+  terms: Record<string, number>
+  length: number
+}
+
+// corpus statistics, stored with an index so searches do not recount them
+export type BM25Stats = {
+  count: number
+  totalLength: number
+  // number of documents that contain each term
+  df: Record<string, number>
+}
+
+const K1 = 1.2
+const B = 0.75
+
+// Reciprocal rank fusion constant, see Cormack et al. (2009)
+const RRF_K = 60
+
+const WORD_REGEX = /[\p{L}\p{N}]+(?:[._\-:/#][\p{L}\p{N}]+)*/gu
+const CJK_REGEX =
+  /[\p{Script=Han}\p{Script=Hiragana}\p{Script=Katakana}\p{Script=Hangul}]/u
+
+export const tokenize = (text: string): string[] => {
+  const tokens: string[] = []
+  const matches = text.normalize("NFKC").match(WORD_REGEX) || []
+
+  for (const match of matches) {
+    // CJK text has no whitespace, index it per character
+    if (CJK_REGEX.test(match)) {
+      for (const char of match) {
+        tokens.push(char.toLowerCase())
+      }
+      continue
+    }
+
+    const word = match.toLowerCase()
+    tokens.push(word)
+
+    const parts = match
+      .split(/[._\-:/#]/)
+      .flatMap((part) => part.split(/(?<=[a-z0-9])(?=[A-Z])/))
+      .map((part) => part.toLowerCase())
+      .filter((part) => part.length > 0)
+
+    if (parts.length > 1) {
+      tokens.push(...parts)
+    }
+  }
+
+  return tokens
+}
+
+export const createBM25Document = (text: string): BM25Document => {
+  const tokens = tokenize(text)
+  const terms: Record<string, number> = {}
+  for (const token of tokens) {
+    terms[token] = (terms[token] || 0) + 1
+  }
+  return {
+    terms,
+    length: tokens.length
+  }
+}
+
+/**
+ * Adds documents to the statistics, or removes them when `sign` is -1.
+ */
+export const updateBM25Stats = (
+  stats: BM25Stats,
+  documents: BM25Document[],
+  sign: 1 | -1 = 1
+) => {
+  for (const doc of documents) {
+    stats.count += sign
+    stats.totalLength += sign * doc.length
+    for (const term in doc.terms) {
+      const df = (stats.df[term] || 0) + sign
+      if (df > 0) {
+        stats.df[term] = df
+      } else {
+        delete stats.df[term]
+      }
+    }
+  }
+  return stats
+}
+
+export const createBM25Stats = (documents: BM25Document[]): BM25Stats =>
+  updateBM25Stats({ count: 0, totalLength: 0, df: {} }, documents)
+
+/**
+ * Scores every document against the query and returns the indexes of the
+ * top `k` matches, best first. Documents without any matching term are
+ * dropped. Without `stats` the statistics are counted over `documents`.
+ */
+export const searchBM25 = (
+  query: string,
+  documents: BM25Document[],
+  k: number,
+  stats?: BM25Stats
+): { index: number; score: number }[] => {
+  const queryTerms = Array.from(new Set(tokenize(query)))
+  if (!queryTerms.length || !documents.length) {
+    return []
+  }
+
+  const count = stats?.count || documents.length
+  const totalLength = stats
+    ? stats.totalLength
+    : documents.reduce((acc, doc) => acc + doc.length, 0)
+  const avgLength = totalLength / count || 1
+
+  const idf: Record<string, number> = {}
+  for (const term of queryTerms) {
+    const df = stats
+      ? stats.df[term] || 0
+      : documents.reduce((acc, doc) => (doc.terms[term] ? acc + 1 : acc), 0)
+    idf[term] = Math.log(1 + (Math.max(count - df, 0) + 0.5) / (df + 0.5))
+  }
+
+  const results: { index: number; score: number }[] = []
+  documents.forEach((doc, index) => {
+    let score = 0
+    for (const term of queryTerms) {
+      const tf = doc.terms[term]
+      if (!tf) {
+        continue
+      }
+      score +=
+        (idf[term] * (tf * (K1 + 1))) /
+        (tf + K1 * (1 - B + B * (doc.length / avgLength)))
+    }
+    if (score > 0) {
+      results.push({ index, score })
+    }
+  })
+
+  return results.sort((a, b) => b.score - a.score).slice(0, k)
+}
+
+/**
+ * Merges several ranked lists into one using reciprocal rank fusion.
+ * `getKey` identifies the same item across lists.
+ */
+export const reciprocalRankFusion = <T>(
+  lists: T[][],
+  getKey: (item: T) => string,
+  k: number
+): [T, number][] => {
+  const scores = new Map<string, { item: T; score: number }>()
+
+  for (const list of lists) {
+    list.forEach((item, rank) => {
+      const key = getKey(item)
+      const existing = scores.get(key)
+      const score = 1 / (RRF_K + rank + 1)
+      if (existing) {
+        existing.score += score
+      } else {
+        scores.set(key, { item, score })
+      }
+    })
+  }
+
+  return Array.from(scores.values())
+    .sort((a, b) => b.score - a.score)
+    .slice(0, k)
+    .map(({ item, score }) => [item, score])
+}
@@ -0,0 +1,477 @@
+// Page actions of the browser agent. The functions starting with an
+// underscore run inside the page through `browser.scripting.executeScript`,
+// so they can not use anything from their enclosing scope.
+
+// the snapshot stops after this many lines
+const MAX_SNAPSHOT_LINES = 400
+// waits this long for a page load started by an action
+const LOAD_TIMEOUT = 15_000
+
+export type PageSnapshot = {
+  title: string
+  url: string
+  tree: string
+  truncated: boolean
+}
+
+export type ElementInfo = {
+  found: boolean
+  description?: string
+  // it is inside a form, so clicking it or pressing Enter in it can submit
+  // the form
+  submits?: boolean
+}
+
+/**
+ * Builds an accessibility tree of the page. Interactive elements get a
+ * numbered ref (`data-pa-ref`) which the other actions take, refs are
+ * assigned again with every snapshot.
+ */
+const _snapshotPage = (maxLines: number) => {
+  const SKIP = new Set([
+    "script",
+    "style",
+    "noscript",
+    "template",
+    "svg",
+    "canvas",
+    "iframe",
+    "head"
+  ])
+  const LANDMARKS: Record<string, string> = {
+    nav: "navigation",
+    main: "main",
+    header: "banner",
+    footer: "contentinfo",
+    aside: "complementary",
+    form: "form",
+    dialog: "dialog",
+    ul: "list",
+    ol: "list",
+    table: "table",
+    tr: "row"
+  }
+  const clean = (text: string | null | undefined, max = 80) => {
+    const value = (text || "").replace(/\s+/g, " ").trim()
+    return value.length > max ? `${value.slice(0, max)}…` : value
+  }
+
+  const isHidden = (el: Element) => {
+    if (
+      el.getAttribute("aria-hidden") === "true" ||
+      el.hasAttribute("hidden")
+    ) {
+      return true
+    }
+    const style = getComputedStyle(el)
+    return style.display === "none" || style.visibility === "hidden"
+  }
+
+  const roleOf = (el: Element): string | null => {
+    const explicit = el.getAttribute("role")
+    if (explicit) {
+      return explicit.split(" ")[0]
+    }
+    const tag = el.tagName.toLowerCase()
+    const type = (el.getAttribute("type") || "text").toLowerCase()
+    switch (tag) {
+      case "a":
+        return el.hasAttribute("href") ? "link" : null
+      case "button":
+      case "summary":
+        return "button"
+      case "select":
+        return "combobox"
+      case "textarea":
+        return "textbox"
+      case "input":
+        if (type === "hidden") {
+          return null
+        }
+        if (["submit", "button", "reset", "image"].includes(type)) {
+          return "button"
+        }
+        if (type === "checkbox" || type === "radio") {
+          return type
+        }
+        if (type === "range") {
+          return "slider"
+        }
+        return type === "search" ? "searchbox" : "textbox"
+      case "img":
+        return el.getAttribute("alt") ? "img" : null
+    }
+    if (/^h[1-6]$/.test(tag)) {
+      return "heading"
+    }
+    if ((el as HTMLElement).isContentEditable) {
+      return "textbox"
+    }
+    return LANDMARKS[tag] || null
+  }
+
+  const INTERACTIVE = new Set([
+    "link",
+    "button",
+    "checkbox",
+    "radio",
+    "combobox",
+    "textbox",
+    "searchbox",
+    "slider",
+    "switch",
+    "tab",
+    "menuitem",
+    "option"
+  ])
+
+  const nameOf = (el: Element) => {
+    const label = el.getAttribute("aria-label")
+    if (label) {
+      return clean(label)
+    }
+    const labelledBy = el.getAttribute("aria-labelledby")
+    if (labelledBy) {
+      const text = labelledBy
+        .split(" ")
+        .map((id) => document.getElementById(id)?.textContent)
+        .join(" ")
+      if (text.trim()) {
+        return clean(text)
+      }
+    }
+    const field = el as HTMLInputElement
+    if (field.labels?.length) {
+      return clean(field.labels[0].innerText)
+    }
+    return clean(
+      el.getAttribute("alt") ||
+        (el as HTMLElement).innerText ||
+        field.placeholder ||
+        el.getAttribute("title") ||
+        (field.type === "submit" ? field.value : "") ||
+        el.getAttribute("name")
+    )
+  }
+
+  const stateOf = (el: Element, role: string) => {
+    const field = el as HTMLInputElement
+    const states: string[] = []
+    if (role === "textbox" || role === "searchbox") {
+      const value = field.isContentEditable ? field.innerText : field.value
+      if (value) {
+        states.push(`value="${clean(value, 60)}"`)
+      }
+    }
+    if (role === "combobox" && el.tagName === "SELECT") {
+      const select = el as unknown as HTMLSelectElement
+      states.push(
+        `value="${clean(select.selectedOptions[0]?.text, 60)}"`,
+        `options=[${Array.from(select.options)
+          .slice(0, 15)
+          .map((option) => `"${clean(option.text, 30)}"`)
+          .join(", ")}]`
+      )
+    }
+    if (field.checked || el.getAttribute("aria-checked") === "true") {
+      states.push("checked")
+    }
+    if (field.disabled || el.getAttribute("aria-disabled") === "true") {
+      states.push("disabled")
+    }
+    const expanded = el.getAttribute("aria-expanded")
+    if (expanded) {
+      states.push(expanded === "true" ? "expanded" : "collapsed")
+    }
+    if (/^H[1-6]$/.test(el.tagName)) {
+      states.push(`level=${el.tagName[1]}`)
+    }
+    return states.length ? ` [${states.join(", ")}]` : ""
+  }
+
+  document
+    .querySelectorAll("[data-pa-ref]")
+    .forEach((el) => el.removeAttribute("data-pa-ref"))
+
+  const lines: string[] = []
+  let ref = 0
+  let truncated = false
+
+  const walk = (el: Element, depth: number) => {
+    if (lines.length >= maxLines) {
+      truncated = true
+      return
+    }
+    const tag = el.tagName.toLowerCase()
+    if (SKIP.has(tag) || isHidden(el)) {
+      return
+    }
+    const indent = "  ".repeat(depth)
+    const role = roleOf(el)
+
+    if (role && INTERACTIVE.has(role)) {
+      ref++
+      el.setAttribute("data-pa-ref", String(ref))
+      lines.push(
+        `${indent}[${ref}] ${role} "${nameOf(el)}"${stateOf(el, role)}`
+      )
+      return
+    }
+    if (role === "heading" || role === "img") {
+      lines.push(`${indent}${role} "${nameOf(el)}"${stateOf(el, role)}`)
+      // links inside headings, e.g. search result titles
+      if (role === "heading" && el.querySelector("a[href], button")) {
+        Array.from(el.children).forEach((child) => walk(child, depth + 1))
+      }
+      return
+    }
+
+    let childDepth = depth
+    if (role) {
+      const name = el.getAttribute("aria-label")
+      lines.push(`${indent}${role}${name ? ` "${clean(name)}"` : ""}`)
+      childDepth++
+    }
+
+    const text = clean(
+      Array.from(el.childNodes)
+        .filter((node) => node.nodeType === Node.TEXT_NODE)
+        .map((node) => node.textContent)
+        .join(" "),
+      200
+    )
+    if (text) {
+      lines.push(`${"  ".repeat(childDepth)}text "${text}"`)
+    }
+
+    const children = [
+      ...Array.from(el.shadowRoot?.children || []),
+      ...Array.from(el.children)
+    ]
+    for (const child of children) {
+      walk(child, childDepth)
+    }
+  }
+
+  walk(document.body, 0)
+
+  const { scrollY, innerHeight } = window
+  const height = document.documentElement.scrollHeight
+  lines.unshift(
+    `scroll: ${Math.round(scrollY)} of ${Math.max(height - innerHeight, 0)}px`
+  )
+
+  return {
+    title: document.title,
+    url: location.href,
+    tree: lines.join("\n"),
+    truncated
+  }
+}
+
+const _describeElement = (ref: number) => {
+  const el = document.querySelector<HTMLElement>(`[data-pa-ref="${ref}"]`)
+  if (!el) {
+    return { found: false }
+  }
+  const tag = el.tagName.toLowerCase()
+  const type = (el.getAttribute("type") || "").toLowerCase()
+  // scripts can submit a form from any of its elements, not only from
+  // submit buttons
+  const submits = !!((el as HTMLInputElement).form || el.closest("form"))
+  const name = (
+    el.getAttribute("aria-label") ||
+    el.innerText ||
+    (el as HTMLInputElement).placeholder ||
+    (el as HTMLInputElement).value ||
+    ""
+  )
+    .replace(/\s+/g, " ")
+    .trim()
+    .slice(0, 80)
+  return {
+    found: true,
+    description: `${tag}${name ? ` "${name}"` : ""}`,
+    submits
+  }
+}
+
+const _clickElement = (ref: number) => {
+  const el = document.querySelector<HTMLElement>(`[data-pa-ref="${ref}"]`)
+  if (!el) {
+    return false
+  }
+  el.scrollIntoView({ block: "center" })
+  el.focus?.()
+  el.click()
+  return true
+}
+
+// returns an error message, exceptions do not leave the page
+const _typeText = (ref: number, text: string, clear: boolean) => {
+  const el = document.querySelector<HTMLElement>(`[data-pa-ref="${ref}"]`)
+  if (!el) {
+    return `No element with ref ${ref}`
+  }
+  el.scrollIntoView({ block: "center" })
+  el.focus()
+
+  if (el instanceof HTMLSelectElement) {
+    const option = Array.from(el.options).find(
+      (option) =>
+        option.value === text ||
+        option.text.trim().toLowerCase() === text.trim().toLowerCase()
+    )
+    if (!option) {
+      return `No option "${text}"`
+    }
+    el.value = option.value
+  } else if (el.isContentEditable) {
+    if (clear) {
+      document.execCommand("selectAll")
+    }
+    document.execCommand("insertText", false, text)
+  } else {
+    const field = el as HTMLInputElement
+    // frameworks like React track the value setter of the prototype
+    const setter = Object.getOwnPropertyDescriptor(
+      Object.getPrototypeOf(field),
+      "value"
+    )?.set
+    const value = clear ? text : field.value + text
+    setter ? setter.call(field, value) : (field.value = value)
+  }
+  el.dispatchEvent(new Event("input", { bubbles: true }))
+  el.dispatchEvent(new Event("change", { bubbles: true }))
+  return null
+}
+
+// presses Enter in the element and submits its form when nothing handled it
+const _pressEnter = (ref: number) => {
+  const el = document.querySelector<HTMLElement>(`[data-pa-ref="${ref}"]`)
+  if (!el) {
+    return false
+  }
+  const init = { key: "Enter", code: "Enter", keyCode: 13, bubbles: true }
+  const handled = !el.dispatchEvent(
+    new KeyboardEvent("keydown", { ...init, cancelable: true })
+  )
+  el.dispatchEvent(new KeyboardEvent("keypress", init))
+  el.dispatchEvent(new KeyboardEvent("keyup", init))
+  const form = (el as HTMLInputElement).form || el.closest("form")
+  if (!handled && form) {
+    form.requestSubmit ? form.requestSubmit() : form.submit()
+  }
+  return true
+}
+
+const _scrollPage = (direction: string, ref?: number) => {
+  if (ref) {
+    const el = document.querySelector(`[data-pa-ref="${ref}"]`)
+    if (!el) {
+      return false
+    }
+    el.scrollIntoView({ block: "center" })
+    return true
+  }
+  const page = window.innerHeight * 0.8
+  switch (direction) {
+    case "top":
+      window.scrollTo(0, 0)
+      break
+    case "bottom":
+      window.scrollTo(0, document.documentElement.scrollHeight)
+      break
+    default:
+      window.scrollBy(0, direction === "up" ? -page : page)
+  }
+  return true
+}
+
+const _extractText = (ref: number) => {
+  const el = document.querySelector<HTMLElement>(`[data-pa-ref="${ref}"]`)
+  return el ? el.innerText : null
+}
+
+const runInTab = async <T>(
+  tabId: number,
+  func: (...args: any[]) => T,
+  args: any[] = []
+): Promise<T> => {
+  const [result] = await browser.scripting.executeScript({
+    target: { tabId },
+    func,
+    args
+  })
+  return result?.result as T
+}
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms))
+
+/**
+ * Waits for a page load started by the last action, if there is one.
+ */
+export const waitForPage = async (tabId: number) => {
+  await sleep(500)
+  const tab = await browser.tabs.get(tabId)
+  if (tab.status !== "loading") {
+    return
+  }
+  await new Promise<void>((resolve) => {
+    const done = () => {
+      clearTimeout(timeout)
+      browser.tabs.onUpdated.removeListener(listener)
+      resolve()
+    }
+    const listener = (id: number, info: { status?: string }) => {
+      if (id === tabId && info.status === "complete") {
+        done()
+      }
+    }
+    const timeout = setTimeout(done, LOAD_TIMEOUT)
+    browser.tabs.onUpdated.addListener(listener)
+  })
+}
+
+export const snapshotPage = async (tabId: number): Promise<PageSnapshot> =>
+  runInTab(tabId, _snapshotPage, [MAX_SNAPSHOT_LINES])
+
+export const formatSnapshot = (snapshot: PageSnapshot) =>
+  `Page: ${snapshot.title}\nURL: ${snapshot.url}\n\n${snapshot.tree}${
+    snapshot.truncated ? "\n[snapshot truncated, scroll to see more]" : ""
+  }`
+
+export const describeElement = (tabId: number, ref: number) =>
+  runInTab<ElementInfo>(tabId, _describeElement, [ref])
+
+export const clickElement = (tabId: number, ref: number) =>
+  runInTab<boolean>(tabId, _clickElement, [ref])
+
+export const typeText = (
+  tabId: number,
+  ref: number,
+  text: string,
+  clear = true
+) => runInTab<string | null>(tabId, _typeText, [ref, text, clear])
+
+export const pressEnter = (tabId: number, ref: number) =>
+  runInTab<boolean>(tabId, _pressEnter, [ref])
+
+export const scrollPage = (tabId: number, direction: string, ref?: number) =>
+  runInTab<boolean>(tabId, _scrollPage, [direction, ref ?? null])
+
+export const extractText = (tabId: number, ref: number) =>
+  runInTab<string | null>(tabId, _extractText, [ref])
+
+export const navigateTab = async (tabId: number, url: string) => {
+  await browser.tabs.update(tabId, { url })
+  await waitForPage(tabId)
+}
+
+export const getTabUrl = async (tabId: number) =>
+  (await browser.tabs.get(tabId)).url
+
+export const goBack = async (tabId: number) => {
+  await browser.tabs.goBack(tabId)
+  await waitForPage(tabId)
+}
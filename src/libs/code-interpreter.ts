@@ -0,0 +1,176 @@
+import { isPythonPackageDownloadEnabled } from "@/services/tools"
+
+export type CodeLanguage = "javascript" | "python"
+
+export type CodeRequest = {
+  type: "run"
+  id: number
+  language: CodeLanguage
+  code: string
+  // ms after which the sandbox stops the run
+  timeout: number
+  // whether Python may download the imported packages from the Pyodide CDN
+  downloadPackages: boolean
+}
+
+export type CodeResult = {
+  // console output, stdout and stderr
+  stdout: string
+  // value of the last Python expression or what the JavaScript returned
+  result?: string
+  error?: string
+  // charts as data URLs
+  images: string[]
+}
+
+// PubSub topic, the chat forms add the published text to their input
+export const CODE_OUTPUT_INSERT = "code-output-insert"
+
+const SANDBOX_LOAD_TIMEOUT = 10 * 1000
+// extra time for the sandbox to stop a run before the sandbox is removed
+const SANDBOX_STOP_TIMEOUT = 5 * 1000
+const JAVASCRIPT_TIMEOUT = 30 * 1000
+// the first Python run loads Pyodide and the imported packages
+const PYTHON_TIMEOUT = 120 * 1000
+
+const LANGUAGES: Record<string, CodeLanguage> = {
+  javascript: "javascript",
+  js: "javascript",
+  python: "python",
+  py: "python",
+  python3: "python"
+}
+
+/**
+ * Sandboxed pages are Chromium only, so Firefox has no interpreter.
+ */
+export const isCodeInterpreterSupported = () =>
+  import.meta.env.BROWSER !== "firefox"
+
+// the interpreter language of a code block, null when it can not run
+export const getCodeLanguage = (language?: string): CodeLanguage | null =>
+  (isCodeInterpreterSupported() && LANGUAGES[(language || "").toLowerCase()]) ||
+  null
+
+type PendingRun = {
+  resolve: (result: CodeResult) => void
+  reject: (reason: any) => void
+}
+
+let sandbox: Promise<HTMLIFrameElement> | null = null
+let removeSandbox: (() => void) | null = null
+let nextRunId = 0
+const pending = new Map<number, PendingRun>()
+
+const resetSandbox = (reason: string) => {
+  for (const run of pending.values()) {
+    run.reject(new Error(reason))
+  }
+  pending.clear()
+  removeSandbox?.()
+  removeSandbox = null
+  sandbox = null
+}
+
+const getSandbox = () => {
+  if (sandbox) {
+    return sandbox
+  }
+  sandbox = new Promise<HTMLIFrameElement>((resolve, reject) => {
+    const frame = document.createElement("iframe")
+    frame.src = browser.runtime.getURL("/sandbox.html")
+    frame.setAttribute("sandbox", "allow-scripts")
+    frame.style.display = "none"
+
+    const timer = setTimeout(() => {
+      reject(new Error("Failed to load the code sandbox"))
+      resetSandbox("Failed to load the code sandbox")
+    }, SANDBOX_LOAD_TIMEOUT)
+    const onMessage = (event: MessageEvent) => {
+      if (event.source !== frame.contentWindow) {
+        return
+      }
+      const { type, id, result } = event.data || {}
+      if (type === "ready") {
+        clearTimeout(timer)
+        resolve(frame)
+      } else if (type === "result") {
+        pending.get(id)?.resolve(result)
+        pending.delete(id)
+      }
+    }
+    window.addEventListener("message", onMessage)
+    removeSandbox = () => {
+      clearTimeout(timer)
+      window.removeEventListener("message", onMessage)
+      frame.remove()
+    }
+    document.body.appendChild(frame)
+  })
+  return sandbox
+}
+
+/**
+ * Runs code in the sandbox page, an iframe with an opaque origin and no
+ * extension APIs. The sandbox stops a run that does not finish in time, the
+ * sandbox itself is removed when it does not answer after that.
+ */
+export const runCode = async (
+  language: CodeLanguage,
+  code: string
+): Promise<CodeResult> => {
+  if (!isCodeInterpreterSupported()) {
+    throw new Error("The code interpreter is not available in this browser")
+  }
+  const frame = await getSandbox()
+  const downloadPackages =
+    language === "python" && (await isPythonPackageDownloadEnabled())
+  const id = nextRunId++
+  const timeout = language === "python" ? PYTHON_TIMEOUT : JAVASCRIPT_TIMEOUT
+  return new Promise<CodeResult>((resolve, reject) => {
+    const timer = setTimeout(
+      () => resetSandbox(`The code did not finish in ${timeout / 1000}s`),
+      timeout + SANDBOX_STOP_TIMEOUT
+    )
+    pending.set(id, {
+      resolve: (result) => {
+        clearTimeout(timer)
+        resolve(result)
+      },
+      reject: (reason) => {
+        clearTimeout(timer)
+        reject(reason)
+      }
+    })
+    const request: CodeRequest = {
+      type: "run",
+      id,
+      language,
+      code,
+      timeout,
+      downloadPackages
+    }
+    frame.contentWindow?.postMessage(request, "*")
+  })
+}
+
+/**
+ * The result as text for the model or the chat input. Charts are only
+ * counted, the model can not see them.
+ */
+export const formatCodeResult = (result: CodeResult) => {
+  const parts: string[] = []
+  if (result.stdout) {
+    parts.push(`Output:\n\`\`\`\n${result.stdout}\n\`\`\``)
+  }
+  if (result.result !== undefined) {
+    parts.push(`Result:\n\`\`\`\n${result.result}\n\`\`\``)
+  }
+  if (result.error) {
+    parts.push(`Error:\n\`\`\`\n${result.error}\n\`\`\``)
+  }
+  if (result.images.length) {
+    parts.push(`${result.images.length} chart(s) shown to the user.`)
+  }
+  return parts.join("\n\n") || "The code ran without output."
+}
@@ -0,0 +1,352 @@
+import type { CrawlOptions, Source } from "@/db/dexie/types"
+import { generateSourceId } from "@/utils/to-source"
+import {
+  extractPageFromHtml,
+  type CrawledPage,
+  type PageExtractor
+} from "./extract-page"
+
+const USER_AGENT = "PageAssist"
+// upper bound for the Crawl-delay a site may ask for, in seconds
+const MAX_CRAWL_DELAY = 10
+const MAX_NESTED_SITEMAPS = 5
+const SKIPPED_EXTENSIONS =
+  /\.(pdf|zip|gz|tar|rar|7z|png|jpe?g|gif|svg|webp|ico|mp3|mp4|webm|avi|mov|css|js|json|xml|woff2?|ttf|exe|dmg)$/i
+
+export const DEFAULT_CRAWL_OPTIONS: CrawlOptions = {
+  maxDepth: 2,
+  maxPages: 50,
+  sameOrigin: true
+}
+
+/**
+ * Creates a pending crawl source. The website is crawled when the knowledge
+ * base is processed.
+ */
+export const createCrawlSource = (
+  url: string,
+  crawl: CrawlOptions
+): Source => ({
+  source_id: generateSourceId(),
+  type: "crawl",
+  filename: url,
+  content: url,
+  sourceType: "crawl",
+  crawl
+})
+
+type RobotsRules = {
+  allow: string[]
+  disallow: string[]
+  crawlDelay?: number
+  sitemaps: string[]
+}
+
+type RobotsGroup = Omit<RobotsRules, "sitemaps"> & { agents: string[] }
+
+const parseRobots = (text: string): RobotsRules => {
+  const groups: RobotsGroup[] = []
+  const sitemaps: string[] = []
+  let current: RobotsGroup | null = null
+  let lastWasAgent = false
+
+  for (const raw of text.split(/\r?\n/)) {
+    const line = raw.replace(/#.*$/, "").trim()
+    const separator = line.indexOf(":")
+    if (separator === -1) {
+      continue
+    }
+    const key = line.slice(0, separator).trim().toLowerCase()
+    const value = line.slice(separator + 1).trim()
+
+    if (key === "sitemap") {
+      sitemaps.push(value)
+      continue
+    }
+    if (key === "user-agent") {
+      // consecutive user-agent lines share one group
+      if (!current || !lastWasAgent) {
+        current = { agents: [], allow: [], disallow: [] }
+        groups.push(current)
+      }
+      current.agents.push(value.toLowerCase())
+      lastWasAgent = true
+      continue
+    }
+    lastWasAgent = false
+    if (!current) {
+      continue
+    }
+    if (key === "allow" && value) {
+      current.allow.push(value)
+    } else if (key === "disallow" && value) {
+      current.disallow.push(value)
+    } else if (key === "crawl-delay") {
+      const delay = parseFloat(value)
+      if (!isNaN(delay)) {
+        current.crawlDelay = delay
+      }
+    }
+  }
+
+  const agent = USER_AGENT.toLowerCase()
+  const group =
+    groups.find((g) => g.agents.some((a) => a !== "*" && agent.includes(a))) ??
+    groups.find((g) => g.agents.includes("*"))
+
+  return {
+    allow: group?.allow ?? [],
+    disallow: group?.disallow ?? [],
+    crawlDelay: group?.crawlDelay,
+    sitemaps
+  }
+}
+
+const matchesRule = (path: string, rule: string) => {
+  const pattern = rule
+    .replace(/[.+?^{}()|[\]\\]/g, "\\$&")
+    .replace(/\*/g, ".*")
+    .replace(/\\?\$$/, "$")
+  return new RegExp(`^${pattern}`).test(path)
+}
+
+// the longest matching rule wins, Allow wins ties
+const isAllowedByRobots = (rules: RobotsRules, url: URL) => {
+  const path = `${url.pathname}${url.search}`
+  const longest = (list: string[]) =>
+    Math.max(
+      -1,
+      ...list.filter((r) => matchesRule(path, r)).map((r) => r.length)
+    )
+  return longest(rules.allow) >= longest(rules.disallow)
+}
+
+const fetchRobots = async (origin: string): Promise<RobotsRules> => {
+  try {
+    const response = await fetch(`${origin}/robots.txt`)
+    if (!response.ok) {
+      return { allow: [], disallow: [], sitemaps: [] }
+    }
+    return parseRobots(await response.text())
+  } catch (e) {
+    return { allow: [], disallow: [], sitemaps: [] }
+  }
+}
+
+const decodeXml = (value: string) =>
+  value
+    .replace(/&amp;/g, "&")
+    .replace(/&lt;/g, "<")
+    .replace(/&gt;/g, ">")
+    .replace(/&quot;/g, '"')
+    .replace(/&apos;/g, "'")
+
+/**
+ * Returns the page urls listed in a sitemap. Sitemap indexes are followed
+ * until `MAX_NESTED_SITEMAPS` sitemaps, the first one included, were fetched.
+ */
+const fetchSitemapUrls = async (sitemapUrl: string): Promise<string[]> => {
+  const urls: string[] = []
+  const queue = [sitemapUrl]
+  let fetched = 0
+
+  while (queue.length > 0 && fetched < MAX_NESTED_SITEMAPS) {
+    const url = queue.shift()!
+    fetched++
+    try {
+      const response = await fetch(url)
+      if (!response.ok) {
+        continue
+      }
+      const xml = await response.text()
+      const locs = Array.from(
+        xml.matchAll(/<loc>\s*([^<]+?)\s*<\/loc>/g),
+        (match) => decodeXml(match[1])
+      )
+      if (/<sitemapindex[\s>]/.test(xml)) {
+        queue.push(...locs)
+      } else {
+        urls.push(...locs)
+      }
+    } catch (e) {
+      console.log("[crawlWebsite] failed to load sitemap", url, e)
+    }
+  }
+
+  return urls
+}
+
+const normalizeUrl = (value: string, base?: string): URL | null => {
+  try {
+    const url = new URL(value, base)
+    if (url.protocol !== "http:" && url.protocol !== "https:") {
+      return null
+    }
+    url.hash = ""
+    if (url.pathname.length > 1 && url.pathname.endsWith("/")) {
+      url.pathname = url.pathname.slice(0, -1)
+    }
+    return url
+  } catch (e) {
+    return null
+  }
+}
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms))
+
+const fetchHtml = async (url: URL): Promise<string | null> => {
+  try {
+    const response = await fetch(url.href)
+    const contentType = response.headers.get("content-type") || ""
+    if (!response.ok || !contentType.includes("html")) {
+      return null
+    }
+    return await response.text()
+  } catch (e) {
+    console.log("[crawlWebsite] failed to fetch", url.href, e)
+    return null
+  }
+}
+
+const getCanonicalUrl = (doc: Document, url: URL) => {
+  const canonical = doc
+    .querySelector('link[rel="canonical"]')
+    ?.getAttribute("href")
+  return (canonical && normalizeUrl(canonical, url.href)) || url
+}
+
+/**
+ * Fetches a single page, e.g. to check a crawled page for changes. Returns
+ * `null` when the page can not be loaded.
+ */
+export const fetchPage = async (
+  url: string,
+  extract: PageExtractor = extractPageFromHtml
+): Promise<CrawledPage | null> => {
+  const pageUrl = normalizeUrl(url)
+  if (!pageUrl) {
+    return null
+  }
+  const html = await fetchHtml(pageUrl)
+  if (!html) {
+    return null
+  }
+  return extract(pageUrl.href, html)
+}
+
+// page sources start with the page title, refreshes compare this text
+export const toPageSource = (page: CrawledPage): Source => ({
+  source_id: generateSourceId(),
+  type: "url",
+  filename: page.url,
+  url: page.url,
+  content: `# ${page.title}\n\n${page.content}`,
+  sourceType: "crawl"
+})
+
+/**
+ * Crawls a website breadth first from `startUrl`. Pages listed in the
+ * sitemap are queued as if they were linked from the start page. robots.txt
+ * rules and Crawl-delay are honored, and pages are deduplicated by their
+ * canonical url.
+ */
+export const crawlWebsite = async (
+  startUrl: string,
+  options: CrawlOptions,
+  onPage?: (page: CrawledPage, count: number) => void
+): Promise<CrawledPage[]> => {
+  const start = normalizeUrl(startUrl)
+  if (!start) {
+    throw new Error(`Invalid crawl url: ${startUrl}`)
+  }
+
+  const pathPrefix = options.pathPrefix?.trim()
+  const inScope = (url: URL) => {
+    if (options.sameOrigin && url.origin !== start.origin) {
+      return false
+    }
+    if (pathPrefix && !url.pathname.startsWith(pathPrefix)) {
+      return false
+    }
+    return !SKIPPED_EXTENSIONS.test(url.pathname)
+  }
+
+  const robots = new Map<string, RobotsRules>()
+  const getRobots = async (origin: string) => {
+    if (!robots.has(origin)) {
+      robots.set(origin, await fetchRobots(origin))
+    }
+    return robots.get(origin)!
+  }
+
+  const queue: { url: URL; depth: number }[] = [{ url: start, depth: 0 }]
+  const queued = new Set<string>([start.href])
+  const canonicals = new Set<string>()
+  const pages: CrawledPage[] = []
+  const lastFetch = new Map<string, number>()
+
+  const enqueue = (url: URL | null, depth: number) => {
+    if (
+      url &&
+      depth <= options.maxDepth &&
+      !queued.has(url.href) &&
+      inScope(url)
+    ) {
+      queued.add(url.href)
+      queue.push({ url, depth })
+    }
+  }
+
+  const startRobots = await getRobots(start.origin)
+  const sitemaps = startRobots.sitemaps.length
+    ? startRobots.sitemaps
+    : [`${start.origin}/sitemap.xml`]
+  for (const sitemap of sitemaps) {
+    for (const url of await fetchSitemapUrls(sitemap)) {
+      enqueue(normalizeUrl(url), 1)
+    }
+  }
+
+  while (queue.length > 0 && pages.length < options.maxPages) {
+    const { url, depth } = queue.shift()!
+    const rules = await getRobots(url.origin)
+    if (!isAllowedByRobots(rules, url)) {
+      continue
+    }
+
+    const delay = Math.min(rules.crawlDelay ?? 0, MAX_CRAWL_DELAY) * 1000
+    const wait = (lastFetch.get(url.origin) ?? 0) + delay - Date.now()
+    if (wait > 0) {
+      await sleep(wait)
+    }
+    lastFetch.set(url.origin, Date.now())
+
+    const html = await fetchHtml(url)
+    if (!html) {
+      continue
+    }
+
+    const doc = new DOMParser().parseFromString(html, "text/html")
+    const pageUrl = getCanonicalUrl(doc, url)
+    if (canonicals.has(pageUrl.href)) {
+      continue
+    }
+    canonicals.add(pageUrl.href)
+
+    if (depth < options.maxDepth) {
+      doc.querySelectorAll("a[href]").forEach((anchor) => {
+        const href = anchor.getAttribute("href")!
+        enqueue(normalizeUrl(href, url.href), depth + 1)
+      })
+    }
+
+    const page = await extractPageFromHtml(pageUrl.href, html)
+    if (!page) {
+      continue
+    }
+    pages.push(page)
+    onPage?.(page, pages.length)
+  }
+
+  return pages
+}
@@ -0,0 +1,313 @@
+import { generateID } from "@/db/dexie/helpers"
+import { pageAssistModel } from "@/models"
+import { getOllamaURL } from "@/services/ollama"
+import {
+  getDeepResearchMaxRounds,
+  getDeepResearchMaxSources,
+  getDeepResearchProviders,
+  getSearchProvider
+} from "@/services/search"
+import type { ToolCallStep } from "@/tools/types"
+import { getHostName, searchWeb } from "@/web/web"
+import { processSingleWebsite } from "@/web/website"
+import { cleanUrl } from "./clean-url"
+import { removeReasoning } from "./reasoning"
+
+const MAX_QUERIES = 4
+const PAGES_PER_QUERY = 3
+const MAX_PAGE_LENGTH = 12000
+
+const PLAN_PROMPT = `You are planning research on the question below. Break it into at most {count} web search queries that each cover a different part of the question, so that together they answer it in depth. Write them the way you would type them into a search engine, in the language of the question.
+Return only the queries, one per line, without numbering or any other text.
+{chat_history}
+Question: {question}`
+
+const NOTES_PROMPT = `You are researching the question "{question}". Below is the text of a web page found with the search "{query}".
+Write down everything on the page that helps to answer the question as a short list of notes: facts, numbers, dates, names and short quotes. Do not add anything that is not on the page.
+If nothing on the page is relevant, return only NOT RELEVANT.
+
+Page: {url}
+{content}`
+
+const REVIEW_PROMPT = `You are researching the question below. These are the notes collected so far with the searches {queries}.
+
+{notes}
+
+Decide what is still missing to answer the question completely and accurately. Write at most {count} new web search queries that would find it, different from the searches above.
+Return only the queries, one per line, without numbering or any other text. Return only DONE when the notes are enough.
+
+Question: {question}`
+
+const REPORT_PROMPT = `You are a research assistant. The current date and time are {current_date_time}.
+
+Write a detailed report in Markdown that answers the user's question, based on the research notes in the \`research-notes\` block.
+- Start with a short summary, then use headings for the parts of the question.
+- Cite the notes by their id in square brackets after every claim taken from them, e.g. [1] or [2][5].
+- Point out where sources disagree and what the notes leave open. Do not make up facts or sources.
+- End with a "Sources" section that lists the cited ids with their URL.
+- Write in the language of the question.
+
+<research-notes>
+{notes}
+</research-notes>
+`
+
+export type ResearchNote = {
+  // citation number, the position in the sources plus one
+  id: number
+  url: string
+  query: string
+  content: string
+}
+
+type Options = {
+  selectedModel: string
+  question: string
+  // previous messages, used to plan follow-up questions
+  chatHistory?: string
+  signal?: AbortSignal
+  onStep?: (steps: ToolCallStep[]) => void
+}
+
+// one pass, so that text from pages can not fill in other placeholders
+const fillPrompt = (prompt: string, values: Record<string, string>) =>
+  prompt.replace(/\{(\w+)\}/g, (match, key) => values[key] ?? match)
+
+const parseQueries = (text: string, count: number, exclude: string[]) => {
+  const seen = new Set(exclude.map((query) => query.toLowerCase()))
+  return removeReasoning(text)
+    .split("\n")
+    .map((line) =>
+      line
+        .replace(/^\s*(?:[-*•]|\d+[.)])\s*/, "")
+        .replace(/^["']|["']$/g, "")
+        .trim()
+    )
+    .filter((line) => {
+      const key = line.toLowerCase()
+      if (!line || key === "done" || seen.has(key)) {
+        return false
+      }
+      seen.add(key)
+      return true
+    })
+    .slice(0, count)
+}
+
+// providers return either a list of results or an answer with results
+const getResultUrls = (results: any): string[] => {
+  const list = Array.isArray(results) ? results : results?.results || []
+  return list
+    .map((result: any) => result?.url || result?.link)
+    .filter((url: any) => typeof url === "string" && /^https?:/.test(url))
+}
+
+const normalizeUrl = (url: string) => url.split("#")[0].replace(/\/$/, "")
+
+const formatNotes = (notes: ResearchNote[]) =>
+  notes
+    .map(
+      (note) =>
+        `<note id="${note.id}" source="${note.url}">\n${note.content}\n</note>`
+    )
+    .join("\n")
+
+/**
+ * System prompt for the final report, with the notes numbered so that the
+ * citations match the order of the sources.
+ */
+export const getResearchReportPrompt = (notes: ResearchNote[]) =>
+  fillPrompt(REPORT_PROMPT, {
+    current_date_time: new Date().toLocaleString(),
+    notes: formatNotes(notes) || "No notes were found."
+  })
+
+/**
+ * Researches a question in rounds: the model plans search queries, every
+ * query runs on each deep research provider, new pages are read and turned
+ * into notes, and the model reviews the notes to plan the next round. It
+ * stops when the model has enough, after the maximum rounds or when the
+ * source budget is used up. Each search and page read is reported as a step.
+ */
+export const runDeepResearch = async ({
+  selectedModel,
+  question,
+  chatHistory,
+  signal,
+  onStep
+}: Options) => {
+  const url = await getOllamaURL()
+  const model = await pageAssistModel({
+    model: selectedModel,
+    baseUrl: cleanUrl(url)
+  })
+  const [configuredProviders, maxRounds, maxSources] = await Promise.all([
+    getDeepResearchProviders(),
+    getDeepResearchMaxRounds(),
+    getDeepResearchMaxSources()
+  ])
+  const providers = configuredProviders.length
+    ? configuredProviders
+    : [await getSearchProvider()]
+
+  const steps: ToolCallStep[] = []
+  const notes: ResearchNote[] = []
+  const visited = new Set<string>()
+  const usedQueries: string[] = []
+
+  const runStep = async (
+    name: string,
+    args: Record<string, any>,
+    execute: () => Promise<{ result: string; summary?: string }>
+  ) => {
+    signal?.throwIfAborted()
+    const step: ToolCallStep = {
+      id: generateID(),
+      name,
+      args,
+      status: "running"
+    }
+    const index = steps.push(step) - 1
+    onStep?.([...steps])
+    try {
+      const { result, summary } = await execute()
+      steps[index] = { ...step, status: "done", result, summary }
+      return result
+    } catch (e) {
+      if (signal?.aborted) {
+        throw e
+      }
+      steps[index] = {
+        ...step,
+        status: "error",
+        error: e instanceof Error ? e.message : String(e)
+      }
+      return null
+    } finally {
+      onStep?.([...steps])
+    }
+  }
+
+  const ask = async (prompt: string) => {
+    const response = await model.invoke(prompt, { signal })
+    return removeReasoning(response.content.toString())
+  }
+
+  const readPage = async (pageUrl: string, query: string) => {
+    const chunks = await processSingleWebsite(pageUrl, query)
+    const content = chunks
+      .map((chunk) => chunk.content)
+      .filter(Boolean)
+      .join("\n\n")
+      .slice(0, MAX_PAGE_LENGTH)
+    if (!content.trim()) {
+      return { result: "The page has no readable content.", summary: "Empty" }
+    }
+    const summary = await ask(
+      fillPrompt(NOTES_PROMPT, { question, query, url: pageUrl, content })
+    )
+    if (!summary || /^NOT RELEVANT\W*$/i.test(summary)) {
+      return { result: "Nothing relevant on the page.", summary: "Skipped" }
+    }
+    const note = { id: notes.length + 1, url: pageUrl, query, content: summary }
+    notes.push(note)
+    return { result: summary, summary: `Note [${note.id}]` }
+  }
+
+  const plan = await runStep("plan_research", { question }, async () => {
+    const planned = parseQueries(
+      await ask(
+        fillPrompt(PLAN_PROMPT, {
+          count: MAX_QUERIES.toString(),
+          chat_history: chatHistory
+            ? `\nPrevious conversation:\n${chatHistory}\n`
+            : "",
+          question
+        })
+      ),
+      MAX_QUERIES,
+      []
+    )
+    return {
+      result: planned.join("\n"),
+      summary: `${planned.length} queries`
+    }
+  })
+  // without a plan the question itself is searched
+  let queries = plan ? plan.split("\n").filter(Boolean) : []
+  if (queries.length === 0) {
+    queries = [question]
+  }
+
+  for (let round = 1; round <= maxRounds; round++) {
+    for (const query of queries) {
+      usedQueries.push(query)
+      const found: string[] = []
+      for (const provider of providers) {
+        await runStep("web_search", { query, provider }, async () => {
+          const urls = getResultUrls(await searchWeb(provider, query))
+          found.push(...urls)
+          return {
+            result: urls.join("\n") || "No results found.",
+            summary: `${urls.length} results`
+          }
+        })
+      }
+
+      const pages = Array.from(new Set(found.map(normalizeUrl)))
+        .filter((page) => !visited.has(page))
+        .slice(0, PAGES_PER_QUERY)
+      for (const page of pages) {
+        if (visited.size >= maxSources) {
+          break
+        }
+        visited.add(page)
+        await runStep("read_page", { url: page, query }, () =>
+          readPage(page, query)
+        )
+      }
+    }
+
+    if (round === maxRounds || visited.size >= maxSources) {
+      break
+    }
+    const next = await runStep(
+      "review_research",
+      { round, notes: notes.length },
+      async () => {
+        const followUps = parseQueries(
+          await ask(
+            fillPrompt(REVIEW_PROMPT, {
+              queries: usedQueries.map((query) => `"${query}"`).join(", "),
+              notes: formatNotes(notes) || "No notes yet.",
+              count: MAX_QUERIES.toString(),
+              question
+            })
+          ),
+          MAX_QUERIES,
+          usedQueries
+        )
+        return {
+          result: followUps.join("\n") || "DONE",
+          summary: followUps.length
+            ? `${followUps.length} follow-up queries`
+            : "Enough notes"
+        }
+      }
+    )
+    queries = next && next !== "DONE" ? next.split("\n") : []
+    if (queries.length === 0) {
+      break
+    }
+  }
+
+  return {
+    notes,
+    steps,
+    sources: notes.map((note) => ({
+      url: note.url,
+      name: getHostName(note.url) || note.url,
+      type: "url"
+    }))
+  }
+}
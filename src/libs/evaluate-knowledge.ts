@@ -0,0 +1,145 @@
+import {
+  addEvaluationRun,
+  DEFAULT_RETRIEVAL_MODE,
+  getKnowledgeById
+} from "@/db/dexie/knowledge"
+import { generateID } from "@/db/dexie/helpers"
+import type {
+  EvaluationResult,
+  EvaluationRun,
+  EvaluationSettings,
+  Knowledge
+} from "@/db/dexie/types"
+import { pageAssistEmbeddingModel } from "@/models/embedding"
+import { getMultiQueryCount, getNoOfRetrievedDocs } from "@/services/app"
+import { getOllamaURL } from "@/services/ollama"
+import { getRerankerType } from "@/services/rerank"
+import { PageAssistVectorStore } from "./PageAssistVectorStore"
+import { cleanUrl } from "./clean-url"
+import { retrieveKnowledge } from "./retrieve-knowledge"
+
+const getEvaluationSettings = async (
+  knowledge: Knowledge,
+  k: number
+): Promise<EvaluationSettings> => ({
+  embedding_model: knowledge.embedding_model,
+  retrievalMode: knowledge.retrievalMode || DEFAULT_RETRIEVAL_MODE,
+  k,
+  // the chunks were split with the settings of their last indexing, which
+  // may differ from the current ones
+  chunkSize: knowledge.splitterSettings?.chunkSize,
+  chunkOverlap: knowledge.splitterSettings?.chunkOverlap,
+  splittingStrategy: knowledge.splitterSettings?.splittingStrategy,
+  parentChunkSize: knowledge.parentChunkSize || undefined,
+  reranker: await getRerankerType(),
+  multiQueryCount: await getMultiQueryCount()
+})
+
+const percentile = (values: number[], p: number) => {
+  if (values.length === 0) {
+    return 0
+  }
+  const sorted = [...values].sort((a, b) => a - b)
+  return sorted[Math.min(sorted.length - 1, Math.ceil(p * sorted.length) - 1)]
+}
+
+/**
+ * Summarizes the per question results: hit@k is the share of questions with
+ * an expected source in the top k documents, MRR the mean reciprocal rank
+ * of the first expected source (0 when it is missing).
+ */
+export const scoreEvaluation = (results: EvaluationResult[]) => {
+  const count = results.length || 1
+  const latencies = results.map((result) => result.latency)
+  return {
+    hitAtK: results.filter((result) => result.rank !== null).length / count,
+    mrr:
+      results.reduce(
+        (total, result) => total + (result.rank ? 1 / result.rank : 0),
+        0
+      ) / count,
+    avgLatency:
+      latencies.reduce((total, latency) => total + latency, 0) / count,
+    p95Latency: percentile(latencies, 0.95)
+  }
+}
+
+/**
+ * Runs every evaluation question of a knowledge base through the same
+ * retrieval as chats and stores the run. Sources are matched the way
+ * knowledge filters match them, on the file id of the documents.
+ */
+export const evaluateKnowledge = async ({
+  id,
+  selectedModel,
+  onProgress
+}: {
+  id: string
+  selectedModel: string
+  onProgress?: (done: number, total: number) => void
+}): Promise<EvaluationRun> => {
+  const knowledge = await getKnowledgeById(id)
+  if (!knowledge) {
+    throw new Error(`No knowledge found for ${id}`)
+  }
+  const questions = knowledge.evaluation?.questions || []
+  if (questions.length === 0) {
+    throw new Error("Add at least one question before running an evaluation")
+  }
+
+  const ollamaUrl = await getOllamaURL()
+  const embedding = await pageAssistEmbeddingModel({
+    model: knowledge.embedding_model,
+    baseUrl: cleanUrl(ollamaUrl)
+  })
+  const vectorstore = await PageAssistVectorStore.fromExistingIndex(
+    embedding,
+    { file_id: null, knownledge_id: knowledge.id }
+  )
+  const k = await getNoOfRetrievedDocs()
+  const results: EvaluationResult[] = []
+  for (const question of questions) {
+    const expected = new Set(question.expectedSources)
+    const start = performance.now()
+    try {
+      const docs = await retrieveKnowledge({
+        vectorstore,
+        embedding,
+        query: question.question,
+        selectedModel,
+        retrievalMode: knowledge.retrievalMode,
+        k
+      })
+      const latency = performance.now() - start
+      const top = docs.slice(0, k)
+      const index = top.findIndex((doc) => expected.has(doc.metadata?.file_id))
+      const retrieved = top.map((doc) => doc.metadata?.source as string)
+      results.push({
+        questionId: question.id,
+        rank: index === -1 ? null : index + 1,
+        latency,
+        retrieved
+      })
+    } catch (e) {
+      console.error(`Evaluation failed for "${question.question}"`, e)
+      results.push({
+        questionId: question.id,
+        rank: null,
+        latency: performance.now() - start,
+        retrieved: [],
+        error: e instanceof Error ? e.message : String(e)
+      })
+    }
+    onProgress?.(results.length, questions.length)
+  }
+
+  const run: EvaluationRun = {
+    id: generateID(),
+    createdAt: Date.now(),
+    settings: await getEvaluationSettings(knowledge, k),
+    ...scoreEvaluation(results),
+    results
+  }
+  await addEvaluationRun(id, run)
+  return run
+}
@@ -12,6 +12,7 @@ import {
   importPromptsV2
 } from "@/db/dexie/helpers"
 import { exportKnowledge, importKnowledgeV2 } from "@/db/dexie/knowledge"
+import { exportMcpServers, importMcpServersV2 } from "@/db/dexie/mcp"
 import { db } from "@/db/dexie/schema"
 import { exportVectors, importVectorsV2 } from "@/db/dexie/vector"
 import { importKnowledge } from "@/db/knowledge"
@@ -52,6 +53,7 @@ export const exportPageAssistData = async () => {
   const oaiConfigs = await exportOAIConfigs()
   const nicknames = await exportNicknames()
   const models = await exportModels()
+  const mcpServers = await exportMcpServers()
 
   const data = {
     knowledge,
@@ -60,7 +62,8 @@ export const exportPageAssistData = async () => {
     prompts,
     oaiConfigs,
     nicknames,
-    models
+    models,
+    mcpServers
   }
 
   const dataStr = JSON.stringify(data, null, 2)
@@ -89,10 +92,16 @@ export const importPageAssistData = async (file: File) => {
             db.prompts,
             db.knowledge,
             db.vectors,
+            db.vectorChunks,
+            db.keywordIndexes,
+            db.keywordChunks,
+            db.keywordStats,
+            db.annIndexes,
             db.sessionFiles,
             db.openaiConfigs,
             db.modelNickname,
-            db.customModels
+            db.customModels,
+            db.mcpServers
           ],
           async () => {
             if (data?.knowledge && Array.isArray(data.knowledge)) {
@@ -121,6 +130,10 @@ export const importPageAssistData = async (file: File) => {
             if (data?.models && Array.isArray(data.models)) {
               await importModelsV2(data.models, options)
             }
+
+            if (data?.mcpServers && Array.isArray(data.mcpServers)) {
+              await importMcpServersV2(data.mcpServers, options)
+            }
           }
         )
 
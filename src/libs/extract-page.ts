@@ -0,0 +1,31 @@
+import { PageAssistHtmlLoader } from "@/loader/html"
+
+export type CrawledPage = {
+  url: string
+  title: string
+  content: string
+}
+
+// turns fetched html into page text; needs a DOM, see `offscreen` for
+// contexts without one
+export type PageExtractor = (
+  url: string,
+  html: string
+) => Promise<CrawledPage | null>
+
+export const extractPageFromHtml: PageExtractor = async (url, html) => {
+  const doc = new DOMParser().parseFromString(html, "text/html")
+  const loader = new PageAssistHtmlLoader({ html, url })
+  const content = (await loader.loadByURL())
+    .map((d) => d.pageContent)
+    .join("\n\n")
+    .trim()
+  if (!content) {
+    return null
+  }
+  return {
+    url,
+    title: doc.title?.trim() || url,
+    content
+  }
+}
@@ -0,0 +1,275 @@
+/**
+ * Minimal HNSW (hierarchical navigable small world) graph for approximate
+ * nearest neighbour search over cosine similarity. Nodes are keyed by the
+ * ids of the chunks in a knowledge base, so chunks can be added, removed and
+ * changed without rebuilding the graph.
+ */
+export type HNSWGraph = {
+  M: number
+  efConstruction: number
+  // -1 while the graph is empty
+  entryPoint: number
+  maxLevel: number
+  levels: Record<number, number>
+  // neighbors[node][level]
+  neighbors: Record<number, number[][]>
+}
+
+type Candidate = { id: number; distance: number }
+
+export type VectorAccessor = (id: number) => number[]
+
+export const createHNSWGraph = (M = 16, efConstruction = 100): HNSWGraph => ({
+  M,
+  efConstruction,
+  entryPoint: -1,
+  maxLevel: -1,
+  levels: {},
+  neighbors: {}
+})
+
+export const getHNSWNodes = (graph: HNSWGraph) =>
+  Object.keys(graph.levels).map(Number)
+
+export const cosineDistance = (a: number[], b: number[]) => {
+  let dot = 0
+  let normA = 0
+  let normB = 0
+  for (let i = 0; i < a.length; i++) {
+    dot += a[i] * b[i]
+    normA += a[i] * a[i]
+    normB += b[i] * b[i]
+  }
+  if (normA === 0 || normB === 0) {
+    return 1
+  }
+  return 1 - dot / (Math.sqrt(normA) * Math.sqrt(normB))
+}
+
+// keeps `list` sorted by ascending distance
+const insertSorted = (list: Candidate[], candidate: Candidate) => {
+  let low = 0
+  let high = list.length
+  while (low < high) {
+    const mid = (low + high) >> 1
+    if (list[mid].distance < candidate.distance) {
+      low = mid + 1
+    } else {
+      high = mid
+    }
+  }
+  list.splice(low, 0, candidate)
+}
+
+const searchLayer = (
+  graph: HNSWGraph,
+  getVector: VectorAccessor,
+  query: number[],
+  entryPoints: Candidate[],
+  ef: number,
+  level: number
+): Candidate[] => {
+  const visited = new Set<number>(entryPoints.map((e) => e.id))
+  const candidates: Candidate[] = []
+  const results: Candidate[] = []
+  for (const entry of entryPoints) {
+    insertSorted(candidates, entry)
+    insertSorted(results, entry)
+  }
+
+  while (candidates.length > 0) {
+    const current = candidates.shift()!
+    const furthest = results[results.length - 1]
+    if (current.distance > furthest.distance && results.length >= ef) {
+      break
+    }
+    for (const neighbor of graph.neighbors[current.id][level] || []) {
+      if (visited.has(neighbor)) {
+        continue
+      }
+      visited.add(neighbor)
+      const distance = cosineDistance(query, getVector(neighbor))
+      if (
+        results.length < ef ||
+        distance < results[results.length - 1].distance
+      ) {
+        insertSorted(candidates, { id: neighbor, distance })
+        insertSorted(results, { id: neighbor, distance })
+        if (results.length > ef) {
+          results.pop()
+        }
+      }
+    }
+  }
+
+  return results
+}
+
+// keeps the `max` links closest to `node`
+const pruneLinks = (
+  getVector: VectorAccessor,
+  node: number,
+  links: number[],
+  max: number
+) => {
+  if (links.length <= max) {
+    return links
+  }
+  const vector = getVector(node)
+  return links
+    .map((link) => ({
+      id: link,
+      distance: cosineDistance(vector, getVector(link))
+    }))
+    .sort((a, b) => a.distance - b.distance)
+    .slice(0, max)
+    .map((c) => c.id)
+}
+
+const randomLevel = (M: number) => {
+  return Math.floor(-Math.log(Math.random() || Number.MIN_VALUE) / Math.log(M))
+}
+
+/**
+ * Adds the vector of chunk `id` to the graph.
+ */
+export const insertHNSWNode = (
+  graph: HNSWGraph,
+  getVector: VectorAccessor,
+  id: number
+) => {
+  const vector = getVector(id)
+  const level = randomLevel(graph.M)
+  graph.levels[id] = level
+  graph.neighbors[id] = Array.from({ length: level + 1 }, () => [])
+
+  if (graph.entryPoint === -1) {
+    graph.entryPoint = id
+    graph.maxLevel = level
+    return
+  }
+
+  let entryPoints: Candidate[] = [
+    {
+      id: graph.entryPoint,
+      distance: cosineDistance(vector, getVector(graph.entryPoint))
+    }
+  ]
+
+  for (let l = graph.maxLevel; l > level; l--) {
+    entryPoints = searchLayer(graph, getVector, vector, entryPoints, 1, l)
+  }
+
+  for (let l = Math.min(level, graph.maxLevel); l >= 0; l--) {
+    const found = searchLayer(
+      graph,
+      getVector,
+      vector,
+      entryPoints,
+      graph.efConstruction,
+      l
+    )
+    const maxNeighbors = l === 0 ? graph.M * 2 : graph.M
+    const selected = found.slice(0, graph.M)
+    graph.neighbors[id][l] = selected.map((c) => c.id)
+
+    for (const neighbor of selected) {
+      const links = graph.neighbors[neighbor.id][l]
+      links.push(id)
+      // drop the furthest link to keep the graph sparse
+      graph.neighbors[neighbor.id][l] = pruneLinks(
+        getVector,
+        neighbor.id,
+        links,
+        maxNeighbors
+      )
+    }
+    entryPoints = found
+  }
+
+  if (level > graph.maxLevel) {
+    graph.maxLevel = level
+    graph.entryPoint = id
+  }
+}
+
+/**
+ * Removes nodes from the graph. Nodes that linked to a removed node are
+ * linked to its neighbours instead, so the graph stays connected.
+ */
+export const removeHNSWNodes = (
+  graph: HNSWGraph,
+  getVector: VectorAccessor,
+  ids: number[]
+) => {
+  const removed = new Map<number, number[][]>()
+  for (const id of ids) {
+    if (graph.neighbors[id]) {
+      removed.set(id, graph.neighbors[id])
+      delete graph.neighbors[id]
+      delete graph.levels[id]
+    }
+  }
+  if (removed.size === 0) {
+    return
+  }
+
+  for (const node of getHNSWNodes(graph)) {
+    graph.neighbors[node] = graph.neighbors[node].map((links, l) => {
+      if (!links.some((link) => removed.has(link))) {
+        return links
+      }
+      const candidates = new Set<number>()
+      for (const link of links) {
+        for (const next of removed.get(link)?.[l] || [link]) {
+          if (next !== node && !removed.has(next)) {
+            candidates.add(next)
+          }
+        }
+      }
+      const maxNeighbors = l === 0 ? graph.M * 2 : graph.M
+      return pruneLinks(getVector, node, Array.from(candidates), maxNeighbors)
+    })
+  }
+
+  if (removed.has(graph.entryPoint)) {
+    graph.entryPoint = -1
+    graph.maxLevel = -1
+    for (const node of getHNSWNodes(graph)) {
+      if (graph.levels[node] > graph.maxLevel) {
+        graph.entryPoint = node
+        graph.maxLevel = graph.levels[node]
+      }
+    }
+  }
+}
+
+/**
+ * Returns the ids of the (approximately) `k` most similar vectors, ordered
+ * by descending similarity.
+ */
+export const searchHNSW = (
+  graph: HNSWGraph,
+  getVector: VectorAccessor,
+  query: number[],
+  k: number,
+  ef = Math.max(k * 2, 64)
+): { id: number; similarity: number }[] => {
+  if (graph.entryPoint === -1) {
+    return []
+  }
+
+  let entryPoints: Candidate[] = [
+    {
+      id: graph.entryPoint,
+      distance: cosineDistance(query, getVector(graph.entryPoint))
+    }
+  ]
+  for (let l = graph.maxLevel; l > 0; l--) {
+    entryPoints = searchLayer(graph, getVector, query, entryPoints, 1, l)
+  }
+
+  return searchLayer(graph, getVector, query, entryPoints, Math.max(ef, k), 0)
+    .slice(0, k)
+    .map((c) => ({ id: c.id, similarity: 1 - c.distance }))
+}
@@ -0,0 +1,124 @@
+import type { Document } from "@langchain/core/documents"
+import { replaceKeywordDocument } from "@/db/dexie/keyword"
+import type { Knowledge, VectorChunk } from "@/db/dexie/types"
+import { deleteVectorChunk, updateVectorChunk } from "@/db/dexie/vector"
+import { pageAssistEmbeddingModel } from "@/models/embedding"
+import { getOllamaURL } from "@/services/ollama"
+import { sha256 } from "@/utils/hash"
+import { getPageAssistReranker, rerankDocsWithScores } from "@/utils/rerank"
+import { PageAssistVectorStore } from "./PageAssistVectorStore"
+import { createBM25Document } from "./bm25"
+import { cleanUrl } from "./clean-url"
+
+export type InspectorResult = {
+  doc: Document
+  score: number
+  // unset when reranking is disabled in the RAG settings
+  rerankScore?: number
+  // whether the reranker keeps the document (threshold and top N)
+  kept?: boolean
+}
+
+const getEmbedding = async (knowledge: Knowledge) => {
+  const ollamaUrl = await getOllamaURL()
+  return pageAssistEmbeddingModel({
+    model: knowledge.embedding_model,
+    baseUrl: cleanUrl(ollamaUrl)
+  })
+}
+
+/**
+ * Replaces the text of a chunk and embeds it again. Indexing skips sources
+ * whose content and chunk settings are unchanged, so the edit stays until
+ * the source itself or those settings change.
+ */
+export const editKnowledgeChunk = async (
+  knowledge: Knowledge,
+  chunk: VectorChunk,
+  content: string
+) => {
+  const embedding = await getEmbedding(knowledge)
+  const [vector] = await embedding.embedDocuments([content])
+  const metadata = {
+    ...chunk.metadata,
+    chunk_hash: await sha256(`${chunk.metadata?.parent_id ?? ""}${content}`)
+  }
+  await updateVectorChunk(chunk.id!, { content, embedding: vector, metadata })
+  await replaceKeywordDocument(
+    `keyword:${knowledge.id}`,
+    { file_id: chunk.file_id, content: chunk.content },
+    {
+      file_id: chunk.file_id,
+      content,
+      metadata,
+      ...createBM25Document(content)
+    }
+  )
+}
+
+export const deleteKnowledgeChunk = async (
+  knowledge: Knowledge,
+  chunk: VectorChunk
+) => {
+  await deleteVectorChunk(chunk.id!)
+  await replaceKeywordDocument(`keyword:${knowledge.id}`, {
+    file_id: chunk.file_id,
+    content: chunk.content
+  })
+}
+
+/**
+ * Runs a query the way chats retrieve from a knowledge base and returns
+ * every candidate with its vector score and, when a reranker is
+ * configured, its rerank score.
+ */
+export const testKnowledgeQuery = async ({
+  knowledge,
+  query,
+  k,
+  selectedModel
+}: {
+  knowledge: Knowledge
+  query: string
+  k: number
+  selectedModel?: string
+}): Promise<InspectorResult[]> => {
+  const embedding = await getEmbedding(knowledge)
+  const vectorstore = await PageAssistVectorStore.fromExistingIndex(
+    embedding,
+    { file_id: null, knownledge_id: knowledge.id }
+  )
+  const { reranker, threshold, topN } = await getPageAssistReranker({
+    embedding,
+    selectedModel
+  })
+
+  const matches = await vectorstore.similaritySearchVectorWithScore(
+    await embedding.embedQuery(query),
+    // chats fetch a wider candidate pool when a reranker narrows it down
+    reranker ? k * 2 : k
+  )
+  const results: InspectorResult[] = matches.map(([doc, score]) => ({
+    doc,
+    score
+  }))
+  if (!reranker || results.length === 0) {
+    return results
+  }
+
+  // scored without threshold so that dropped documents show their score
+  const reranked = await rerankDocsWithScores({
+    query,
+    docs: results.map((result) => result.doc),
+    embedding,
+    reranker,
+    threshold: Number.NEGATIVE_INFINITY,
+    topN: results.length
+  })
+  return reranked.map(([doc, rerankScore], i) => ({
+    doc,
+    score: results.find((result) => result.doc === doc)?.score ?? 0,
+    rerankScore,
+    kept: rerankScore > threshold && i < Math.min(topN, k)
+  }))
+}
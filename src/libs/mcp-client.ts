@@ -0,0 +1,493 @@
+// A small Model Context Protocol client over HTTP. Supports the streamable
+// HTTP transport and the older HTTP with SSE transport, with the requests
+// Page Assist needs: tools, resources and prompts.
+import type { McpServer } from "@/db/dexie/types"
+import { getCustomHeaders } from "@/utils/clean-headers"
+
+const PROTOCOL_VERSION = "2025-03-26"
+const REQUEST_TIMEOUT = 60_000
+// the handshake and tool listing run before every chat message, so a slow
+// server must not hold it up for long
+const LIST_TIMEOUT = 10_000
+
+export type McpTool = {
+  name: string
+  description?: string
+  inputSchema?: Record<string, any>
+}
+
+export type McpResource = {
+  uri: string
+  name: string
+  description?: string
+  mimeType?: string
+}
+
+export type McpPrompt = {
+  name: string
+  description?: string
+  arguments?: { name: string; description?: string; required?: boolean }[]
+}
+
+type JsonRpcMessage = {
+  jsonrpc: "2.0"
+  id?: number | string
+  method?: string
+  params?: any
+  result?: any
+  error?: { code: number; message: string; data?: any }
+}
+
+type Pending = {
+  resolve: (result: any) => void
+  reject: (error: Error) => void
+}
+
+type SSEEvent = { event: string; data: string }
+
+/**
+ * Reads a `text/event-stream` body and calls `onEvent` for every event.
+ */
+const readSSE = async (
+  body: ReadableStream<Uint8Array>,
+  onEvent: (event: SSEEvent) => void | boolean
+) => {
+  const reader = body.getReader()
+  const decoder = new TextDecoder()
+  let buffer = ""
+  let event = "message"
+  let data: string[] = []
+
+  const dispatch = () => {
+    const stop =
+      data.length > 0 ? onEvent({ event, data: data.join("\n") }) : false
+    event = "message"
+    data = []
+    return stop
+  }
+
+  try {
+    while (true) {
+      const { done, value } = await reader.read()
+      if (done) {
+        break
+      }
+      buffer += decoder.decode(value, { stream: true })
+      const lines = buffer.split(/\r\n|\r|\n/)
+      buffer = lines.pop() ?? ""
+      for (const line of lines) {
+        if (line === "") {
+          // `onEvent` returns true once it has what it waits for
+          if (dispatch()) {
+            return
+          }
+        } else if (line.startsWith("event:")) {
+          event = line.slice(6).trim()
+        } else if (line.startsWith("data:")) {
+          data.push(line.slice(5).replace(/^ /, ""))
+        }
+      }
+    }
+    dispatch()
+  } finally {
+    reader.releaseLock()
+    body.cancel().catch(() => {})
+  }
+}
+
+const parseMessages = (text: string): JsonRpcMessage[] => {
+  if (!text.trim()) {
+    return []
+  }
+  const parsed = JSON.parse(text)
+  return Array.isArray(parsed) ? parsed : [parsed]
+}
+
+export class McpClient {
+  server: McpServer
+  private nextId = 1
+  private pending = new Map<number | string, Pending>()
+  private sessionId: string | null = null
+  private protocolVersion: string | null = null
+  // set up by the SSE transport
+  private endpoint: string | null = null
+  private stream: AbortController | null = null
+  private connecting: Promise<void> | null = null
+  // tool list of the current session, dropped when the server reports a
+  // change
+  private tools: Promise<McpTool[]> | null = null
+
+  constructor(server: McpServer) {
+    this.server = server
+  }
+
+  private headers(extra: Record<string, string> = {}) {
+    return {
+      ...getCustomHeaders({ headers: this.server.headers }),
+      ...(this.sessionId ? { "Mcp-Session-Id": this.sessionId } : {}),
+      ...(this.protocolVersion
+        ? { "MCP-Protocol-Version": this.protocolVersion }
+        : {}),
+      ...extra
+    }
+  }
+
+  private handleMessage(message: JsonRpcMessage) {
+    if (message.method !== undefined) {
+      if (message.method === "notifications/tools/list_changed") {
+        this.tools = null
+      }
+      // requests from the server, only ping is supported
+      if (message.id !== undefined) {
+        this.send({
+          jsonrpc: "2.0",
+          id: message.id,
+          ...(message.method === "ping"
+            ? { result: {} }
+            : { error: { code: -32601, message: "Method not found" } })
+        }).catch(() => {})
+      }
+      return
+    }
+    const pending = this.pending.get(message.id!)
+    if (!pending) {
+      return
+    }
+    this.pending.delete(message.id!)
+    if (message.error) {
+      pending.reject(
+        new Error(`MCP error ${message.error.code}: ${message.error.message}`)
+      )
+    } else {
+      pending.resolve(message.result)
+    }
+  }
+
+  private failPending(error: Error) {
+    this.pending.forEach((pending) => pending.reject(error))
+    this.pending.clear()
+  }
+
+  /**
+   * Opens the event stream of the SSE transport and waits for the server
+   * to announce the endpoint that receives the requests.
+   */
+  private openStream() {
+    this.stream = new AbortController()
+    const controller = this.stream
+    return new Promise<void>((resolve, reject) => {
+      const timeout = setTimeout(() => {
+        controller.abort()
+        reject(new Error("MCP server did not send an endpoint"))
+      }, LIST_TIMEOUT)
+
+      fetch(this.server.url, {
+        headers: this.headers({ Accept: "text/event-stream" }),
+        signal: controller.signal
+      })
+        .then(async (response) => {
+          if (!response.ok || !response.body) {
+            throw new Error(
+              `MCP server responded with ${response.status} ${response.statusText}`
+            )
+          }
+          await readSSE(response.body, ({ event, data }) => {
+            if (event === "endpoint") {
+              this.endpoint = new URL(data, this.server.url).toString()
+              clearTimeout(timeout)
+              resolve()
+            } else if (event === "message") {
+              try {
+                parseMessages(data).forEach((m) => this.handleMessage(m))
+              } catch (e) {
+                console.error("Invalid MCP message", e)
+              }
+            }
+          })
+          throw new Error("MCP event stream closed")
+        })
+        .catch((e) => {
+          clearTimeout(timeout)
+          this.endpoint = null
+          this.failPending(e)
+          reject(e)
+        })
+    })
+  }
+
+  /**
+   * Sends a JSON-RPC message. Responses of the streamable HTTP transport
+   * arrive in the response body, either as JSON or as an event stream,
+   * those of the SSE transport on the event stream.
+   */
+  private async send(message: JsonRpcMessage, signal?: AbortSignal) {
+    const sse = this.server.transport === "sse"
+    const response = await fetch(sse ? this.endpoint! : this.server.url, {
+      method: "POST",
+      headers: this.headers({
+        "Content-Type": "application/json",
+        Accept: "application/json, text/event-stream"
+      }),
+      body: JSON.stringify(message),
+      signal
+    })
+    if (!response.ok) {
+      if (response.status === 404 && this.sessionId) {
+        // the session expired, the next request initializes a new one
+        this.close()
+      }
+      const text = await response.text().catch(() => "")
+      throw new Error(
+        `MCP server responded with ${response.status} ${response.statusText}${
+          text ? `: ${text.slice(0, 200)}` : ""
+        }`
+      )
+    }
+    const sessionId = response.headers.get("Mcp-Session-Id")
+    if (sessionId) {
+      this.sessionId = sessionId
+    }
+    if (sse || response.status === 202 || !response.body) {
+      return
+    }
+
+    if (response.headers.get("Content-Type")?.includes("text/event-stream")) {
+      await readSSE(response.body, ({ data }) => {
+        parseMessages(data).forEach((m) => this.handleMessage(m))
+        return message.id !== undefined && !this.pending.has(message.id)
+      })
+    } else {
+      parseMessages(await response.text()).forEach((m) =>
+        this.handleMessage(m)
+      )
+    }
+  }
+
+  private async request<T = any>(
+    method: string,
+    params?: Record<string, any>,
+    signal?: AbortSignal,
+    timeoutMs = REQUEST_TIMEOUT
+  ): Promise<T> {
+    const id = this.nextId++
+    const controller = new AbortController()
+    const abort = () => controller.abort()
+    signal?.addEventListener("abort", abort)
+    const timeout = setTimeout(abort, timeoutMs)
+
+    const result = new Promise<T>((resolve, reject) => {
+      this.pending.set(id, { resolve, reject })
+      controller.signal.addEventListener("abort", () => {
+        this.pending.delete(id)
+        reject(
+          new Error(signal?.aborted ? "AbortError" : "MCP request timed out")
+        )
+      })
+    })
+    // awaited after sending, a failed send leaves it unobserved
+    result.catch(() => {})
+
+    try {
+      await this.send({ jsonrpc: "2.0", id, method, params }, controller.signal)
+      if (this.server.transport !== "sse" && this.pending.has(id)) {
+        this.pending.delete(id)
+        throw new Error(`MCP server sent no response to ${method}`)
+      }
+      return await result
+    } catch (e) {
+      this.pending.delete(id)
+      throw e
+    } finally {
+      clearTimeout(timeout)
+      signal?.removeEventListener("abort", abort)
+    }
+  }
+
+  private async initialize() {
+    if (this.server.transport === "sse") {
+      await this.openStream()
+    }
+    const result = await this.request(
+      "initialize",
+      {
+        protocolVersion: PROTOCOL_VERSION,
+        capabilities: {},
+        clientInfo: {
+          name: "page-assist",
+          version: browser.runtime.getManifest().version
+        }
+      },
+      undefined,
+      LIST_TIMEOUT
+    )
+    this.protocolVersion = result?.protocolVersion || PROTOCOL_VERSION
+    await this.send({ jsonrpc: "2.0", method: "notifications/initialized" })
+  }
+
+  async connect() {
+    this.connecting ??= this.initialize().catch((e) => {
+      this.connecting = null
+      this.close()
+      throw e
+    })
+    return this.connecting
+  }
+
+  // follows `nextCursor` until the list is complete
+  private async list<T>(method: string, key: string, signal?: AbortSignal) {
+    await this.connect()
+    const items: T[] = []
+    let cursor: string | undefined
+    do {
+      const result = await this.request(
+        method,
+        cursor ? { cursor } : {},
+        signal,
+        LIST_TIMEOUT
+      )
+      items.push(...(result?.[key] || []))
+      cursor = result?.nextCursor
+    } while (cursor)
+    return items
+  }
+
+  listTools(signal?: AbortSignal) {
+    return this.list<McpTool>("tools/list", "tools", signal)
+  }
+
+  /**
+   * The tool list, cached for the session until the server sends
+   * `notifications/tools/list_changed`.
+   */
+  getTools() {
+    this.tools ??= this.listTools().catch((e) => {
+      this.tools = null
+      throw e
+    })
+    return this.tools
+  }
+
+  listResources(signal?: AbortSignal) {
+    return this.list<McpResource>("resources/list", "resources", signal)
+  }
+
+  listPrompts(signal?: AbortSignal) {
+    return this.list<McpPrompt>("prompts/list", "prompts", signal)
+  }
+
+  /**
+   * Calls a tool and returns its content as text. Tool errors are thrown.
+   */
+  async callTool(
+    name: string,
+    args: Record<string, any>,
+    signal?: AbortSignal
+  ) {
+    await this.connect()
+    const result = await this.request(
+      "tools/call",
+      { name, arguments: args },
+      signal
+    )
+    const text = contentToText(result?.content || [])
+    if (result?.isError) {
+      throw new Error(text || `Tool ${name} failed`)
+    }
+    return text
+  }
+
+  async readResource(uri: string, signal?: AbortSignal) {
+    await this.connect()
+    const result = await this.request("resources/read", { uri }, signal)
+    return (result?.contents || [])
+      .map((content: any) =>
+        content.text !== undefined
+          ? content.text
+          : `[binary ${content.mimeType || "data"}: ${content.uri}]`
+      )
+      .join("\n\n")
+  }
+
+  // returns the text of the prompt messages
+  async getPrompt(
+    name: string,
+    args: Record<string, string> = {},
+    signal?: AbortSignal
+  ) {
+    await this.connect()
+    const result = await this.request(
+      "prompts/get",
+      { name, arguments: args },
+      signal
+    )
+    return contentToText(
+      (result?.messages || []).map((message: any) => message.content)
+    )
+  }
+
+  close() {
+    this.failPending(new Error("MCP connection closed"))
+    this.stream?.abort()
+    this.stream = null
+    this.endpoint = null
+    if (this.server.transport !== "sse" && this.sessionId) {
+      fetch(this.server.url, {
+        method: "DELETE",
+        headers: this.headers()
+      }).catch(() => {})
+    }
+    this.sessionId = null
+    this.protocolVersion = null
+    this.connecting = null
+    this.tools = null
+  }
+}
+
+const contentToText = (content: any[]) =>
+  content
+    .map((item) => {
+      switch (item?.type) {
+        case "text":
+          return item.text
+        case "resource":
+          return item.resource?.text ?? `[resource: ${item.resource?.uri}]`
+        case "resource_link":
+          return `[resource: ${item.uri}]`
+        default:
+          return `[${item?.type || "unknown"} content]`
+      }
+    })
+    .join("\n\n")
+
+const clients = new Map<string, McpClient>()
+
+// the connection is kept until the server settings change
+const clientKey = (server: McpServer) =>
+  JSON.stringify([server.url, server.transport, server.headers || []])
+
+/**
+ * Returns a connected client for the server, reusing the open session.
+ */
+export const getMcpClient = async (server: McpServer) => {
+  let client = clients.get(server.id)
+  if (client && clientKey(client.server) !== clientKey(server)) {
+    client.close()
+    client = undefined
+  }
+  if (!client) {
+    client = new McpClient(server)
+    clients.set(server.id, client)
+  }
+  try {
+    await client.connect()
+  } catch (e) {
+    clients.delete(server.id)
+    throw e
+  }
+  return client
+}
+
+export const closeMcpClient = (id: string) => {
+  clients.get(id)?.close()
+  clients.delete(id)
+}
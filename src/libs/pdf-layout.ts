@@ -0,0 +1,299 @@
+// Layout aware text extraction for pdf pages. pdf.js returns positioned text
+// runs, these are grouped into lines, lines are split into columns, and
+// aligned rows are written as Markdown tables.
+
+type Segment = { text: string; x0: number; x1: number }
+
+type Line = { y: number; height: number; segments: Segment[] }
+
+// runs on lines closer than this (times the font height) share a line
+const LINE_TOLERANCE = 0.5
+// gaps wider than this (times the font height) split a line into segments,
+// wider than the spaces of justified text
+const SEGMENT_GAP = 1.5
+// vertical gaps wider than this (times the font height) start a paragraph
+const PARAGRAPH_GAP = 1.8
+// rows further apart than this (times the font height) end a table
+const TABLE_ROW_GAP = 3
+// column segments have at least this many characters, table cells are
+// usually shorter
+const MIN_COLUMN_TEXT = 20
+const MAX_TABLE_CELL_TEXT = 40
+
+const toLines = (items: any[]): Line[] => {
+  const runs = items
+    .filter((item) => typeof item.str === "string" && item.str.trim())
+    .map((item) => {
+      const x0 = item.transform[4]
+      return {
+        text: item.str.replace(/\x00/g, ""),
+        x0,
+        x1: x0 + (item.width || 0),
+        y: item.transform[5],
+        height: Math.abs(item.height || item.transform[3]) || 10
+      }
+    })
+    // top to bottom, pdf coordinates start at the bottom
+    .sort((a, b) => b.y - a.y || a.x0 - b.x0)
+
+  const grouped: (typeof runs)[] = []
+  for (const run of runs) {
+    const line = grouped[grouped.length - 1]
+    if (
+      line &&
+      Math.abs(line[0].y - run.y) <=
+        LINE_TOLERANCE * Math.min(line[0].height, run.height)
+    ) {
+      line.push(run)
+    } else {
+      grouped.push([run])
+    }
+  }
+
+  return grouped.map((line) => {
+    const height = Math.max(...line.map((run) => run.height))
+    const segments: Segment[] = []
+    for (const run of line.sort((a, b) => a.x0 - b.x0)) {
+      const segment = segments[segments.length - 1]
+      const gap = segment ? run.x0 - segment.x1 : Infinity
+      if (gap > SEGMENT_GAP * height) {
+        segments.push({ text: run.text, x0: run.x0, x1: run.x1 })
+        continue
+      }
+      const space =
+        gap > 0.15 * height &&
+        !segment.text.endsWith(" ") &&
+        !run.text.startsWith(" ")
+      segment.text += (space ? " " : "") + run.text
+      segment.x1 = Math.max(segment.x1, run.x1)
+    }
+    segments.forEach((segment) => {
+      segment.text = segment.text.replace(/\s+/g, " ").trim()
+    })
+    return { y: line[0].y, height, segments }
+  })
+}
+
+/**
+ * Finds the gutters between text columns: x positions that almost no line
+ * crosses, with long segments on both sides.
+ */
+const findGutters = (lines: Line[]): number[] => {
+  if (lines.length < 5) {
+    return []
+  }
+  const left = Math.min(...lines.map((l) => l.segments[0].x0))
+  const right = Math.max(
+    ...lines.map((l) => l.segments[l.segments.length - 1].x1)
+  )
+  const width = right - left
+
+  const isGutter = (x: number) => {
+    let crossing = 0
+    let before = 0
+    let after = 0
+    for (const line of lines) {
+      const long = line.segments.filter(
+        (s) => s.text.length >= MIN_COLUMN_TEXT
+      )
+      if (line.segments.some((s) => s.x0 < x && s.x1 > x)) {
+        crossing++
+      }
+      if (long.some((s) => s.x1 <= x)) {
+        before++
+      }
+      if (long.some((s) => s.x0 >= x)) {
+        after++
+      }
+    }
+    return (
+      crossing <= lines.length * 0.1 &&
+      before >= lines.length * 0.25 &&
+      after >= lines.length * 0.25
+    )
+  }
+
+  const gutters: number[] = []
+  let start: number | null = null
+  const step = Math.max(width / 200, 1)
+  for (let x = left + width * 0.2; x <= left + width * 0.8; x += step) {
+    if (isGutter(x)) {
+      start ??= x
+    } else if (start !== null) {
+      gutters.push((start + x - step) / 2)
+      start = null
+    }
+  }
+  if (start !== null) {
+    gutters.push((start + left + width * 0.8) / 2)
+  }
+  return gutters
+}
+
+/**
+ * Puts the lines in reading order. Lines inside columns are read column by
+ * column, lines spanning the columns (titles, figures, footers) separate
+ * the column blocks. Returns blocks of lines that are read top to bottom.
+ */
+const toReadingOrder = (lines: Line[]): Line[][] => {
+  const gutters = findGutters(lines)
+  if (gutters.length === 0) {
+    return [lines]
+  }
+
+  const blocks: Line[][] = []
+  let spanning: Line[] = []
+  let columns: Line[][] = gutters.map(() => []).concat([[]])
+
+  const flushColumns = () => {
+    blocks.push(...columns.filter((column) => column.length > 0))
+    columns = gutters.map(() => []).concat([[]])
+  }
+
+  for (const line of lines) {
+    const spans = line.segments.some((s) =>
+      gutters.some((gutter) => s.x0 < gutter && s.x1 > gutter)
+    )
+    if (spans) {
+      flushColumns()
+      spanning.push(line)
+      continue
+    }
+    if (spanning.length > 0) {
+      blocks.push(spanning)
+      spanning = []
+    }
+    columns.forEach((column, i) => {
+      const segments = line.segments.filter((s) => {
+        const center = (s.x0 + s.x1) / 2
+        return gutters.filter((gutter) => gutter < center).length === i
+      })
+      if (segments.length > 0) {
+        column.push({ ...line, segments })
+      }
+    })
+  }
+  flushColumns()
+  if (spanning.length > 0) {
+    blocks.push(spanning)
+  }
+  return blocks
+}
+
+const overlap = (a: Segment, b: { x0: number; x1: number }) =>
+  Math.min(a.x1, b.x1) - Math.max(a.x0, b.x0)
+
+const escapeCell = (text: string) => text.replace(/\|/g, "\\|")
+
+/**
+ * Reads a table starting at `start`: consecutive lines with at least two
+ * segments and short cells. The line with the most segments defines the
+ * columns. Returns the Markdown table and the index after the table.
+ */
+const readTable = (
+  lines: Line[],
+  start: number
+): { markdown: string; end: number } | null => {
+  let end = start
+  while (
+    end < lines.length &&
+    lines[end].segments.length >= 2 &&
+    (end === start ||
+      lines[end - 1].y - lines[end].y <= TABLE_ROW_GAP * lines[end].height)
+  ) {
+    end++
+  }
+  if (end - start < 2) {
+    return null
+  }
+
+  const rows = lines.slice(start, end)
+  const columns = rows
+    .reduce((a, b) => (b.segments.length > a.segments.length ? b : a))
+    .segments.map(({ x0, x1 }) => ({ x0, x1 }))
+
+  const cells = rows.map((row) => {
+    const values = columns.map(() => [] as string[])
+    for (const segment of row.segments) {
+      let best = 0
+      columns.forEach((column, i) => {
+        const score = overlap(segment, column)
+        if (score > overlap(segment, columns[best])) {
+          best = i
+        } else if (
+          score === overlap(segment, columns[best]) &&
+          Math.abs(segment.x0 - column.x0) <
+            Math.abs(segment.x0 - columns[best].x0)
+        ) {
+          best = i
+        }
+      })
+      values[best].push(segment.text)
+    }
+    return values.map((value) => escapeCell(value.join(" ")))
+  })
+
+  const filled = cells.flat().filter(Boolean)
+  const average =
+    filled.reduce((total, cell) => total + cell.length, 0) / filled.length
+  if (average > MAX_TABLE_CELL_TEXT) {
+    return null
+  }
+
+  const [header, ...body] = cells
+  const markdown = [
+    `| ${header.join(" | ")} |`,
+    `| ${header.map(() => "---").join(" | ")} |`,
+    ...body.map((row) => `| ${row.join(" | ")} |`)
+  ].join("\n")
+  return { markdown, end }
+}
+
+const blockToText = (lines: Line[]) => {
+  const parts: string[] = []
+  let paragraph: string[] = []
+  const flush = () => {
+    if (paragraph.length > 0) {
+      parts.push(paragraph.join("\n"))
+      paragraph = []
+    }
+  }
+
+  let i = 0
+  while (i < lines.length) {
+    const table = readTable(lines, i)
+    if (table) {
+      flush()
+      parts.push(table.markdown)
+      i = table.end
+      continue
+    }
+    const line = lines[i]
+    const previous = lines[i - 1]
+    if (previous && previous.y - line.y > PARAGRAPH_GAP * line.height) {
+      flush()
+    }
+    paragraph.push(line.segments.map((s) => s.text).join(" "))
+    i++
+  }
+  flush()
+  return parts.join("\n\n")
+}
+
+/**
+ * Returns the text of a page from its pdf.js text items, in reading order
+ * across columns and with tables as Markdown.
+ */
+export const layoutTextFromItems = (items: any[]) => {
+  const lines = toLines(items)
+  return toReadingOrder(lines)
+    .map(blockToText)
+    .filter(Boolean)
+    .join("\n\n")
+    .trim()
+}
+
+export const extractLayoutText = async (page: any) => {
+  const content = await page.getTextContent()
+  return layoutTextFromItems(content?.items || [])
+}
@@ -1,4 +1,6 @@
 import { pdfDist } from "./pdfjs"
+import { extractLayoutText } from "./pdf-layout"
+import { getPdfExtractionMode, type PdfExtractionMode } from "@/services/kb"
 
 export const getPdf = async (data: ArrayBuffer) => {
   const pdf = pdfDist.getDocument({
@@ -29,29 +31,45 @@ export const processPdf = async (base64: string) => {
 }
 
 
-export const processPDFFromURL = async (url: string) => {
-  const res = await fetch(url)
-  const data = await res.arrayBuffer()
-  const pdf = await getPdf(data)
-  let pdfText = '';
+// "layout" keeps columns in reading order and tables as Markdown
+export const getPdfPageText = async (page: any, mode: PdfExtractionMode) => {
+  if (mode === "layout") {
+    return extractLayoutText(page)
+  }
+  const content = await page.getTextContent()
+  return (content?.items || [])
+    .map((item: any) => item.str)
+    .join("\n")
+    .replace(/\x00/g, "")
+    .trim()
+}
 
+export const getPdfPages = async (
+  pdf: Awaited<ReturnType<typeof getPdf>>,
+  mode?: PdfExtractionMode
+) => {
+  mode ??= await getPdfExtractionMode()
+  const pages: { content: string; page: number }[] = []
   for (let i = 1; i <= pdf.numPages; i += 1) {
-    const page = await pdf.getPage(i)
-    const content = await page.getTextContent()
-
-    if (content?.items.length === 0) {
-      continue
+    const text = await getPdfPageText(await pdf.getPage(i), mode)
+    if (text) {
+      pages.push({ content: text, page: i })
     }
-
-    const text = content?.items
-      .map((item: any) => item.str)
-      .join("\n")
-      .replace(/\x00/g, "")
-      .trim()
-
-    pdfText += text;
   }
+  return pages
+}
 
-  return pdfText;
+export const processPDFFromURL = async (url: string) => {
+  const res = await fetch(url)
+  const data = await res.arrayBuffer()
+  const pdf = await getPdf(data)
+  const mode = await getPdfExtractionMode()
+  const pages = await getPdfPages(pdf, mode)
 
+  if (mode === "layout") {
+    return pages
+      .map(({ content, page }) => `[Page ${page}]\n${content}`)
+      .join("\n\n")
+  }
+  return pages.map(({ content }) => content).join("")
 }
\ No newline at end of file
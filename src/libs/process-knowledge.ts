@@ -1,14 +1,139 @@
-import { getKnowledgeById, updateKnowledgeStatus } from "@/db/dexie/knowledge"
+import {
+  getKnowledgeById,
+  replaceCrawlSource,
+  setSourceError,
+  updateKnowledgeIndexStats,
+  updateKnowledgeStatus,
+  updateSourceContentHash
+} from "@/db/dexie/knowledge"
+import { deleteVectorByFileId, getVectorsByFileId } from "@/db/dexie/vector"
+import { deleteKeywordDocumentsByFileId } from "@/db/dexie/keyword"
+import type { Knowledge, PageAssistVector, Source } from "@/db/dexie/types"
 import { PageAssistPDFUrlLoader } from "@/loader/pdf-url"
 import { getOllamaURL } from "@/services/ollama"
 import { PageAssistVectorStore } from "./PageAssistVectorStore"
 import { PageAssisCSVUrlLoader } from "@/loader/csv"
 import { PageAssisTXTUrlLoader } from "@/loader/txt"
 import { PageAssistDocxLoader } from "@/loader/docx"
+import { isOfficeDocumentType, loadOfficeDocument } from "@/loader/office"
 import { cleanUrl } from "./clean-url"
+import { decodeEmbedding } from "./quantization"
 import { sendEmbeddingCompleteNotification } from "./send-notification"
 import { pageAssistEmbeddingModel } from "@/models/embedding"
-import { getPageAssistTextSplitter } from "@/utils/text-splitter"
+import {
+  getPageAssistTextSplitter,
+  getSplitterSettings,
+  splitDocumentsWithParents
+} from "@/utils/text-splitter"
+import { toArrayBufferFromBase64 } from "@/utils/to-source"
+import { sha256 } from "@/utils/hash"
+import { Document } from "@langchain/core/documents"
+import {
+  crawlWebsite,
+  DEFAULT_CRAWL_OPTIONS,
+  toPageSource
+} from "./crawl-website"
+
+/**
+ * Loads the documents of a source. `onEmptyPages` receives the pdf pages
+ * that yielded no text, also after OCR.
+ */
+export const loadSource = async (
+  doc: Source,
+  options: { ocr?: boolean; onEmptyPages?: (pages: number[]) => void } = {}
+): Promise<Document[]> => {
+  if (doc.type === "url") {
+    // the page text was extracted when the page was fetched
+    return [
+      new Document({
+        pageContent: doc.content,
+        metadata: { source: doc.filename, url: doc.url ?? doc.filename }
+      })
+    ]
+  } else if (doc.type === "pdf" || doc.type === "application/pdf") {
+    const loader = new PageAssistPDFUrlLoader({
+      name: doc.filename,
+      url: doc.content,
+      ocr: options.ocr
+    })
+    const docs = await loader.load()
+    options.onEmptyPages?.(loader.emptyPages)
+    return docs
+  } else if (doc.type === "csv" || doc.type === "text/csv") {
+    const loader = new PageAssisCSVUrlLoader({
+      name: doc.filename,
+      url: doc.content,
+      options: {}
+    })
+    return loader.load()
+  } else if (
+    doc.type === "docx" ||
+    doc.type ===
+      "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+  ) {
+    const loader = new PageAssistDocxLoader({
+      fileName: doc.filename,
+      buffer: await toArrayBufferFromBase64(doc.content)
+    })
+    return loader.load()
+  } else if (isOfficeDocumentType(doc.type)) {
+    return loadOfficeDocument({
+      type: doc.type,
+      fileName: doc.filename,
+      buffer: await toArrayBufferFromBase64(doc.content)
+    })
+  }
+
+  const loader = new PageAssisTXTUrlLoader({
+    name: doc.filename,
+    url: doc.content
+  })
+  return loader.load()
+}
+
+/**
+ * The settings that decide how the sources of a knowledge base are split.
+ * They are part of the content hash, so changing them indexes the sources
+ * again.
+ */
+export const getChunkingKey = async (knowledge: Knowledge) =>
+  JSON.stringify({
+    ...(await getSplitterSettings()),
+    parentChunkSize: knowledge.parentChunkSize || 0,
+    // only set when off, so hashes from before this key had it stay valid
+    ...(knowledge.ocr === false && { ocr: false })
+  })
+
+export const hashSourceContent = (content: string, chunkingKey: string) =>
+  sha256(`${chunkingKey}\n${content}`)
+
+/**
+ * Crawls the website of every pending crawl source and replaces the source
+ * with one "url" source per page, which are then indexed like any other
+ * source.
+ */
+const expandCrawlSources = async (knowledge: Knowledge): Promise<Source[]> => {
+  let sources = knowledge.source
+  for (const source of knowledge.source) {
+    if (source.type !== "crawl" || !source.content) {
+      continue
+    }
+    const pages = await crawlWebsite(
+      source.content,
+      source.crawl ?? DEFAULT_CRAWL_OPTIONS
+    )
+    if (pages.length === 0) {
+      throw new Error(`No pages found when crawling ${source.content}`)
+    }
+    console.log(`Crawled ${pages.length} pages from ${source.content}`)
+    sources = await replaceCrawlSource(
+      knowledge.id,
+      source.source_id,
+      pages.map(toPageSource)
+    )
+  }
+  return sources
+}
 
 export const processKnowledge = async (msg: any, id: string): Promise<void> => {
   console.log(`Processing knowledge with id: ${id}`)
@@ -22,89 +147,154 @@ export const processKnowledge = async (msg: any, id: string): Promise<void> => {
     }
 
     await updateKnowledgeStatus(id, "processing")
+    knowledge.source = await expandCrawlSources(knowledge)
 
     const ollamaEmbedding = await pageAssistEmbeddingModel({
       baseUrl: cleanUrl(ollamaUrl),
       model: knowledge.embedding_model
     })
 
-    const textSplitter = await getPageAssistTextSplitter()
+    const textSplitter = await getPageAssistTextSplitter(ollamaEmbedding)
 
-    for (const doc of knowledge.source) {
+    const splitterSettings = await getSplitterSettings()
+    const chunkingKey = await getChunkingKey(knowledge)
+    const stats = { added: 0, removed: 0, unchanged: 0, failed: 0 }
 
+    for (const doc of knowledge.source) {
       // skip if there is no doc.content
       if (!doc?.content || doc?.content === null) {
         console.log(`Skipping document with id ${doc.source_id}`)
         continue
       }
 
-      if (doc.type === "pdf" || doc.type === "application/pdf") {
-        const loader = new PageAssistPDFUrlLoader({
-          name: doc.filename,
-          url: doc.content
-        })
-        let docs = await loader.load()
-        const chunks = await textSplitter.splitDocuments(docs)
-        await PageAssistVectorStore.fromDocuments(chunks, ollamaEmbedding, {
-          knownledge_id: knowledge.id,
-          file_id: doc.source_id
-        })
-      } else if (doc.type === "csv" || doc.type === "text/csv") {
-        const loader = new PageAssisCSVUrlLoader({
-          name: doc.filename,
-          url: doc.content,
-          options: {}
-        })
+      try {
+        const existing: PageAssistVector[] = await getVectorsByFileId(
+          `vector:${knowledge.id}`,
+          doc.source_id
+        )
 
-        let docs = await loader.load()
+        const contentHash = await hashSourceContent(doc.content, chunkingKey)
+        if (doc.content_hash === contentHash && existing.length > 0) {
+          stats.unchanged += existing.length
+          await updateSourceContentHash(
+            id,
+            doc.source_id,
+            contentHash,
+            doc.emptyPages
+          )
+          continue
+        }
 
-        const chunks = await textSplitter.splitDocuments(docs)
-        await PageAssistVectorStore.fromDocuments(chunks, ollamaEmbedding, {
-          knownledge_id: knowledge.id,
-          file_id: doc.source_id
+        let emptyPages: number[] = []
+        const docs = await loadSource(doc, {
+          ocr: knowledge.ocr !== false,
+          onEmptyPages: (pages) => {
+            emptyPages = pages
+          }
         })
-      } else if (
-        doc.type === "docx" ||
-        doc.type ===
-          "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
-      ) {
-        try {
-          const loader = new PageAssistDocxLoader({
-            fileName: doc.filename,
-            buffer: await toArrayBufferFromBase64(doc.content)
-          })
-
-          let docs = await loader.load()
-
-          const chunks = await textSplitter.splitDocuments(docs)
-
-          await PageAssistVectorStore.fromDocuments(chunks, ollamaEmbedding, {
+        if (emptyPages.length > 0) {
+          console.warn(
+            `${doc.filename}: no text found on pages ${emptyPages.join(", ")}`
+          )
+        }
+        const chunks = await splitDocumentsWithParents(
+          docs,
+          textSplitter,
+          knowledge.parentChunkSize
+        )
+        for (const chunk of chunks) {
+          chunk.metadata = {
+            ...chunk.metadata,
+            file_id: doc.source_id,
+            // a chunk moved to another passage has to be stored again
+            chunk_hash: await sha256(
+              `${chunk.metadata.parent_id ?? ""}${chunk.pageContent}`
+            )
+          }
+        }
+
+        const existingByHash = new Map<string, PageAssistVector>()
+        for (const vector of existing) {
+          if (vector.metadata?.chunk_hash) {
+            existingByHash.set(vector.metadata.chunk_hash, vector)
+          }
+        }
+        const chunkHashes = new Set(chunks.map((c) => c.metadata.chunk_hash))
+
+        const unchangedChunks = chunks.filter((c) =>
+          existingByHash.has(c.metadata.chunk_hash)
+        )
+        const addedChunks = chunks.filter(
+          (c) => !existingByHash.has(c.metadata.chunk_hash)
+        )
+        const removedCount = existing.filter(
+          (v) => !chunkHashes.has(v.metadata?.chunk_hash)
+        ).length
+
+        // Drop the old chunks of this source and write back the ones that
+        // did not change together with their existing embeddings
+        if (existing.length > 0) {
+          await deleteVectorByFileId(`vector:${knowledge.id}`, doc.source_id)
+          await deleteKeywordDocumentsByFileId(
+            `keyword:${knowledge.id}`,
+            doc.source_id
+          )
+        }
+
+        const vectorstore = await PageAssistVectorStore.fromExistingIndex(
+          ollamaEmbedding,
+          {
             knownledge_id: knowledge.id,
             file_id: doc.source_id
-          })
-        } catch (error) {
-          console.error(`Error processing knowledge with id: ${id}`, error)
+          }
+        )
+        if (unchangedChunks.length > 0) {
+          await vectorstore.addVectors(
+            unchangedChunks.map(
+              (c) => decodeEmbedding(existingByHash.get(c.metadata.chunk_hash))
+            ),
+            unchangedChunks
+          )
+        }
+        if (addedChunks.length > 0) {
+          await vectorstore.addDocuments(addedChunks)
         }
-      } else {
-        const loader = new PageAssisTXTUrlLoader({
-          name: doc.filename,
-          url: doc.content
-        })
-
-        let docs = await loader.load()
 
-        const chunks = await textSplitter.splitDocuments(docs)
+        await updateSourceContentHash(
+          id,
+          doc.source_id,
+          contentHash,
+          emptyPages
+        )
 
-        await PageAssistVectorStore.fromDocuments(chunks, ollamaEmbedding, {
-          knownledge_id: knowledge.id,
-          file_id: doc.source_id
-        })
+        stats.added += addedChunks.length
+        stats.unchanged += unchangedChunks.length
+        stats.removed += removedCount
+      } catch (error) {
+        // one broken source does not stop the others
+        console.error(`Error indexing source ${doc.source_id}`, error)
+        await setSourceError(
+          id,
+          doc.source_id,
+          error instanceof Error ? error.message : String(error)
+        )
+        stats.failed++
       }
     }
 
+    console.log(
+      `Knowledge ${id}: ${stats.added} chunks added, ${stats.removed} removed, ${stats.unchanged} unchanged, ${stats.failed} sources failed`
+    )
+    // evaluations report the settings the chunks were made with
+    await updateKnowledgeIndexStats(
+      id,
+      { ...stats, updatedAt: Date.now() },
+      splitterSettings
+    )
+
     await updateKnowledgeStatus(id, "finished")
 
-    await sendEmbeddingCompleteNotification()
+    await sendEmbeddingCompleteNotification(stats)
   } catch (error) {
     console.error(`Error processing knowledge with id: ${id}`, error)
     await updateKnowledgeStatus(id, "failed")
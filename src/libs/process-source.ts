@@ -1,5 +1,6 @@
 import { PageAssisCSVUrlLoader } from "@/loader/csv"
 import { PageAssistDocxLoader } from "@/loader/docx"
+import { isOfficeDocumentType, loadOfficeDocument } from "@/loader/office"
 import { PageAssistPDFUrlLoader } from "@/loader/pdf-url"
 import { PageAssisTXTUrlLoader } from "@/loader/txt"
 import { toArrayBufferFromBase64 } from "~/utils/to-source"
@@ -47,6 +48,14 @@ export const processSource = async ({
     } catch (error) {
       console.error(`Error loading docx file: ${error}`)
     }
+  } else if (isOfficeDocumentType(type)) {
+    const docs = await loadOfficeDocument({
+      type,
+      fileName: filename,
+      buffer: await toArrayBufferFromBase64(url)
+    })
+
+    return docs.map((e) => e.pageContent).join("\n\n")
   } else {
     const loader = new PageAssisTXTUrlLoader({
       name: filename,
@@ -0,0 +1,193 @@
+import type {
+  EmbeddingQuantization,
+  PageAssistVector,
+  QuantizedEmbedding
+} from "@/db/dexie/types"
+
+// candidates kept from the quantized pass for every requested result
+const RESCORE_MULTIPLIER = 4
+
+type StoredEmbedding = Pick<PageAssistVector, "embedding" | "quantized">
+
+const quantizeInt8 = (embedding: ArrayLike<number>) => {
+  let max = 0
+  for (let i = 0; i < embedding.length; i++) {
+    max = Math.max(max, Math.abs(embedding[i]))
+  }
+  const scale = max / 127 || 1
+  const int8 = new Int8Array(embedding.length)
+  for (let i = 0; i < embedding.length; i++) {
+    int8[i] = Math.round(embedding[i] / scale)
+  }
+  return { int8, scale }
+}
+
+const quantizeBinary = (embedding: ArrayLike<number>) => {
+  const bits = new Uint8Array(Math.ceil(embedding.length / 8))
+  for (let i = 0; i < embedding.length; i++) {
+    if (embedding[i] > 0) {
+      bits[i >> 3] |= 1 << (i & 7)
+    }
+  }
+  return bits
+}
+
+/**
+ * Converts an embedding into its stored form. Without quantization the
+ * embedding is kept as a Float32Array. int8 keeps one byte per dimension.
+ * binary keeps the int8 values for rescoring plus the sign bits for a fast
+ * first pass, so it is slightly larger than int8.
+ */
+export const encodeEmbedding = (
+  embedding: ArrayLike<number>,
+  quantization: EmbeddingQuantization
+): StoredEmbedding => {
+  if (quantization === "none") {
+    return { embedding: Float32Array.from(embedding), quantized: undefined }
+  }
+  const { int8, scale } = quantizeInt8(embedding)
+  const quantized: QuantizedEmbedding = { type: quantization, int8, scale }
+  if (quantization === "binary") {
+    quantized.bits = quantizeBinary(embedding)
+  }
+  return { embedding: undefined, quantized }
+}
+
+/**
+ * Returns the embedding at the best precision that is stored. Quantized
+ * embeddings are scaled back, which keeps their cosine similarity intact.
+ */
+export const decodeEmbedding = (vector: StoredEmbedding): number[] => {
+  if (vector.embedding) {
+    return Array.from(vector.embedding)
+  }
+  if (vector.quantized) {
+    const { int8, scale } = vector.quantized
+    return Array.from(int8, (value) => value * scale)
+  }
+  return []
+}
+
+export const getQuantization = (
+  vector: StoredEmbedding
+): EmbeddingQuantization => {
+  return vector.quantized?.type ?? "none"
+}
+
+const cosine = (a: ArrayLike<number>, b: ArrayLike<number>) => {
+  let dot = 0
+  let normA = 0
+  let normB = 0
+  for (let i = 0; i < a.length; i++) {
+    dot += a[i] * b[i]
+    normA += a[i] * a[i]
+    normB += b[i] * b[i]
+  }
+  if (normA === 0 || normB === 0) {
+    return 0
+  }
+  return dot / (Math.sqrt(normA) * Math.sqrt(normB))
+}
+
+const POPCOUNT = Uint8Array.from({ length: 256 }, (_, i) => {
+  let count = 0
+  for (let n = i; n; n >>= 1) {
+    count += n & 1
+  }
+  return count
+})
+
+const hammingSimilarity = (a: Uint8Array, b: Uint8Array) => {
+  let distance = 0
+  for (let i = 0; i < a.length; i++) {
+    distance += POPCOUNT[a[i] ^ b[i]]
+  }
+  return -distance
+}
+
+// score of the full precision query against the stored embedding, which
+// is int8 for quantized vectors
+const rescore = (query: number[], vector: StoredEmbedding) => {
+  return cosine(query, vector.embedding ?? vector.quantized?.int8 ?? [])
+}
+
+/**
+ * Ranks stored vectors against a full precision query. Quantized vectors are
+ * first compared with the quantized query (Hamming distance of the sign bits
+ * for binary, int8 cosine for int8) and only the best candidates are
+ * rescored with the full precision query against their int8 values.
+ */
+export const rankVectors = (
+  query: number[],
+  vectors: StoredEmbedding[],
+  k: number
+): { index: number; score: number }[] => {
+  const quantized = vectors.some((vector) => vector.quantized)
+  if (!quantized) {
+    return vectors
+      .map((vector, index) => ({ index, score: rescore(query, vector) }))
+      .sort((a, b) => b.score - a.score)
+      .slice(0, k)
+  }
+
+  const queryInt8 = quantizeInt8(query).int8
+  const queryBits = quantizeBinary(query)
+  const candidates = vectors
+    .map((vector, index) => {
+      let score: number
+      if (vector.quantized?.bits) {
+        // map Hamming similarity into the cosine range so both compare
+        score = 1 + (2 * hammingSimilarity(queryBits, vector.quantized.bits)) /
+          query.length
+      } else if (vector.quantized) {
+        score = cosine(queryInt8, vector.quantized.int8)
+      } else {
+        score = rescore(query, vector)
+      }
+      return { index, score }
+    })
+    .sort((a, b) => b.score - a.score)
+    .slice(0, k * RESCORE_MULTIPLIER)
+
+  return candidates
+    .map(({ index }) => ({ index, score: rescore(query, vectors[index]) }))
+    .sort((a, b) => b.score - a.score)
+    .slice(0, k)
+}
+
+/**
+ * JSON friendly copy of a stored vector, used for exports. Typed arrays
+ * would otherwise be written as objects keyed by index.
+ */
+export const toSerializableVector = (vector: PageAssistVector) => ({
+  ...vector,
+  embedding: vector.embedding ? Array.from(vector.embedding) : undefined,
+  quantized: vector.quantized
+    ? {
+        ...vector.quantized,
+        int8: Array.from(vector.quantized.int8),
+        bits: vector.quantized.bits
+          ? Array.from(vector.quantized.bits)
+          : undefined
+      }
+    : undefined
+})
+
+/**
+ * Restores the typed arrays of an imported vector. Vectors keep the format
+ * they were exported with; plain embeddings from older exports are stored
+ * as Float32Array.
+ */
+export const fromSerializableVector = (vector: any): PageAssistVector => ({
+  ...vector,
+  embedding: vector.embedding ? Float32Array.from(vector.embedding) : undefined,
+  quantized: vector.quantized
+    ? {
+        ...vector.quantized,
+        int8: Int8Array.from(vector.quantized.int8),
+        bits: vector.quantized.bits
+          ? Uint8Array.from(vector.quantized.bits)
+          : undefined
+      }
+    : undefined
+})
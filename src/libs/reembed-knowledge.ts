@@ -0,0 +1,172 @@
+import {
+  getKnowledgeById,
+  setKnowledgeMigration,
+  swapKnowledgeEmbeddingModel
+} from "@/db/dexie/knowledge"
+import {
+  deleteVector,
+  getParentContents,
+  getVector
+} from "@/db/dexie/vector"
+import { deleteKeywordIndex } from "@/db/dexie/keyword"
+import { getOllamaURL } from "@/services/ollama"
+import { pageAssistEmbeddingModel } from "@/models/embedding"
+import {
+  getPageAssistTextSplitter,
+  getSplitterSettings,
+  splitDocumentsWithParents
+} from "@/utils/text-splitter"
+import { sha256 } from "@/utils/hash"
+import { Document } from "@langchain/core/documents"
+import { PageAssistVectorStore } from "./PageAssistVectorStore"
+import { cleanUrl } from "./clean-url"
+import { loadSource } from "./process-knowledge"
+import { sendEmbeddingMigrationNotification } from "./send-notification"
+
+const BATCH_SIZE = 32
+
+/**
+ * Re-embeds a knowledge base with another embedding model. The new vectors
+ * are written to a shadow set (`vector:<id>:shadow`) while the knowledge
+ * base keeps serving queries with the old model, and are swapped in once
+ * every chunk has been embedded.
+ */
+export const reEmbedKnowledge = async (id: string, model: string) => {
+  console.log(`Re-embedding knowledge ${id} with ${model}`)
+  const knowledge = await getKnowledgeById(id)
+  if (!knowledge) {
+    console.error(`Knowledge with id ${id} not found`)
+    return
+  }
+
+  const shadowId = `${id}:shadow`
+
+  try {
+    await deleteVector(`vector:${shadowId}`)
+    await deleteKeywordIndex(`keyword:${shadowId}`)
+    await setKnowledgeMigration(id, { model, status: "processing", progress: 0 })
+
+    const ollamaUrl = await getOllamaURL()
+    const embedding = await pageAssistEmbeddingModel({
+      baseUrl: cleanUrl(ollamaUrl),
+      model
+    })
+    const splitterSettings = await getSplitterSettings()
+    const textSplitter = await getPageAssistTextSplitter(embedding)
+    const stored = await getVector(`vector:${id}`)
+    const parents = await getParentContents(`vector:${id}`)
+
+    // sources that still carry content are chunked from it, the others
+    // reuse their stored chunks. Sources that failed to index have none.
+    const files: { file_id: string; docs: Document[] }[] = []
+    let split = false
+    for (const source of knowledge.source) {
+      if (source?.content && source.type !== "crawl" && !source.error) {
+        split = true
+        const chunks = await splitDocumentsWithParents(
+          await loadSource(source, { ocr: knowledge.ocr !== false }),
+          textSplitter,
+          knowledge.parentChunkSize
+        )
+        for (const chunk of chunks) {
+          chunk.metadata = {
+            ...chunk.metadata,
+            file_id: source.source_id,
+            chunk_hash: await sha256(
+              `${chunk.metadata.parent_id ?? ""}${chunk.pageContent}`
+            )
+          }
+        }
+        files.push({ file_id: source.source_id, docs: chunks })
+      } else {
+        files.push({
+          file_id: source.source_id,
+          docs: (stored?.vectors || [])
+            .filter((v) => v.file_id === source.source_id)
+            .map(
+              (v) =>
+                new Document({
+                  pageContent: v.content,
+                  // the shadow set stores its own parent passages
+                  metadata: parents.has(v.metadata?.parent_id)
+                    ? {
+                        ...v.metadata,
+                        parent_content: parents.get(v.metadata.parent_id)
+                      }
+                    : v.metadata
+                })
+            )
+        })
+      }
+    }
+
+    const total = files.reduce((acc, f) => acc + f.docs.length, 0)
+    let done = 0
+    let lastReported = 0
+
+    for (const file of files) {
+      const vectorstore = await PageAssistVectorStore.fromExistingIndex(
+        embedding,
+        {
+          knownledge_id: shadowId,
+          file_id: file.file_id
+        }
+      )
+      for (let i = 0; i < file.docs.length; i += BATCH_SIZE) {
+        await vectorstore.addDocuments(file.docs.slice(i, i + BATCH_SIZE))
+        done += Math.min(BATCH_SIZE, file.docs.length - i)
+
+        const progress = Math.floor((done / total) * 100)
+        await setKnowledgeMigration(id, {
+          model,
+          status: "processing",
+          progress
+        })
+        // notify every 25% to avoid flooding the notification center
+        if (progress - lastReported >= 25 && progress < 100) {
+          lastReported = progress
+          await sendEmbeddingMigrationNotification({
+            id,
+            title: knowledge.title,
+            model,
+            progress,
+            status: "processing"
+          })
+        }
+      }
+    }
+
+    await swapKnowledgeEmbeddingModel(
+      id,
+      model,
+      split ? splitterSettings : undefined
+    )
+
+    await sendEmbeddingMigrationNotification({
+      id,
+      title: knowledge.title,
+      model,
+      progress: 100,
+      status: "finished"
+    })
+  } catch (error) {
+    console.error(`Error re-embedding knowledge with id: ${id}`, error)
+    await deleteVector(`vector:${shadowId}`)
+    await deleteKeywordIndex(`keyword:${shadowId}`)
+    await setKnowledgeMigration(id, {
+      model,
+      status: "failed",
+      progress: 0,
+      error: error?.message || String(error)
+    })
+    await sendEmbeddingMigrationNotification({
+      id,
+      title: knowledge.title,
+      model,
+      progress: 0,
+      status: "failed"
+    })
+  } finally {
+    console.log(`Finished re-embedding knowledge with id: ${id}`)
+  }
+}
@@ -0,0 +1,172 @@
+import {
+  addNewSources,
+  claimQueuedRefresh,
+  getAllKnowledge,
+  getKnowledgeById,
+  isKnowledgeMigrating,
+  setKnowledgeRefresh,
+  updateKnowledgeStatus
+} from "@/db/dexie/knowledge"
+import type { Knowledge, Source } from "@/db/dexie/types"
+import { fetchPage, toPageSource } from "./crawl-website"
+import type { PageExtractor } from "./extract-page"
+import {
+  getChunkingKey,
+  hashSourceContent,
+  processKnowledge
+} from "./process-knowledge"
+
+const HOUR = 60 * 60 * 1000
+
+// sent to the extension pages when a refresh queued pages for indexing
+export const KNOWLEDGE_REFRESH_QUEUED = "knowledge_refresh_queued"
+
+type RefreshOptions = {
+  // parses the fetched pages in contexts without a DOM
+  extract?: PageExtractor
+  // only re-fetch the pages and leave indexing to an extension page, for
+  // the service worker that can be stopped at any time
+  queueIndexing?: boolean
+}
+
+const getUrlSources = (knowledge: Knowledge) =>
+  knowledge.source.filter((source) => source.type === "url" && source.url)
+
+export const isRefreshDue = (knowledge: Knowledge, now = Date.now()) => {
+  const interval = knowledge.refresh?.interval
+  if (
+    !interval ||
+    knowledge.status === "processing" ||
+    knowledge.refresh?.status === "queued" ||
+    isKnowledgeMigrating(knowledge)
+  ) {
+    return false
+  }
+  if (getUrlSources(knowledge).length === 0) {
+    return false
+  }
+  const last = knowledge.refresh?.lastRefreshedAt ?? knowledge.createdAt
+  return now - last >= interval * HOUR
+}
+
+// processKnowledge records failures on the knowledge base and its sources
+// instead of throwing, so they are read back
+const indexChangedPages = async (id: string) => {
+  await processKnowledge(null, id)
+  const knowledge = await getKnowledgeById(id)
+  if (knowledge?.status === "failed") {
+    throw new Error("Indexing the changed pages failed")
+  }
+  const failed = knowledge?.source.filter((source) => source.error) || []
+  if (failed.length > 0) {
+    throw new Error(
+      `${failed.length} pages could not be indexed: ${failed[0].error}`
+    )
+  }
+}
+
+/**
+ * Re-fetches the "url" sources of a knowledge base and re-indexes the pages
+ * whose content changed. Indexing only embeds the chunks that changed.
+ * Pages that can not be fetched keep their current chunks. Returns true
+ * when the changed pages were queued for indexing.
+ */
+export const refreshKnowledge = async (
+  id: string,
+  { extract, queueIndexing }: RefreshOptions = {}
+): Promise<boolean> => {
+  const knowledge = await getKnowledgeById(id)
+  if (!knowledge) {
+    return false
+  }
+
+  await setKnowledgeRefresh(id, { status: "processing", error: undefined })
+  try {
+    const chunkingKey = await getChunkingKey(knowledge)
+    const changed: Source[] = []
+    for (const source of getUrlSources(knowledge)) {
+      const page = await fetchPage(source.url!, extract)
+      if (!page) {
+        console.log(`Skipping ${source.url}, the page could not be loaded`)
+        continue
+      }
+      const { content } = toPageSource(page)
+      const hash = await hashSourceContent(content, chunkingKey)
+      if (hash !== source.content_hash) {
+        changed.push({ ...source, content })
+      }
+    }
+
+    if (changed.length > 0) {
+      console.log(`Knowledge ${id}: ${changed.length} pages changed`)
+      // same url, so the pages replace their sources and keep their ids
+      await addNewSources(id, changed)
+      if (queueIndexing) {
+        await updateKnowledgeStatus(id, "pending")
+        await setKnowledgeRefresh(id, {
+          status: "queued",
+          lastRefreshedAt: Date.now(),
+          changed: changed.length
+        })
+        return true
+      }
+      await indexChangedPages(id)
+    }
+
+    await setKnowledgeRefresh(id, {
+      status: "finished",
+      lastRefreshedAt: Date.now(),
+      changed: changed.length
+    })
+  } catch (error) {
+    console.error(`Error refreshing knowledge with id: ${id}`, error)
+    // not retried before the next interval
+    await setKnowledgeRefresh(id, {
+      status: "failed",
+      lastRefreshedAt: Date.now(),
+      error: error instanceof Error ? error.message : String(error)
+    })
+  }
+  return false
+}
+
+/**
+ * Refreshes every knowledge base whose refresh interval has passed. Contexts
+ * without a DOM pass an `extract` function that parses pages elsewhere.
+ * Returns true when any changed pages were queued for indexing.
+ */
+export const refreshDueKnowledge = async (
+  options: RefreshOptions = {}
+): Promise<boolean> => {
+  const knowledge = (await getAllKnowledge()) || []
+  let queued = false
+  for (const kb of knowledge) {
+    if (isRefreshDue(kb)) {
+      queued = (await refreshKnowledge(kb.id, options)) || queued
+    }
+  }
+  return queued
+}
+
+/**
+ * Indexes the pages that a refresh in the service worker queued. Runs in
+ * the extension pages, each queued refresh is claimed by one of them.
+ */
+export const indexQueuedRefreshes = async (): Promise<void> => {
+  const knowledge = (await getAllKnowledge()) || []
+  for (const kb of knowledge) {
+    if (kb.refresh?.status !== "queued" || !(await claimQueuedRefresh(kb.id))) {
+      continue
+    }
+    try {
+      await indexChangedPages(kb.id)
+      await setKnowledgeRefresh(kb.id, { status: "finished" })
+    } catch (error) {
+      console.error(`Error indexing refreshed knowledge ${kb.id}`, error)
+      await setKnowledgeRefresh(kb.id, {
+        status: "failed",
+        error: error instanceof Error ? error.message : String(error)
+      })
+    }
+  }
+}
@@ -0,0 +1,63 @@
+import type { Document } from "@langchain/core/documents"
+import type { EmbeddingsInterface } from "@langchain/core/embeddings"
+import { getUniqueDocs } from "@/chain/chat-with-x"
+import { DEFAULT_RETRIEVAL_MODE } from "@/db/dexie/knowledge"
+import type { RetrievalMode } from "@/db/dexie/types"
+import { getMultiQueryCount, getNoOfRetrievedDocs } from "@/services/app"
+import { generateQueryVariants, multiQuerySearch } from "@/utils/multi-query"
+import { getPageAssistReranker, rerankDocs } from "@/utils/rerank"
+import type { PageAssistVectorStore } from "./PageAssistVectorStore"
+
+/**
+ * Retrieves the documents for a (standalone) query from a knowledge base
+ * with the current RAG settings: query variants, the knowledge base
+ * retrieval mode and the reranker.
+ */
+export const retrieveKnowledge = async ({
+  vectorstore,
+  embedding,
+  query,
+  selectedModel,
+  retrievalMode = DEFAULT_RETRIEVAL_MODE,
+  filter,
+  k
+}: {
+  vectorstore: PageAssistVectorStore
+  embedding: EmbeddingsInterface
+  query: string
+  selectedModel: string
+  retrievalMode?: RetrievalMode
+  filter?: (doc: Document) => boolean
+  // defaults to the number of retrieved documents setting
+  k?: number
+}): Promise<Document[]> => {
+  const docSize = k ?? (await getNoOfRetrievedDocs())
+  const { reranker, threshold, topN } = await getPageAssistReranker({
+    embedding,
+    selectedModel
+  })
+  // paraphrases of the standalone query widen recall for vague questions
+  const queries = await generateQueryVariants({
+    model: selectedModel,
+    query,
+    count: await getMultiQueryCount()
+  })
+  let docs = await multiQuerySearch(
+    queries,
+    // fetch a wider candidate pool when a reranker narrows it down
+    reranker ? docSize * 2 : docSize,
+    (q, k) => vectorstore.searchKB(q, k, retrievalMode, filter)
+  )
+  if (reranker) {
+    docs = await rerankDocs({
+      query,
+      docs,
+      embedding,
+      reranker,
+      threshold,
+      // never more than the number of retrieved documents
+      topN: Math.min(topN, docSize)
+    })
+  }
+  return getUniqueDocs(docs)
+}
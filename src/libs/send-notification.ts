@@ -1,27 +1,74 @@
 import { Storage } from "@plasmohq/storage"
 const storage = new Storage()
 
-export const sendNotification = async (title: string, message: string) => {
+export const sendNotification = async (
+  title: string,
+  message: string,
+  notificationId?: string
+) => {
   try {
     const sendNotificationAfterIndexing = await storage.get<boolean>(
       "sendNotificationAfterIndexing"
     )
     if (sendNotificationAfterIndexing) {
-      browser.notifications.create({
-        type: "basic",
+      const options = {
+        type: "basic" as const,
         iconUrl: browser.runtime.getURL("/icon/128.png"),
         title,
         message
-      })
+      }
+      // Reusing an id replaces the previous notification instead of stacking
+      if (notificationId) {
+        browser.notifications.create(notificationId, options)
+      } else {
+        browser.notifications.create(options)
+      }
     }
   } catch (error) {
     console.error(error)
   }
 }
 
-export const sendEmbeddingCompleteNotification = async () => {
+export const sendEmbeddingCompleteNotification = async (stats?: {
+  added: number
+  removed: number
+  unchanged: number
+  failed?: number
+}) => {
+  const summary = stats
+    ? ` ${stats.added} chunks added, ${stats.removed} removed, ${stats.unchanged} unchanged.${
+        stats.failed ? ` ${stats.failed} sources could not be indexed.` : ""
+      }`
+    : ""
   await sendNotification(
     "Page Assist - Embedding Completed",
-    "The knowledge base embedding process is complete. You can now use the knowledge base for chatting."
+    `The knowledge base embedding process is complete. You can now use the knowledge base for chatting.${summary}`
+  )
+}
+
+
+export const sendEmbeddingMigrationNotification = async ({
+  id,
+  title,
+  model,
+  progress,
+  status
+}: {
+  id: string
+  title: string
+  model: string
+  progress: number
+  status: "processing" | "finished" | "failed"
+}) => {
+  const message = {
+    processing: `Re-embedding "${title}" with ${model}: ${progress}% done.`,
+    finished: `"${title}" now uses ${model}.`,
+    failed: `Re-embedding "${title}" with ${model} failed. The previous embeddings are still in use.`
+  }[status]
+
+  await sendNotification(
+    "Page Assist - Embedding Migration",
+    message,
+    `embedding-migration-${id}`
   )
 }
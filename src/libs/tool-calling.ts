@@ -0,0 +1,252 @@
+import type { BaseChatModel } from "@langchain/core/language_models/chat_models"
+import {
+  AIMessage,
+  ToolMessage,
+  type BaseMessage
+} from "@langchain/core/messages"
+import { ChatOpenAI } from "@langchain/openai"
+import { ChatOllama } from "@/models/ChatOllama"
+import { CustomChatOpenAI } from "@/models/CustomChatOpenAI"
+import { getToolMaxRounds } from "@/services/tools"
+import { getEnabledTools, toFunctionTool } from "@/tools"
+import type {
+  PageAssistTool,
+  ToolCallStep,
+  ToolContext,
+  ToolResult
+} from "@/tools/types"
+
+// tool results are cut to this many characters before the model sees them
+const MAX_TOOL_RESULT_LENGTH = 12000
+
+type PendingToolCall = {
+  index: number
+  id?: string
+  name: string
+  arguments: string
+}
+
+/**
+ * Adds streamed tool call deltas (OpenAI format, `ChatOllama` converts its
+ * tool calls to it) to `calls`. Deltas of one call share an index, a delta
+ * with a new id on a used index starts another call.
+ */
+export const mergeToolCallDeltas = (
+  calls: PendingToolCall[],
+  deltas: any[]
+) => {
+  deltas.forEach((delta, i) => {
+    const index = delta.index ?? i
+    let call: PendingToolCall | undefined
+    for (let j = calls.length - 1; j >= 0; j--) {
+      if (calls[j].index === index) {
+        call = calls[j]
+        break
+      }
+    }
+    if (!call || (delta.id && call.id && delta.id !== call.id)) {
+      call = { index, name: "", arguments: "" }
+      calls.push(call)
+    }
+    call.id ??= delta.id
+    call.name += delta.function?.name ?? ""
+    const args = delta.function?.arguments
+    if (typeof args === "string") {
+      call.arguments += args
+    } else if (args) {
+      call.arguments += JSON.stringify(args)
+    }
+  })
+  return calls
+}
+
+const parseArguments = (args: string): Record<string, any> => {
+  if (!args.trim()) {
+    return {}
+  }
+  return JSON.parse(args)
+}
+
+const supportsTools = (model: BaseChatModel) =>
+  model instanceof ChatOllama ||
+  model instanceof CustomChatOpenAI ||
+  model instanceof ChatOpenAI
+
+const isToolsUnsupportedError = (e: any) =>
+  /not support tools|tools? (?:is|are) not supported/i.test(
+    e?.message || String(e)
+  )
+
+const truncate = (text: string) =>
+  text.length > MAX_TOOL_RESULT_LENGTH
+    ? `${text.slice(0, MAX_TOOL_RESULT_LENGTH)}\n[truncated]`
+    : text
+
+const executeTool = async (
+  tools: PageAssistTool[],
+  name: string,
+  args: Record<string, any>,
+  context: ToolContext
+): Promise<ToolResult> => {
+  const tool = tools.find((tool) => tool.name === name)
+  if (!tool) {
+    throw new Error(`Unknown tool: ${name}`)
+  }
+  return tool.execute(args, context)
+}
+
+/**
+ * Streams a chat model response like `model.stream` and runs the tool calls
+ * it makes: the results are sent back and the model streams again, until it
+ * answers without tool calls or `toolMaxRounds` is reached. Falls back to a
+ * plain stream when tool calling is off or the model does not support it.
+ *
+ * Passing `tools` replaces the enabled tools and makes them required, the
+ * stream fails instead of falling back. `maxSteps` limits the number of
+ * tool calls instead of rounds.
+ */
+export async function* streamWithTools(
+  model: BaseChatModel,
+  messages: BaseMessage[],
+  {
+    signal,
+    callbacks,
+    context,
+    onToolStep,
+    onSources,
+    tools: requiredTools,
+    maxSteps
+  }: {
+    signal?: AbortSignal
+    callbacks?: any[]
+    context: ToolContext
+    onToolStep?: (steps: ToolCallStep[]) => void
+    onSources?: (sources: any[]) => void
+    tools?: PageAssistTool[]
+    maxSteps?: number
+  }
+): AsyncGenerator<any> {
+  if (requiredTools && !supportsTools(model)) {
+    throw new Error("The selected model does not support tool calling")
+  }
+  const tools = supportsTools(model)
+    ? requiredTools ?? (await getEnabledTools())
+    : []
+  if (tools.length === 0) {
+    yield* await model.stream(messages, { signal, callbacks })
+    return
+  }
+
+  const maxRounds = maxSteps ?? (await getToolMaxRounds())
+  const functionTools = tools.map(toFunctionTool)
+  const conversation = [...messages]
+  const steps: ToolCallStep[] = []
+  const sources: any[] = []
+
+  for (let round = 0; ; round++) {
+    const calls: PendingToolCall[] = []
+    let content = ""
+    let received = false
+    // the last round offers no tools so that the model has to answer
+    const offerTools =
+      round < maxRounds && steps.length < (maxSteps ?? Infinity)
+    try {
+      const chunks = await model.stream(conversation, {
+        signal,
+        callbacks,
+        ...(offerTools ? { tools: functionTools } : {})
+      } as any)
+      for await (const chunk of chunks) {
+        received = true
+        if (chunk?.additional_kwargs?.tool_calls) {
+          mergeToolCallDeltas(calls, chunk.additional_kwargs.tool_calls)
+        }
+        content += typeof chunk?.content === "string" ? chunk.content : ""
+        yield chunk
+      }
+    } catch (e) {
+      if (
+        round === 0 &&
+        !received &&
+        !requiredTools &&
+        isToolsUnsupportedError(e)
+      ) {
+        console.warn("Model does not support tools, answering without", e)
+        yield* await model.stream(messages, { signal, callbacks })
+        return
+      }
+      throw e
+    }
+
+    // calls made without tools on offer are not run
+    if (calls.length === 0 || !offerTools) {
+      return
+    }
+
+    calls.forEach((call, i) => {
+      call.id ??= `call_${round}_${i}`
+    })
+    conversation.push(
+      new AIMessage({
+        content,
+        additional_kwargs: {
+          tool_calls: calls.map((call) => ({
+            id: call.id,
+            type: "function",
+            function: { name: call.name, arguments: call.arguments || "{}" }
+          }))
+        }
+      })
+    )
+
+    for (const call of calls) {
+      if (signal?.aborted) {
+        throw new Error("AbortError")
+      }
+      if (maxSteps !== undefined && steps.length >= maxSteps) {
+        conversation.push(
+          new ToolMessage({
+            content: "Step budget used up, the call was not run.",
+            tool_call_id: call.id!
+          })
+        )
+        continue
+      }
+      const index = steps.length
+      let args: Record<string, any> = {}
+      let output: string
+      steps.push({ id: call.id!, name: call.name, args, status: "running" })
+      onToolStep?.([...steps])
+      try {
+        args = parseArguments(call.arguments)
+        steps[index] = { ...steps[index], args }
+        onToolStep?.([...steps])
+        const result = await executeTool(tools, call.name, args, context)
+        output = truncate(result.content)
+        steps[index] = {
+          ...steps[index],
+          status: "done",
+          result: output,
+          summary: result.summary,
+          images: result.images
+        }
+        if (result.sources?.length) {
+          sources.push(...result.sources)
+          onSources?.([...sources])
+        }
+      } catch (e) {
+        const error = e instanceof Error ? e.message : String(e)
+        console.error(`Tool ${call.name} failed`, e)
+        output = `Error: ${error}`
+        steps[index] = { ...steps[index], status: "error", error }
+      }
+      onToolStep?.([...steps])
+      conversation.push(
+        new ToolMessage({
+          content: output,
+          tool_call_id: call.id!
+        })
+      )
+    }
+  }
+}
@@ -0,0 +1,83 @@
+import { BaseDocumentLoader } from "langchain/document_loaders/base"
+import { Document } from "@langchain/core/documents"
+import TurndownService from "turndown"
+import { getElements, openZip, parseXml, resolveZipPath } from "./zip"
+
+export interface WebLoaderParams {
+  fileName: string
+  buffer: ArrayBuffer
+}
+
+export class PageAssistEpubLoader
+  extends BaseDocumentLoader
+  implements WebLoaderParams
+{
+  fileName: string
+  buffer: ArrayBuffer
+
+  constructor({ fileName, buffer }: WebLoaderParams) {
+    super()
+    this.fileName = fileName
+    this.buffer = buffer
+  }
+
+  async load(): Promise<Document<Record<string, any>>[]> {
+    const zip = openZip(this.buffer)
+    const container = await zip.readText("META-INF/container.xml")
+    const opfPath = container
+      ? getElements(parseXml(container), "rootfile")[0]?.getAttribute(
+          "full-path"
+        )
+      : null
+    const opfXml = opfPath ? await zip.readText(opfPath) : null
+    if (!opfPath || !opfXml) {
+      throw new Error(`Invalid EPUB file: ${this.fileName}`)
+    }
+
+    const opf = parseXml(opfXml)
+    const manifest = new Map(
+      getElements(opf, "item").map((item) => [
+        item.getAttribute("id"),
+        item.getAttribute("href")
+      ])
+    )
+    const bookTitle = getElements(opf, "title")[0]?.textContent?.trim()
+    const turndownService = new TurndownService({
+      headingStyle: "atx",
+      codeBlockStyle: "fenced"
+    })
+
+    const documents: Document[] = []
+    const spine = getElements(opf, "itemref")
+    for (let i = 0; i < spine.length; i++) {
+      const href = manifest.get(spine[i].getAttribute("idref"))
+      const html = href
+        ? await zip.readText(resolveZipPath(opfPath, href))
+        : null
+      if (!html) {
+        continue
+      }
+      const doc = new DOMParser().parseFromString(html, "text/html")
+      doc.querySelectorAll("script, style, img, svg").forEach((e) => e.remove())
+      const text = turndownService.turndown(doc.body?.innerHTML || "").trim()
+      if (!text) {
+        continue
+      }
+      const heading = doc.querySelector("h1, h2, h3")?.textContent?.trim()
+      documents.push(
+        new Document({
+          pageContent: text,
+          metadata: {
+            source: this.fileName,
+            chapter: i + 1,
+            title: heading || doc.title?.trim() || undefined,
+            book: bookTitle,
+            type: "epub"
+          }
+        })
+      )
+    }
+
+    return documents
+  }
+}
@@ -2,7 +2,10 @@ import { BaseDocumentLoader } from "langchain/document_loaders/base"
 import { Document } from "@langchain/core/documents"
 import { YtTranscript } from "yt-transcript"
 import { isWikipedia, parseWikipedia } from "@/parser/wiki"
-import { extractReadabilityContent } from "@/parser/reader"
+import {
+  extractReadabilityContent,
+  extractReadabilityContentFromHTML
+} from "@/parser/reader"
 import { isYoutubeLink } from "@/utils/is-youtube"
 
 
@@ -95,9 +98,13 @@ export class PageAssistHtmlLoader
       }
       // await urlRewriteRuntime(this.url, "web")
       let text = "";
+      // pages that were already fetched (e.g. by the crawler) are not
+      // fetched again
       if (isWikipedia(this.url)) {
-        const fetchHTML = await fetch(this.url)
-        text = parseWikipedia(await fetchHTML.text())
+        const html = this.html || (await (await fetch(this.url)).text())
+        text = parseWikipedia(html)
+      } else if (this.html) {
+        text = extractReadabilityContentFromHTML(this.html)
       } else {
         text = await extractReadabilityContent(this.url)
       }
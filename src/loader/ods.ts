@@ -0,0 +1,87 @@
+import { BaseDocumentLoader } from "langchain/document_loaders/base"
+import { Document } from "@langchain/core/documents"
+import { getElements, openZip, parseXml } from "./zip"
+import { rowsToText } from "./xlsx"
+
+export interface WebLoaderParams {
+  fileName: string
+  buffer: ArrayBuffer
+}
+
+// repeated rows and cells pad sheets up to the maximum sheet size
+const MAX_REPEAT = 1000
+
+const repeat = (element: Element, attribute: string) =>
+  Math.min(parseInt(element.getAttribute(attribute) || "1") || 1, MAX_REPEAT)
+
+const rowValues = (row: Element) => {
+  const values: string[] = []
+  for (const cell of Array.from(row.children)) {
+    if (
+      cell.localName !== "table-cell" &&
+      cell.localName !== "covered-table-cell"
+    ) {
+      continue
+    }
+    const value = getElements(cell, "p")
+      .map((p) => p.textContent)
+      .join("\n")
+    const count = repeat(cell, "table:number-columns-repeated")
+    for (let i = 0; i < count; i++) {
+      values.push(value)
+    }
+  }
+  return values
+}
+
+export class PageAssistOdsLoader
+  extends BaseDocumentLoader
+  implements WebLoaderParams
+{
+  fileName: string
+  buffer: ArrayBuffer
+
+  constructor({ fileName, buffer }: WebLoaderParams) {
+    super()
+    this.fileName = fileName
+    this.buffer = buffer
+  }
+
+  async load(): Promise<Document<Record<string, any>>[]> {
+    const content = await openZip(this.buffer).readText("content.xml")
+    if (!content) {
+      throw new Error(`Invalid ODS file: ${this.fileName}`)
+    }
+
+    const documents: Document[] = []
+    for (const table of getElements(parseXml(content), "table")) {
+      const rows: string[][] = []
+      for (const row of getElements(table, "table-row")) {
+        const values = rowValues(row)
+        if (values.every((value) => !value)) {
+          continue
+        }
+        const count = repeat(row, "table:number-rows-repeated")
+        for (let i = 0; i < count; i++) {
+          rows.push(values)
+        }
+      }
+      const text = rowsToText(rows)
+      if (!text) {
+        continue
+      }
+      documents.push(
+        new Document({
+          pageContent: text,
+          metadata: {
+            source: this.fileName,
+            sheet: table.getAttribute("table:name"),
+            type: "ods"
+          }
+        })
+      )
+    }
+
+    return documents
+  }
+}
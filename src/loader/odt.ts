@@ -0,0 +1,114 @@
+import { BaseDocumentLoader } from "langchain/document_loaders/base"
+import { Document } from "@langchain/core/documents"
+import { openZip, parseXml } from "./zip"
+
+export interface WebLoaderParams {
+  fileName: string
+  buffer: ArrayBuffer
+}
+
+const OFFICE_NS = "urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+
+type Section = { title?: string; lines: string[] }
+
+// text of a paragraph, keeping the spacing elements of the format
+const inlineText = (node: Node): string => {
+  let text = ""
+  node.childNodes.forEach((child) => {
+    if (child.nodeType === Node.TEXT_NODE) {
+      text += child.nodeValue
+      return
+    }
+    const element = child as Element
+    switch (element.localName) {
+      case "s":
+        text += " ".repeat(parseInt(element.getAttribute("text:c") || "1"))
+        break
+      case "tab":
+        text += "\t"
+        break
+      case "line-break":
+        text += "\n"
+        break
+      case "note":
+      case "annotation":
+        break
+      default:
+        text += inlineText(element)
+    }
+  })
+  return text
+}
+
+const isBlock = (element: Element) =>
+  element.localName === "h" || element.localName === "p"
+
+export class PageAssistOdtLoader
+  extends BaseDocumentLoader
+  implements WebLoaderParams
+{
+  fileName: string
+  buffer: ArrayBuffer
+
+  constructor({ fileName, buffer }: WebLoaderParams) {
+    super()
+    this.fileName = fileName
+    this.buffer = buffer
+  }
+
+  async load(): Promise<Document<Record<string, any>>[]> {
+    const content = await openZip(this.buffer).readText("content.xml")
+    const body = content
+      ? parseXml(content).getElementsByTagNameNS(OFFICE_NS, "text")[0]
+      : null
+    if (!body) {
+      throw new Error(`Invalid ODT file: ${this.fileName}`)
+    }
+
+    // paragraphs inside notes and annotations are skipped
+    const blocks = Array.from(body.getElementsByTagName("*")).filter(
+      (element) =>
+        isBlock(element) &&
+        !element.parentElement?.closest("h, p, note, annotation")
+    )
+    const level = (element: Element) =>
+      parseInt(element.getAttribute("text:outline-level") || "1")
+    const chapterLevel = Math.min(
+      ...blocks.filter((e) => e.localName === "h").map(level)
+    )
+
+    // one document per top level heading
+    const sections: Section[] = [{ lines: [] }]
+    for (const block of blocks) {
+      const text = inlineText(block).trim()
+      if (!text) {
+        continue
+      }
+      if (block.localName === "h") {
+        if (level(block) === chapterLevel) {
+          sections.push({ title: text, lines: [] })
+        }
+        sections[sections.length - 1].lines.push(
+          `${"#".repeat(Math.min(level(block), 6))} ${text}`
+        )
+      } else {
+        sections[sections.length - 1].lines.push(text)
+      }
+    }
+
+    return sections
+      .filter((section) => section.lines.length > 0)
+      .map(
+        (section, i) =>
+          new Document({
+            pageContent: section.lines.join("\n\n"),
+            metadata: {
+              source: this.fileName,
+              chapter: i + 1,
+              title: section.title,
+              type: "odt"
+            }
+          })
+      )
+  }
+}
@@ -0,0 +1,68 @@
+import { Document } from "@langchain/core/documents"
+import { PageAssistEpubLoader } from "./epub"
+import { PageAssistOdsLoader } from "./ods"
+import { PageAssistOdtLoader } from "./odt"
+import { PageAssistPptxLoader } from "./pptx"
+import { PageAssistRtfLoader } from "./rtf"
+import { PageAssistXlsxLoader } from "./xlsx"
+
+// office documents and ebooks, keyed by file extension
+export const officeDocumentTypes: Record<string, string> = {
+  epub: "application/epub+zip",
+  pptx:
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+  xlsx: "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+  ods: "application/vnd.oasis.opendocument.spreadsheet",
+  odt: "application/vnd.oasis.opendocument.text",
+  rtf: "application/rtf"
+}
+
+export const officeMimeTypes = [
+  ...Object.values(officeDocumentTypes),
+  "text/rtf"
+]
+
+export const officeFileExtensions = Object.keys(officeDocumentTypes)
+  .map((ext) => `.${ext}`)
+  .join(",")
+
+/**
+ * Returns the mime type for an office document by its file extension, for
+ * files the browser reports without a type.
+ */
+export const getOfficeDocumentType = (fileName: string): string | null => {
+  const ext = fileName.split(".").pop()?.toLowerCase()
+  return (ext && officeDocumentTypes[ext]) || null
+}
+
+export const isOfficeDocumentType = (type: string) =>
+  officeMimeTypes.includes(type)
+
+export const loadOfficeDocument = async ({
+  type,
+  fileName,
+  buffer
+}: {
+  type: string
+  fileName: string
+  buffer: ArrayBuffer
+}): Promise<Document[]> => {
+  const params = { fileName, buffer }
+  switch (type) {
+    case officeDocumentTypes.epub:
+      return new PageAssistEpubLoader(params).load()
+    case officeDocumentTypes.pptx:
+      return new PageAssistPptxLoader(params).load()
+    case officeDocumentTypes.xlsx:
+      return new PageAssistXlsxLoader(params).load()
+    case officeDocumentTypes.ods:
+      return new PageAssistOdsLoader(params).load()
+    case officeDocumentTypes.odt:
+      return new PageAssistOdtLoader(params).load()
+    case officeDocumentTypes.rtf:
+    case "text/rtf":
+      return new PageAssistRtfLoader(params).load()
+    default:
+      throw new Error(`Unsupported document type: ${type}`)
+  }
+}
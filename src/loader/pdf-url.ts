@@ -1,9 +1,30 @@
 import { BaseDocumentLoader } from "langchain/document_loaders/base"
 import { Document } from "@langchain/core/documents"
-import { processPdf } from "@/libs/pdf"
+import { getPdfPageText, processPdf } from "@/libs/pdf"
+import { getPdfExtractionMode, type PdfExtractionMode } from "@/services/kb"
+import { getOCRLanguage } from "@/services/ocr"
+import { createOCRWorker } from "@/utils/ocr"
+
+// render scale for OCR, pdf units are too small for tesseract
+const OCR_SCALE = 2
+
 export interface WebLoaderParams {
   url: string
   name: string
+  // run OCR on pages without a text layer, e.g. scanned pages
+  ocr?: boolean
+  // defaults to the pdf extraction mode setting
+  mode?: PdfExtractionMode
+}
+
+const renderPage = async (page: any) => {
+  const viewport = page.getViewport({ scale: OCR_SCALE })
+  const canvas = document.createElement("canvas")
+  canvas.width = viewport.width
+  canvas.height = viewport.height
+  await page.render({ canvasContext: canvas.getContext("2d")!, viewport })
+    .promise
+  return canvas.toDataURL("image/png")
 }
 
 export class PageAssistPDFUrlLoader
@@ -13,35 +34,62 @@ export class PageAssistPDFUrlLoader
   pdf: { content: string; page: number }[]
   url: string
   name: string
+  ocr: boolean
+  mode?: PdfExtractionMode
+  // pages that yielded no text, set by `load`
+  emptyPages: number[] = []
 
-  constructor({ url, name }: WebLoaderParams) {
+  constructor({ url, name, ocr = false, mode }: WebLoaderParams) {
     super()
     this.url = url
     this.name = name
+    this.ocr = ocr
+    this.mode = mode
   }
 
   async load(): Promise<Document<Record<string, any>>[]> {
     const documents: Document[] = []
+    this.emptyPages = []
+    // created for the first page that needs it
+    let worker: Awaited<ReturnType<typeof createOCRWorker>> | null = null
 
     const data = await processPdf(this.url)
+    const mode = this.mode ?? (await getPdfExtractionMode())
 
-    for (let i = 1; i <= data.numPages; i += 1) {
-      const page = await data.getPage(i)
-      const content = await page.getTextContent()
+    try {
+      for (let i = 1; i <= data.numPages; i += 1) {
+        const page = await data.getPage(i)
+        let text = await getPdfPageText(page, mode)
+        let ocr = false
 
-      if (content?.items.length === 0) {
-        continue
-      }
+        if (!text && this.ocr) {
+          try {
+            worker ??= await createOCRWorker(await getOCRLanguage())
+            const result = await worker.recognize(await renderPage(page))
+            text = result.data.text.trim()
+            ocr = true
+          } catch (e) {
+            console.error(`OCR failed for page ${i} of ${this.name}`, e)
+          }
+        }
+
+        if (!text) {
+          this.emptyPages.push(i)
+          continue
+        }
 
-      const text = content?.items
-        .map((item: any) => item.str)
-        .join("\n")
-        .replace(/\x00/g, "")
-        .trim()
-      documents.push({
-        pageContent: text,
-        metadata: { source: this.name, page: i, type: "pdf" }
-      })
+        documents.push({
+          pageContent: text,
+          metadata: {
+            source: this.name,
+            page: i,
+            type: "pdf",
+            ...(ocr ? { ocr: true } : {})
+          }
+        })
+      }
+    } finally {
+      await worker?.terminate()
     }
 
     return documents
@@ -0,0 +1,71 @@
+import { BaseDocumentLoader } from "langchain/document_loaders/base"
+import { Document } from "@langchain/core/documents"
+import { getElements, openZip, parseXml, readRelationships } from "./zip"
+
+export interface WebLoaderParams {
+  fileName: string
+  buffer: ArrayBuffer
+}
+
+const PRESENTATION = "ppt/presentation.xml"
+
+// one line per paragraph, the runs of a paragraph are joined
+const paragraphsToText = (xml: string) =>
+  getElements(parseXml(xml), "p")
+    .map((p) =>
+      getElements(p, "t")
+        .map((t) => t.textContent)
+        .join("")
+        .trim()
+    )
+    .filter(Boolean)
+    .join("\n")
+
+export class PageAssistPptxLoader
+  extends BaseDocumentLoader
+  implements WebLoaderParams
+{
+  fileName: string
+  buffer: ArrayBuffer
+
+  constructor({ fileName, buffer }: WebLoaderParams) {
+    super()
+    this.fileName = fileName
+    this.buffer = buffer
+  }
+
+  async load(): Promise<Document<Record<string, any>>[]> {
+    const zip = openZip(this.buffer)
+    const presentation = await zip.readText(PRESENTATION)
+    if (!presentation) {
+      throw new Error(`Invalid PPTX file: ${this.fileName}`)
+    }
+    const relationships = await readRelationships(zip, PRESENTATION)
+    // slides in presentation order, not file order
+    const slides = getElements(parseXml(presentation), "sldId")
+      .map((slide) => relationships.get(slide.getAttribute("r:id") || ""))
+      .filter(Boolean)
+      .map((rel) => rel!.target)
+
+    const documents: Document[] = []
+    for (let i = 0; i < slides.length; i++) {
+      const text = paragraphsToText((await zip.readText(slides[i])) || "")
+      const notesRel = Array.from(
+        (await readRelationships(zip, slides[i])).values()
+      ).find((rel) => rel.type.endsWith("/notesSlide"))
+      const notesXml = notesRel ? await zip.readText(notesRel.target) : null
+      const notes = notesXml ? paragraphsToText(notesXml) : ""
+      if (!text && !notes) {
+        continue
+      }
+      documents.push(
+        new Document({
+          pageContent: notes ? `${text}\n\nNotes:\n${notes}` : text,
+          metadata: { source: this.fileName, slide: i + 1, type: "pptx" }
+        })
+      )
+    }
+
+    return documents
+  }
+}
@@ -0,0 +1,153 @@
+import { BaseDocumentLoader } from "langchain/document_loaders/base"
+import { Document } from "@langchain/core/documents"
+
+export interface WebLoaderParams {
+  fileName: string
+  buffer: ArrayBuffer
+}
+
+// groups that hold formatting or embedded data instead of text
+const SKIPPED_DESTINATIONS = new Set([
+  "fonttbl",
+  "colortbl",
+  "stylesheet",
+  "listtable",
+  "listoverridetable",
+  "info",
+  "pict",
+  "object",
+  "header",
+  "footer",
+  "footnote",
+  "themedata",
+  "datastore",
+  "xmlnstbl",
+  "rsidtbl",
+  "generator"
+])
+
+const CONTROL_TEXT: Record<string, string> = {
+  par: "\n",
+  line: "\n",
+  row: "\n",
+  sect: "\n\n",
+  page: "\n\n",
+  tab: "\t",
+  cell: "\t",
+  emdash: "—",
+  endash: "–",
+  bullet: "•",
+  lquote: "‘",
+  rquote: "’",
+  ldblquote: "“",
+  rdblquote: "”"
+}
+
+/**
+ * Extracts the plain text of an rtf document. Formatting is dropped, hex
+ * escapes are decoded as windows-1252 and \u escapes as unicode.
+ */
+export const rtfToText = (rtf: string) => {
+  const decoder = new TextDecoder("windows-1252")
+  const stack: { skip: boolean; unicodeSkip: number }[] = []
+  let skip = false
+  let unicodeSkip = 1
+  // characters still to drop after a \u escape
+  let pendingSkip = 0
+  let text = ""
+
+  const append = (value: string) => {
+    if (pendingSkip > 0) {
+      pendingSkip--
+      return
+    }
+    if (!skip) {
+      text += value
+    }
+  }
+
+  let i = 0
+  while (i < rtf.length) {
+    const char = rtf[i]
+    if (char === "{") {
+      stack.push({ skip, unicodeSkip })
+      i++
+    } else if (char === "}") {
+      ;({ skip, unicodeSkip } = stack.pop() ?? { skip, unicodeSkip })
+      i++
+    } else if (char === "\\") {
+      const match = /^\\(?:([a-zA-Z]+)(-?\d+)? ?|'([0-9a-fA-F]{2})|(.))/s.exec(
+        rtf.slice(i, i + 64)
+      )
+      if (!match) {
+        i++
+        continue
+      }
+      i += match[0].length
+      const [, word, param, hex, symbol] = match
+      if (hex) {
+        append(decoder.decode(new Uint8Array([parseInt(hex, 16)])))
+      } else if (symbol) {
+        if (symbol === "*") {
+          skip = true
+        } else if (symbol === "~") {
+          append(" ")
+        } else if (symbol === "\n" || symbol === "\r") {
+          append("\n")
+        } else if ("\\{}".includes(symbol)) {
+          append(symbol)
+        }
+      } else if (word === "u" && param) {
+        const code = parseInt(param)
+        append(String.fromCharCode(code < 0 ? code + 65536 : code))
+        pendingSkip = unicodeSkip
+      } else if (word === "uc" && param) {
+        unicodeSkip = parseInt(param)
+      } else if (SKIPPED_DESTINATIONS.has(word)) {
+        skip = true
+      } else if (word in CONTROL_TEXT) {
+        append(CONTROL_TEXT[word])
+      }
+    } else {
+      // raw line breaks are not part of the text
+      if (char !== "\n" && char !== "\r") {
+        append(char)
+      }
+      i++
+    }
+  }
+
+  return text
+    .replace(/[ \t]+\n/g, "\n")
+    .replace(/\n{3,}/g, "\n\n")
+    .trim()
+}
+
+export class PageAssistRtfLoader
+  extends BaseDocumentLoader
+  implements WebLoaderParams
+{
+  fileName: string
+  buffer: ArrayBuffer
+
+  constructor({ fileName, buffer }: WebLoaderParams) {
+    super()
+    this.fileName = fileName
+    this.buffer = buffer
+  }
+
+  async load(): Promise<Document<Record<string, any>>[]> {
+    // rtf is 7-bit ascii, other characters are escaped
+    const rtf = new TextDecoder("latin1").decode(this.buffer)
+    const text = rtfToText(rtf)
+    if (!text) {
+      return []
+    }
+    return [
+      new Document({
+        pageContent: text,
+        metadata: { source: this.fileName, type: "rtf" }
+      })
+    ]
+  }
+}
@@ -0,0 +1,116 @@
+import { BaseDocumentLoader } from "langchain/document_loaders/base"
+import { Document } from "@langchain/core/documents"
+import { dsvFormat } from "d3-dsv"
+import { getElements, openZip, parseXml, readRelationships } from "./zip"
+
+export interface WebLoaderParams {
+  fileName: string
+  buffer: ArrayBuffer
+}
+
+const WORKBOOK = "xl/workbook.xml"
+
+// sheets are written as csv, empty rows and trailing empty cells dropped
+export const rowsToText = (rows: string[][]) => {
+  const trimmed = rows
+    .map((row) => {
+      let end = row.length
+      while (end > 0 && !row[end - 1]) {
+        end--
+      }
+      return row.slice(0, end)
+    })
+    .filter((row) => row.length > 0)
+  return dsvFormat(",").formatRows(trimmed).trim()
+}
+
+// "AB12" -> 27
+const columnIndex = (ref: string) => {
+  let index = 0
+  for (const char of ref.replace(/\d+$/, "")) {
+    index = index * 26 + (char.charCodeAt(0) - 64)
+  }
+  return index - 1
+}
+
+const cellValue = (cell: Element, sharedStrings: string[]) => {
+  const type = cell.getAttribute("t")
+  if (type === "inlineStr") {
+    return getElements(cell, "t")
+      .map((t) => t.textContent)
+      .join("")
+  }
+  const value = getElements(cell, "v")[0]?.textContent ?? ""
+  if (type === "s") {
+    return sharedStrings[parseInt(value)] ?? ""
+  }
+  if (type === "b") {
+    return value === "1" ? "TRUE" : "FALSE"
+  }
+  return value
+}
+
+export class PageAssistXlsxLoader
+  extends BaseDocumentLoader
+  implements WebLoaderParams
+{
+  fileName: string
+  buffer: ArrayBuffer
+
+  constructor({ fileName, buffer }: WebLoaderParams) {
+    super()
+    this.fileName = fileName
+    this.buffer = buffer
+  }
+
+  async load(): Promise<Document<Record<string, any>>[]> {
+    const zip = openZip(this.buffer)
+    const workbook = await zip.readText(WORKBOOK)
+    if (!workbook) {
+      throw new Error(`Invalid XLSX file: ${this.fileName}`)
+    }
+    const relationships = await readRelationships(zip, WORKBOOK)
+
+    const sharedXml = await zip.readText("xl/sharedStrings.xml")
+    const sharedStrings = sharedXml
+      ? getElements(parseXml(sharedXml), "si").map((si) =>
+          getElements(si, "t")
+            .map((t) => t.textContent)
+            .join("")
+        )
+      : []
+
+    const documents: Document[] = []
+    for (const sheet of getElements(parseXml(workbook), "sheet")) {
+      const rel = relationships.get(sheet.getAttribute("r:id") || "")
+      const sheetXml = rel ? await zip.readText(rel.target) : null
+      if (!sheetXml) {
+        continue
+      }
+      const rows = getElements(parseXml(sheetXml), "row").map((row) => {
+        const values: string[] = []
+        getElements(row, "c").forEach((cell, i) => {
+          const ref = cell.getAttribute("r")
+          values[ref ? columnIndex(ref) : i] = cellValue(cell, sharedStrings)
+        })
+        return Array.from(values, (value) => value ?? "")
+      })
+      const text = rowsToText(rows)
+      if (!text) {
+        continue
+      }
+      documents.push(
+        new Document({
+          pageContent: text,
+          metadata: {
+            source: this.fileName,
+            sheet: sheet.getAttribute("name"),
+            type: "xlsx"
+          }
+        })
+      )
+    }
+
+    return documents
+  }
+}
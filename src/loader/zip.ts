@@ -0,0 +1,135 @@
+const EOCD_SIGNATURE = 0x06054b50
+const CENTRAL_SIGNATURE = 0x02014b50
+const LOCAL_SIGNATURE = 0x04034b50
+// the end of central directory record may be followed by a 64kb comment
+const MAX_EOCD_SEARCH = 22 + 0xffff
+
+type ZipEntry = {
+  method: number
+  compressedSize: number
+  localOffset: number
+}
+
+export type ZipArchive = {
+  names: string[]
+  read: (name: string) => Promise<Uint8Array | null>
+  readText: (name: string) => Promise<string | null>
+}
+
+const inflateRaw = async (data: Uint8Array) => {
+  const stream = new Blob([data])
+    .stream()
+    .pipeThrough(new DecompressionStream("deflate-raw" as CompressionFormat))
+  return new Uint8Array(await new Response(stream).arrayBuffer())
+}
+
+/**
+ * Minimal zip reader for the office and ebook formats, which are zip
+ * archives of xml files. Supports stored and deflated entries, not zip64.
+ */
+export const openZip = (buffer: ArrayBuffer): ZipArchive => {
+  const view = new DataView(buffer)
+  const bytes = new Uint8Array(buffer)
+  const decoder = new TextDecoder()
+
+  let eocd = -1
+  const stop = Math.max(0, buffer.byteLength - MAX_EOCD_SEARCH)
+  for (let i = buffer.byteLength - 22; i >= stop; i--) {
+    if (view.getUint32(i, true) === EOCD_SIGNATURE) {
+      eocd = i
+      break
+    }
+  }
+  if (eocd === -1) {
+    throw new Error("Invalid file, not a zip archive")
+  }
+
+  const entries = new Map<string, ZipEntry>()
+  const count = view.getUint16(eocd + 10, true)
+  let offset = view.getUint32(eocd + 16, true)
+  for (let i = 0; i < count; i++) {
+    if (view.getUint32(offset, true) !== CENTRAL_SIGNATURE) {
+      break
+    }
+    const nameLength = view.getUint16(offset + 28, true)
+    const extraLength = view.getUint16(offset + 30, true)
+    const commentLength = view.getUint16(offset + 32, true)
+    const name = decoder.decode(
+      bytes.subarray(offset + 46, offset + 46 + nameLength)
+    )
+    entries.set(name, {
+      method: view.getUint16(offset + 10, true),
+      compressedSize: view.getUint32(offset + 20, true),
+      localOffset: view.getUint32(offset + 42, true)
+    })
+    offset += 46 + nameLength + extraLength + commentLength
+  }
+
+  const read = async (name: string) => {
+    const entry = entries.get(name)
+    if (!entry || view.getUint32(entry.localOffset, true) !== LOCAL_SIGNATURE) {
+      return null
+    }
+    // the local header has its own name and extra field lengths
+    const start =
+      entry.localOffset +
+      30 +
+      view.getUint16(entry.localOffset + 26, true) +
+      view.getUint16(entry.localOffset + 28, true)
+    const data = bytes.subarray(start, start + entry.compressedSize)
+    if (entry.method === 0) {
+      return data
+    }
+    if (entry.method === 8) {
+      return inflateRaw(data)
+    }
+    throw new Error(`Unsupported zip compression method: ${entry.method}`)
+  }
+
+  return {
+    names: Array.from(entries.keys()),
+    read,
+    readText: async (name: string) => {
+      const data = await read(name)
+      return data ? decoder.decode(data) : null
+    }
+  }
+}
+
+export const parseXml = (xml: string) =>
+  new DOMParser().parseFromString(xml, "application/xml")
+
+// matches by local name, so "a:t" and "t" are both found with "t"
+export const getElements = (node: Document | Element, localName: string) =>
+  Array.from(node.getElementsByTagNameNS("*", localName))
+
+// resolves a path relative to another file in the archive
+export const resolveZipPath = (from: string, href: string) => {
+  const resolved = new URL(href, `zip:///${from}`).pathname.slice(1)
+  return decodeURIComponent(resolved)
+}
+
+/**
+ * Reads the relationships of an office open xml part, e.g. the slides of a
+ * presentation. Targets are resolved to paths in the archive.
+ */
+export const readRelationships = async (zip: ZipArchive, part: string) => {
+  const slash = part.lastIndexOf("/")
+  const xml = await zip.readText(
+    `${part.slice(0, slash + 1)}_rels/${part.slice(slash + 1)}.rels`
+  )
+  const relationships = new Map<string, { type: string; target: string }>()
+  if (!xml) {
+    return relationships
+  }
+  for (const rel of getElements(parseXml(xml), "Relationship")) {
+    const target = rel.getAttribute("Target") || ""
+    relationships.set(rel.getAttribute("Id") || "", {
+      type: rel.getAttribute("Type") || "",
+      target: target.startsWith("/")
+        ? target.slice(1)
+        : resolveZipPath(part, target)
+    })
+  }
+  return relationships
+}
@@ -22,7 +22,19 @@ import {
 
 export interface ChatOllamaInput extends OllamaInput { }
 
-export interface ChatOllamaCallOptions extends BaseLanguageModelCallOptions { }
+// Ollama expects the arguments of earlier tool calls as an object
+const parseToolArguments = (args?: string) => {
+    try {
+        return JSON.parse(args || "{}");
+    } catch (e) {
+        return {};
+    }
+};
+
+export interface ChatOllamaCallOptions extends BaseLanguageModelCallOptions {
+    // function tools in the OpenAI format, which Ollama accepts as is
+    tools?: any[];
+}
 
 export class ChatOllama
     extends SimpleChatModel<ChatOllamaCallOptions>
@@ -189,6 +201,7 @@ export class ChatOllama
             format: this.format,
             keep_alive: this.keepAlive,
             think: this.thinking,
+            tools: options?.tools?.length ? options.tools : undefined,
             options: {
                 embedding_only: this.embeddingOnly,
                 f16_kv: this.f16KV,
@@ -292,15 +305,36 @@ export class ChatOllama
                     }
                 )
             );
+            // Ollama sends complete tool calls, they get the OpenAI delta
+            // format so that both backends are read the same way
+            let toolCallIndex = 0;
             for await (const chunk of stream) {
                 if (!chunk.done) {
+                    const additional_kwargs: Record<string, any> = {};
+                    if (chunk?.message?.thinking) {
+                        additional_kwargs.reasoning_content = chunk.message.thinking;
+                    }
+                    if (chunk?.message?.tool_calls?.length) {
+                        additional_kwargs.tool_calls = chunk.message.tool_calls.map(
+                            (toolCall) => ({
+                                index: toolCallIndex++,
+                                type: "function",
+                                function: {
+                                    name: toolCall.function.name,
+                                    arguments: JSON.stringify(
+                                        toolCall.function.arguments ?? {}
+                                    ),
+                                },
+                            })
+                        );
+                    }
                     yield new ChatGenerationChunk({
                         text: chunk.message.content,
                         message: new AIMessageChunk({
                             content: chunk.message.content,
-                            additional_kwargs: chunk?.message?.thinking ? {
-                                reasoning_content: chunk?.message?.thinking
-                            } : undefined
+                            additional_kwargs: Object.keys(additional_kwargs).length
+                                ? additional_kwargs
+                                : undefined
                         }),
                     });
                     await runManager?.handleLLMNewToken(chunk.message.content ?? "");
@@ -344,6 +378,8 @@ export class ChatOllama
                 role = "assistant";
             } else if (message._getType() === "system") {
                 role = "system";
+            } else if (message._getType() === "tool") {
+                role = "tool";
             } else {
                 throw new Error(
                     `Unsupported message type for Ollama: ${message._getType()}`
@@ -371,10 +407,17 @@ export class ChatOllama
                     }
                 }
             }
+            const toolCalls = message.additional_kwargs?.tool_calls;
             return {
                 role,
                 content,
                 images,
+                tool_calls: toolCalls?.map((toolCall) => ({
+                    function: {
+                        name: toolCall.function.name,
+                        arguments: parseToolArguments(toolCall.function.arguments),
+                    },
+                })),
             };
         });
     }
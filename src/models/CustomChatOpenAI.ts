@@ -146,13 +146,25 @@ function convertMessagesToOpenAIParams(messages: any[]) {
     // TODO: Function messages do not support array content, fix cast
     return messages.map((message) => {
         // eslint-disable-next-line @typescript-eslint/no-explicit-any
-        const completionParam: { role: string; content: string; name?: string } = {
+        const completionParam: {
+            role: string
+            content: string
+            name?: string
+            tool_calls?: any[]
+            tool_call_id?: string
+        } = {
             role: messageToOpenAIRole(message),
             content: message.content
         }
         if (message.name != null) {
             completionParam.name = message.name
         }
+        if (message.additional_kwargs?.tool_calls) {
+            completionParam.tool_calls = message.additional_kwargs.tool_calls
+        }
+        if (message.tool_call_id != null) {
+            completionParam.tool_call_id = message.tool_call_id
+        }
 
         return completionParam
     })
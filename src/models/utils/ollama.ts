@@ -85,11 +85,19 @@ export interface OllamaRequestParams {
   }
 }
 
+export type OllamaToolCall = {
+  function: {
+    name: string
+    arguments: Record<string, any>
+  }
+}
+
 export type OllamaMessage = {
-  role: StringWithAutocomplete<"user" | "assistant" | "system">
+  role: StringWithAutocomplete<"user" | "assistant" | "system" | "tool">
   content: string
   thinking?: string
   images?: string[]
+  tool_calls?: OllamaToolCall[]
 }
 
 export interface OllamaGenerateRequestParams extends OllamaRequestParams {
@@ -98,6 +106,7 @@ export interface OllamaGenerateRequestParams extends OllamaRequestParams {
 
 export interface OllamaChatRequestParams extends OllamaRequestParams {
   messages: OllamaMessage[]
+  tools?: any[]
 }
 
 export type BaseOllamaGenerationChunk = {
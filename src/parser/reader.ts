@@ -24,6 +24,10 @@ export const extractReadabilityContent = async (url: string) => {
 
   const html = await response.text()
 
+  return extractReadabilityContentFromHTML(html)
+}
+
+export const extractReadabilityContentFromHTML = (html: string) => {
   const doc = new DOMParser().parseFromString(html, "text/html")
   const reader = new Readability(doc)
   const article = reader.parse()
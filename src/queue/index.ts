@@ -1,9 +1,17 @@
 import { processKnowledge } from "@/libs/process-knowledge"
+import { reEmbedKnowledge } from "@/libs/reembed-knowledge"
+import {
+  indexQueuedRefreshes,
+  KNOWLEDGE_REFRESH_QUEUED
+} from "@/libs/refresh-knowledge"
 import PubSub from "pubsub-js"
 
 export const KNOWLEDGE_QUEUE = Symbol("queue")
+export const KNOWLEDGE_REEMBED_QUEUE = Symbol("reembed-queue")
 
 let isProcessing = false
+let isReEmbedding = false
+let isIndexingRefresh = false
 
 PubSub.subscribe(KNOWLEDGE_QUEUE, async (msg, id) => {
   try {
@@ -16,8 +24,42 @@ PubSub.subscribe(KNOWLEDGE_QUEUE, async (msg, id) => {
   }
 })
 
+PubSub.subscribe(
+  KNOWLEDGE_REEMBED_QUEUE,
+  async (msg, { id, model }: { id: string; model: string }) => {
+    try {
+      isReEmbedding = true
+      await reEmbedKnowledge(id, model)
+      isReEmbedding = false
+    } catch (error) {
+      console.error(error)
+      isReEmbedding = false
+    }
+  }
+)
+
+// the service worker only re-fetches refreshed pages, they are indexed here
+const indexRefreshedKnowledge = async () => {
+  try {
+    isIndexingRefresh = true
+    await indexQueuedRefreshes()
+    isIndexingRefresh = false
+  } catch (error) {
+    console.error(error)
+    isIndexingRefresh = false
+  }
+}
+
+browser.runtime.onMessage.addListener((message) => {
+  if (message?.type === KNOWLEDGE_REFRESH_QUEUED) {
+    indexRefreshedKnowledge()
+  }
+})
+
+indexRefreshedKnowledge()
+
 window.addEventListener("beforeunload", (event) => {
-  if (isProcessing) {
+  if (isProcessing || isReEmbedding || isIndexingRefresh) {
     event.preventDefault()
     event.returnValue = ""
   }
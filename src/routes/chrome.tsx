@@ -6,10 +6,12 @@ import OptionPrompt from "./option-settings-prompt"
 import OptionOllamaSettings from "./options-settings-ollama"
 import OptionShare from "./option-settings-share"
 import OptionKnowledgeBase from "./option-settings-knowledge"
+import OptionKnowledgeInspector from "./option-settings-knowledge-inspector"
 import OptionAbout from "./option-settings-about"
 import SidepanelChat from "./sidepanel-chat"
 import SidepanelSettings from "./sidepanel-settings"
 import OptionRagSettings from "./option-rag"
+import OptionToolSettings from "./option-settings-tools"
 import OptionChrome from "./option-settings-chrome"
 import OptionOpenAI from "./option-settings-openai"
 import SidepanelSettingsOpenAI from "./sidepanel-settings-openai"
@@ -27,7 +29,12 @@ export const OptionRoutingChrome = () => {
       <Route path="/settings/openai" element={<OptionOpenAI />} />
       <Route path="/settings/share" element={<OptionShare />} />
       <Route path="/settings/knowledge" element={<OptionKnowledgeBase />} />
+      <Route
+        path="/settings/knowledge/:id"
+        element={<OptionKnowledgeInspector />}
+      />
       <Route path="/settings/rag" element={<OptionRagSettings />} />
+      <Route path="/settings/tools" element={<OptionToolSettings />} />
       <Route path="/settings/about" element={<OptionAbout />} />
     </Routes>
   )
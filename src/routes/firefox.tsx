@@ -15,8 +15,12 @@ const OptionOllamaSettings = lazy(() => import("./options-settings-ollama"))
 const OptionSettings = lazy(() => import("./option-settings"))
 const OptionShare = lazy(() => import("./option-settings-share"))
 const OptionKnowledgeBase = lazy(() => import("./option-settings-knowledge"))
+const OptionKnowledgeInspector = lazy(
+  () => import("./option-settings-knowledge-inspector")
+)
 const OptionAbout = lazy(() => import("./option-settings-about"))
 const OptionRagSettings = lazy(() => import("./option-rag"))
+const OptionToolSettings = lazy(() => import("./option-settings-tools"))
 const OptionOpenAI = lazy(() => import("./option-settings-openai"))
 
 export const OptionRoutingFirefox = () => {
@@ -30,8 +34,13 @@ export const OptionRoutingFirefox = () => {
       <Route path="/settings/openai" element={<OptionOpenAI />} />
       <Route path="/settings/share" element={<OptionShare />} />
       <Route path="/settings/knowledge" element={<OptionKnowledgeBase />} />
+      <Route
+        path="/settings/knowledge/:id"
+        element={<OptionKnowledgeInspector />}
+      />
       <Route path="/settings/about" element={<OptionAbout />} />
       <Route path="/settings/rag" element={<OptionRagSettings />} />
+      <Route path="/settings/tools" element={<OptionToolSettings />} />
     </Routes>
   )
 }
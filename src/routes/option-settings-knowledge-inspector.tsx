@@ -0,0 +1,17 @@
+import { useParams } from "react-router-dom"
+import { SettingsLayout } from "~/components/Layouts/SettingsOptionLayout"
+import OptionLayout from "~/components/Layouts/Layout"
+import { KnowledgeInspector } from "@/components/Option/Knowledge/KnowledgeInspector"
+
+const OptionKnowledgeInspector = () => {
+  const { id } = useParams()
+  return (
+    <OptionLayout>
+      <SettingsLayout>
+        <KnowledgeInspector id={id!} />
+      </SettingsLayout>
+    </OptionLayout>
+  )
+}
+
+export default OptionKnowledgeInspector
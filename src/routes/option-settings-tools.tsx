@@ -0,0 +1,17 @@
+import { SettingsLayout } from "~/components/Layouts/SettingsOptionLayout"
+import OptionLayout from "~/components/Layouts/Layout"
+import { ToolSettings } from "@/components/Option/Settings/tools"
+import { McpSettings } from "@/components/Option/Settings/mcp"
+
+const OptionToolSettings = () => {
+  return (
+    <OptionLayout>
+      <SettingsLayout>
+        <ToolSettings />
+        <McpSettings />
+      </SettingsLayout>
+    </OptionLayout>
+  )
+}
+
+export default OptionToolSettings
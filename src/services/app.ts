@@ -162,6 +162,17 @@ export const setNoOfRetrievedDocs = async (
   await storage.set("noOfRetrievedDocs", noOfRetrievedDocs)
 }
 
+export const getMultiQueryCount = async (): Promise<number> => {
+  const multiQueryCount = await storage.get<number>("multiQueryCount")
+  return multiQueryCount || 0
+}
+
+export const setMultiQueryCount = async (
+  multiQueryCount: number
+): Promise<void> => {
+  await storage.set("multiQueryCount", multiQueryCount)
+}
+
 export const isRemoveReasoningTagFromCopy = async (): Promise<boolean> => {
   const removeReasoningTagFromCopy = await storage.get<boolean>(
     "removeReasoningTagFromCopy"
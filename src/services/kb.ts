@@ -1,4 +1,7 @@
 import { Storage } from "@plasmohq/storage"
+import type { EmbeddingQuantization } from "@/db/dexie/types"
+
+export type PdfExtractionMode = "text" | "layout"
 
 const storage = new Storage()
 
@@ -15,4 +18,29 @@ export const getMaxContextSize = async (): Promise<number> => {
         "maxWebsiteContext"
     )
     return maxWebsiteContext ?? 7028
-}
\ No newline at end of file
+}
+
+export const getEmbeddingQuantization =
+    async (): Promise<EmbeddingQuantization> => {
+        const quantization = await storage.get<EmbeddingQuantization | undefined>(
+            "embeddingQuantization"
+        )
+        return quantization ?? "none"
+    }
+
+export const setEmbeddingQuantization = async (
+    quantization: EmbeddingQuantization
+) => {
+    await storage.set("embeddingQuantization", quantization)
+}
+
+export const getPdfExtractionMode = async (): Promise<PdfExtractionMode> => {
+    const mode = await storage.get<PdfExtractionMode | undefined>(
+        "pdfExtractionMode"
+    )
+    return mode ?? "text"
+}
+
+export const setPdfExtractionMode = async (mode: PdfExtractionMode) => {
+    await storage.set("pdfExtractionMode", mode)
+}
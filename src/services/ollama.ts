@@ -351,6 +351,34 @@ export const defaultEmbeddingChunkOverlap = async () => {
   return parseInt(embeddingChunkOverlap)
 }
 
+export const defaultChunkSizeUnit = async (): Promise<
+  "characters" | "tokens"
+> => {
+  const chunkSizeUnit = await storage.get("defaultChunkSizeUnit")
+  if (chunkSizeUnit === "tokens") {
+    return "tokens"
+  }
+  return "characters"
+}
+
+export const setDefaultChunkSizeUnit = async (unit: string) => {
+  await storage.set("defaultChunkSizeUnit", unit)
+}
+
+export const defaultSemanticBreakpointPercentile = async () => {
+  const breakpoint = await storage.get("defaultSemanticBreakpointPercentile")
+  if (!breakpoint || breakpoint.length === 0) {
+    return 5
+  }
+  return parseInt(breakpoint)
+}
+
+export const setDefaultSemanticBreakpointPercentile = async (
+  breakpoint: number
+) => {
+  await storage.set("defaultSemanticBreakpointPercentile", breakpoint.toString())
+}
+
 export const setDefaultSplittingStrategy = async (strategy: string) => {
   await storage.set("defaultSplittingStrategy", strategy)
 }
@@ -378,7 +406,9 @@ export const saveForRag = async (
   totalFilePerKB: number,
   noOfRetrievedDocs?: number,
   strategy?: string,
-  separator?: string
+  separator?: string,
+  chunkSizeUnit?: string,
+  semanticBreakpoint?: number
 ) => {
   await setDefaultEmbeddingModelForRag(model)
   await setDefaultEmbeddingChunkSize(chunkSize)
@@ -393,6 +423,12 @@ export const saveForRag = async (
   if (separator) {
     await setDefaultSplittingSeparator(separator)
   }
+  if (chunkSizeUnit) {
+    await setDefaultChunkSizeUnit(chunkSizeUnit)
+  }
+  if (semanticBreakpoint) {
+    await setDefaultSemanticBreakpointPercentile(semanticBreakpoint)
+  }
 }
 
 export const getWebSearchPrompt = async () => {
@@ -0,0 +1,127 @@
+import { Storage } from "@plasmohq/storage"
+
+const storage = new Storage()
+
+export const RERANKER_TYPES = ["none", "embedding", "endpoint", "llm"] as const
+
+export type RerankerType = (typeof RERANKER_TYPES)[number]
+
+export type RerankerThresholds = Record<Exclude<RerankerType, "none">, number>
+
+// every scorer has its own scale: cosine similarity, the relevance score
+// of the rerank model and the judge's grade normalised to 0..1
+const DEFAULT_RERANKER_THRESHOLDS: RerankerThresholds = {
+  embedding: 0.5,
+  endpoint: 0.1,
+  llm: 0.5
+}
+const DEFAULT_RERANKER_TOP_N = 15
+
+export const getRerankerType = async (): Promise<RerankerType> => {
+  const rerankerType = await storage.get<RerankerType | undefined>(
+    "rerankerType"
+  )
+  if (!rerankerType || !RERANKER_TYPES.includes(rerankerType)) {
+    return "none"
+  }
+  return rerankerType
+}
+
+export const setRerankerType = async (rerankerType: RerankerType) => {
+  await storage.set("rerankerType", rerankerType)
+}
+
+export const getRerankerThresholds =
+  async (): Promise<RerankerThresholds> => {
+    const thresholds = await storage.get<
+      Partial<RerankerThresholds> | undefined
+    >("rerankerThresholds")
+    return { ...DEFAULT_RERANKER_THRESHOLDS, ...thresholds }
+  }
+
+export const setRerankerThresholds = async (
+  thresholds: RerankerThresholds
+) => {
+  await storage.set("rerankerThresholds", thresholds)
+}
+
+export const getRerankerTopN = async (): Promise<number> => {
+  const topN = await storage.get<number | undefined>("rerankerTopN")
+  return topN ?? DEFAULT_RERANKER_TOP_N
+}
+
+export const setRerankerTopN = async (topN: number) => {
+  await storage.set("rerankerTopN", topN)
+}
+
+export const getRerankerBaseUrl = async (): Promise<string> => {
+  const baseUrl = await storage.get("rerankerBaseUrl")
+  return baseUrl || ""
+}
+
+export const setRerankerBaseUrl = async (baseUrl: string) => {
+  await storage.set("rerankerBaseUrl", baseUrl)
+}
+
+export const getRerankerApiKey = async (): Promise<string> => {
+  const apiKey = await storage.get("rerankerApiKey")
+  return apiKey || ""
+}
+
+export const setRerankerApiKey = async (apiKey: string) => {
+  await storage.set("rerankerApiKey", apiKey)
+}
+
+export const getRerankerModel = async (): Promise<string> => {
+  const model = await storage.get("rerankerModel")
+  return model || ""
+}
+
+export const setRerankerModel = async (model: string) => {
+  await storage.set("rerankerModel", model)
+}
+
+export const getRerankSettings = async () => {
+  const [type, thresholds, topN, baseUrl, apiKey, model] = await Promise.all([
+    getRerankerType(),
+    getRerankerThresholds(),
+    getRerankerTopN(),
+    getRerankerBaseUrl(),
+    getRerankerApiKey(),
+    getRerankerModel()
+  ])
+
+  return {
+    type,
+    thresholds,
+    topN,
+    baseUrl,
+    apiKey,
+    model
+  }
+}
+
+export const setRerankSettings = async ({
+  type,
+  thresholds,
+  topN,
+  baseUrl,
+  apiKey,
+  model
+}: {
+  type: RerankerType
+  thresholds: RerankerThresholds
+  topN: number
+  baseUrl?: string
+  apiKey?: string
+  model?: string
+}) => {
+  await Promise.all([
+    setRerankerType(type),
+    setRerankerThresholds(thresholds),
+    setRerankerTopN(topN),
+    setRerankerBaseUrl(baseUrl || ""),
+    setRerankerApiKey(apiKey || ""),
+    setRerankerModel(model || "")
+  ])
+}
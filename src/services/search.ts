@@ -7,6 +7,8 @@ const storage2 = new Storage({
 
 const TOTAL_SEARCH_RESULTS = 2
 const DEFAULT_PROVIDER = "duckduckgo"
+const DEEP_RESEARCH_MAX_ROUNDS = 3
+const DEEP_RESEARCH_MAX_SOURCES = 12
 
 const AVAILABLE_PROVIDERS = ["google", "duckduckgo"] as const
 
@@ -160,6 +162,40 @@ export const setInternetSearchOn = async (defaultInternetSearchOn: boolean) => {
   await storage.set("defaultInternetSearchOn", defaultInternetSearchOn)
 }
 
+// empty means the search provider above
+export const getDeepResearchProviders = async () => {
+  const providers = await storage.get<string[] | undefined>(
+    "deepResearchProviders"
+  )
+  return providers ?? []
+}
+
+export const setDeepResearchProviders = async (providers: string[]) => {
+  await storage.set("deepResearchProviders", providers)
+}
+
+export const getDeepResearchMaxRounds = async () => {
+  const maxRounds = await storage.get<number | undefined>(
+    "deepResearchMaxRounds"
+  )
+  return maxRounds ?? DEEP_RESEARCH_MAX_ROUNDS
+}
+
+export const setDeepResearchMaxRounds = async (maxRounds: number) => {
+  await storage.set("deepResearchMaxRounds", maxRounds)
+}
+
+export const getDeepResearchMaxSources = async () => {
+  const maxSources = await storage.get<number | undefined>(
+    "deepResearchMaxSources"
+  )
+  return maxSources ?? DEEP_RESEARCH_MAX_SOURCES
+}
+
+export const setDeepResearchMaxSources = async (maxSources: number) => {
+  await storage.set("deepResearchMaxSources", maxSources)
+}
+
 export const getSearchSettings = async () => {
   const [
     isSimpleInternetSearch,
@@ -175,7 +211,10 @@ export const getSearchSettings = async () => {
     exaAPIKey,
     firecrawlAPIKey,
     ollamaSearchApiKey,
-    kagiApiKey
+    kagiApiKey,
+    deepResearchProviders,
+    deepResearchMaxRounds,
+    deepResearchMaxSources
   ] = await Promise.all([
     getIsSimpleInternetSearch(),
     getSearchProvider(),
@@ -190,7 +229,10 @@ export const getSearchSettings = async () => {
     getExaAPIKey(),
     getFirecrawlAPIKey(),
     getOllamaSearchApiKey(),
-    getKagiApiKey()
+    getKagiApiKey(),
+    getDeepResearchProviders(),
+    getDeepResearchMaxRounds(),
+    getDeepResearchMaxSources()
   ])
 
   return {
@@ -207,7 +249,10 @@ export const getSearchSettings = async () => {
     exaAPIKey,
     firecrawlAPIKey,
     ollamaSearchApiKey,
-    kagiApiKey
+    kagiApiKey,
+    deepResearchProviders,
+    deepResearchMaxRounds,
+    deepResearchMaxSources
   }
 }
 
@@ -225,7 +270,10 @@ export const setSearchSettings = async ({
   exaAPIKey,
   firecrawlAPIKey,
   ollamaSearchApiKey,
-  kagiApiKey
+  kagiApiKey,
+  deepResearchProviders,
+  deepResearchMaxRounds,
+  deepResearchMaxSources
 }: {
   isSimpleInternetSearch: boolean
   searchProvider: string
@@ -241,6 +289,9 @@ export const setSearchSettings = async ({
   firecrawlAPIKey: string
   ollamaSearchApiKey: string
   kagiApiKey: string
+  deepResearchProviders: string[]
+  deepResearchMaxRounds: number
+  deepResearchMaxSources: number
 }) => {
   await Promise.all([
     setIsSimpleInternetSearch(isSimpleInternetSearch),
@@ -256,6 +307,9 @@ export const setSearchSettings = async ({
     setExaAPIKey(exaAPIKey),
     setFirecrawlAPIKey(firecrawlAPIKey),
     setOllamaSearchApiKey(ollamaSearchApiKey),
-    setKagiApiKey(kagiApiKey)
+    setKagiApiKey(kagiApiKey),
+    setDeepResearchProviders(deepResearchProviders),
+    setDeepResearchMaxRounds(deepResearchMaxRounds),
+    setDeepResearchMaxSources(deepResearchMaxSources)
   ])
 }
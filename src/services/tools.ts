@@ -0,0 +1,141 @@
+import { Storage } from "@plasmohq/storage"
+
+const storage = new Storage()
+
+const DEFAULT_TOOL_MAX_ROUNDS = 5
+const DEFAULT_AGENT_MAX_STEPS = 15
+// tools that stay off until they are turned on, running model code is one
+export const OPT_IN_TOOLS = ["run_code"]
+
+export const isToolCallingEnabled = async (): Promise<boolean> => {
+  const enabled = await storage.get<boolean | undefined>("toolCallingEnabled")
+  return enabled ?? false
+}
+
+export const setToolCallingEnabled = async (enabled: boolean) => {
+  await storage.set("toolCallingEnabled", enabled)
+}
+
+// tools are enabled unless listed here, opt-in tools are not listed
+export const getDisabledTools = async (): Promise<string[]> => {
+  const disabledTools = await storage.get<string[] | undefined>(
+    "disabledTools"
+  )
+  return disabledTools || []
+}
+
+export const setDisabledTools = async (disabledTools: string[]) => {
+  await storage.set("disabledTools", disabledTools)
+}
+
+// opt-in tools are disabled unless listed here
+export const getEnabledOptInTools = async (): Promise<string[]> => {
+  const enabledOptInTools = await storage.get<string[] | undefined>(
+    "enabledOptInTools"
+  )
+  return enabledOptInTools || []
+}
+
+export const setEnabledOptInTools = async (enabledOptInTools: string[]) => {
+  await storage.set("enabledOptInTools", enabledOptInTools)
+}
+
+/**
+ * Names of the given tools that are enabled, opt-in tools only when they
+ * were turned on.
+ */
+export const filterEnabledTools = async (names: string[]) => {
+  const [disabled, optIn] = await Promise.all([
+    getDisabledTools(),
+    getEnabledOptInTools()
+  ])
+  return names.filter((name) =>
+    OPT_IN_TOOLS.includes(name)
+      ? optIn.includes(name)
+      : !disabled.includes(name)
+  )
+}
+
+export const getToolMaxRounds = async (): Promise<number> => {
+  const maxRounds = await storage.get<number | undefined>("toolMaxRounds")
+  return maxRounds || DEFAULT_TOOL_MAX_ROUNDS
+}
+
+export const setToolMaxRounds = async (maxRounds: number) => {
+  await storage.set("toolMaxRounds", maxRounds)
+}
+
+// actions the browser agent can take for one message
+export const getAgentMaxSteps = async (): Promise<number> => {
+  const maxSteps = await storage.get<number | undefined>("agentMaxSteps")
+  return maxSteps || DEFAULT_AGENT_MAX_STEPS
+}
+
+export const setAgentMaxSteps = async (maxSteps: number) => {
+  await storage.set("agentMaxSteps", maxSteps)
+}
+
+// Python packages are downloaded from the Pyodide CDN, so it stays off until
+// it is turned on
+export const isPythonPackageDownloadEnabled = async (): Promise<boolean> => {
+  const enabled = await storage.get<boolean | undefined>(
+    "pythonPackageDownload"
+  )
+  return enabled ?? false
+}
+
+export const setPythonPackageDownloadEnabled = async (enabled: boolean) => {
+  await storage.set("pythonPackageDownload", enabled)
+}
+
+export const getToolSettings = async () => {
+  const [
+    enabled,
+    disabledTools,
+    enabledOptInTools,
+    maxRounds,
+    agentMaxSteps,
+    pythonPackageDownload
+  ] = await Promise.all([
+    isToolCallingEnabled(),
+    getDisabledTools(),
+    getEnabledOptInTools(),
+    getToolMaxRounds(),
+    getAgentMaxSteps(),
+    isPythonPackageDownloadEnabled()
+  ])
+
+  return {
+    enabled,
+    disabledTools,
+    enabledOptInTools,
+    maxRounds,
+    agentMaxSteps,
+    pythonPackageDownload
+  }
+}
+
+export const setToolSettings = async ({
+  enabled,
+  disabledTools,
+  enabledOptInTools,
+  maxRounds,
+  agentMaxSteps,
+  pythonPackageDownload
+}: {
+  enabled: boolean
+  disabledTools: string[]
+  enabledOptInTools: string[]
+  maxRounds: number
+  agentMaxSteps: number
+  pythonPackageDownload: boolean
+}) => {
+  await Promise.all([
+    setToolCallingEnabled(enabled),
+    setDisabledTools(disabledTools),
+    setEnabledOptInTools(enabledOptInTools),
+    setToolMaxRounds(maxRounds),
+    setAgentMaxSteps(agentMaxSteps),
+    setPythonPackageDownloadEnabled(pythonPackageDownload)
+  ])
+}
@@ -0,0 +1,27 @@
+import type { ToolConfirmation } from "@/tools/types"
+import { create } from "zustand"
+
+type PendingConfirmation = ToolConfirmation & {
+  resolve: (confirmed: boolean) => void
+}
+
+type State = {
+  // browser agent action waiting for the user
+  pendingConfirmation: PendingConfirmation | null
+  requestConfirmation: (request: ToolConfirmation) => Promise<boolean>
+  respondConfirmation: (confirmed: boolean) => void
+}
+
+export const useAgentStore = create<State>((set, get) => ({
+  pendingConfirmation: null,
+  requestConfirmation: (request) =>
+    new Promise<boolean>((resolve) => {
+      // a newer request replaces one that was not answered
+      get().pendingConfirmation?.resolve(false)
+      set({ pendingConfirmation: { ...request, resolve } })
+    }),
+  respondConfirmation: (confirmed) => {
+    get().pendingConfirmation?.resolve(confirmed)
+    set({ pendingConfirmation: null })
+  }
+}))
@@ -1,5 +1,6 @@
 import { Knowledge } from "@/db/knowledge"
 import { ChatDocuments } from "@/models/ChatTypes"
+import type { ToolCallStep } from "@/tools/types"
 import { create } from "zustand"
 import { type UploadedFile } from "@/db/dexie/types"
 import { isFireFoxPrivateMode } from "@/utils/is-private-mode"
@@ -26,6 +27,7 @@ export type Message = {
   modelName?: string
   modelImage?: string
   documents?: ChatDocuments
+  toolCalls?: ToolCallStep[]
 }
 
 export type ChatHistory = {
@@ -58,6 +60,10 @@ type State = {
   setIsEmbedding: (isEmbedding: boolean) => void
   webSearch: boolean
   setWebSearch: (webSearch: boolean) => void
+  deepResearch: boolean
+  setDeepResearch: (deepResearch: boolean) => void
+  agentMode: boolean
+  setAgentMode: (agentMode: boolean) => void
   isSearchingInternet: boolean
   setIsSearchingInternet: (isSearchingInternet: boolean) => void
 
@@ -121,6 +127,10 @@ export const useStoreMessageOption = create<State>((set) => ({
   setIsEmbedding: (isEmbedding) => set({ isEmbedding }),
   webSearch: false,
   setWebSearch: (webSearch) => set({ webSearch }),
+  deepResearch: false,
+  setDeepResearch: (deepResearch) => set({ deepResearch }),
+  agentMode: false,
+  setAgentMode: (agentMode) => set({ agentMode }),
   isSearchingInternet: false,
   setIsSearchingInternet: (isSearchingInternet) => set({ isSearchingInternet }),
   selectedSystemPrompt: null,
@@ -0,0 +1,260 @@
+import {
+  clickElement,
+  describeElement,
+  extractText,
+  formatSnapshot,
+  getTabUrl,
+  goBack,
+  navigateTab,
+  pressEnter,
+  scrollPage,
+  snapshotPage,
+  typeText,
+  waitForPage
+} from "@/libs/browser-agent"
+import { getTabContents } from "@/libs/get-tab-contents"
+import type { PageAssistTool, ToolContext, ToolResult } from "./types"
+
+const requireTab = (context: ToolContext) => {
+  if (!context.tabId) {
+    throw new Error("No tab to act on")
+  }
+  return context.tabId
+}
+
+// actions return the new snapshot so that the model sees their effect
+const withSnapshot = async (
+  tabId: number,
+  summary: string
+): Promise<ToolResult> => ({
+  content: `${summary}\n\n${formatSnapshot(await snapshotPage(tabId))}`,
+  summary
+})
+
+const getElement = async (tabId: number, ref: number) => {
+  const element = await describeElement(tabId, ref)
+  if (!element?.found) {
+    throw new Error(
+      `No element with ref ${ref}, take a new snapshot and use its refs`
+    )
+  }
+  return element
+}
+
+const declined = (summary: string): ToolResult => ({
+  content: `The user declined this action: ${summary}. Do not retry it, ask the user how to continue.`,
+  summary: `Declined: ${summary}`
+})
+
+const refParameter = {
+  type: "number",
+  description: "Ref of the element from the latest snapshot"
+}
+
+export const snapshotTool: PageAssistTool = {
+  name: "browser_snapshot",
+  description:
+    "Get an accessibility tree snapshot of the page. Interactive elements are listed as [ref] role \"name\", use the refs with the other browser tools.",
+  parameters: {
+    type: "object",
+    properties: {}
+  },
+  execute: async (_, context) => {
+    const snapshot = await snapshotPage(requireTab(context))
+    return {
+      content: formatSnapshot(snapshot),
+      summary: `Read ${snapshot.url}`
+    }
+  }
+}
+
+export const clickTool: PageAssistTool = {
+  name: "browser_click",
+  description: "Click an element of the page.",
+  parameters: {
+    type: "object",
+    properties: {
+      ref: refParameter
+    },
+    required: ["ref"]
+  },
+  execute: async ({ ref }, context) => {
+    const tabId = requireTab(context)
+    const element = await getElement(tabId, ref)
+    const summary = `Clicked ${element.description}`
+    if (
+      element.submits &&
+      !(await context.confirm?.({
+        tool: "browser_click",
+        description: `Click ${element.description}, which is part of a form and can submit it`
+      }))
+    ) {
+      return declined(summary)
+    }
+    await clickElement(tabId, ref)
+    await waitForPage(tabId)
+    return withSnapshot(tabId, summary)
+  }
+}
+
+export const typeTool: PageAssistTool = {
+  name: "browser_type",
+  description:
+    "Type text into a text field or choose an option of a select. Set submit to press Enter afterwards.",
+  parameters: {
+    type: "object",
+    properties: {
+      ref: refParameter,
+      text: {
+        type: "string",
+        description: "Text to type, or the option to choose"
+      },
+      submit: {
+        type: "boolean",
+        description: "Press Enter after typing, e.g. to search"
+      },
+      clear: {
+        type: "boolean",
+        description: "Replace the current value, defaults to true"
+      }
+    },
+    required: ["ref", "text"]
+  },
+  execute: async ({ ref, text, submit = false, clear = true }, context) => {
+    const tabId = requireTab(context)
+    const element = await getElement(tabId, ref)
+    const summary = `Typed "${text}" into ${element.description}${
+      submit ? " and pressed Enter" : ""
+    }`
+    if (
+      submit &&
+      !(await context.confirm?.({
+        tool: "browser_type",
+        description: `Type "${text}" into ${element.description} and press Enter, which can submit a form`
+      }))
+    ) {
+      return declined(summary)
+    }
+    const error = await typeText(tabId, ref, String(text), clear)
+    if (error) {
+      throw new Error(error)
+    }
+    if (submit) {
+      await pressEnter(tabId, ref)
+      await waitForPage(tabId)
+    }
+    return withSnapshot(tabId, summary)
+  }
+}
+
+export const scrollTool: PageAssistTool = {
+  name: "browser_scroll",
+  description:
+    "Scroll the page up or down by a screen, to the top or bottom, or to an element.",
+  parameters: {
+    type: "object",
+    properties: {
+      direction: {
+        type: "string",
+        enum: ["up", "down", "top", "bottom"]
+      },
+      ref: {
+        type: "number",
+        description: "Scroll to this element instead"
+      }
+    }
+  },
+  execute: async ({ direction = "down", ref }, context) => {
+    const tabId = requireTab(context)
+    if (!(await scrollPage(tabId, direction, ref))) {
+      throw new Error(`No element with ref ${ref}`)
+    }
+    return withSnapshot(
+      tabId,
+      ref ? `Scrolled to element ${ref}` : `Scrolled ${direction}`
+    )
+  }
+}
+
+export const navigateTool: PageAssistTool = {
+  name: "browser_navigate",
+  description:
+    "Open a URL in the tab, or go back to the previous page when url is \"back\".",
+  parameters: {
+    type: "object",
+    properties: {
+      url: {
+        type: "string",
+        description: "The URL to open, or \"back\""
+      }
+    },
+    required: ["url"]
+  },
+  execute: async ({ url }, context) => {
+    const tabId = requireTab(context)
+    if (url === "back") {
+      await goBack(tabId)
+      return withSnapshot(tabId, "Went back")
+    }
+    const target = /^[a-z]+:/i.test(url) ? url : `https://${url}`
+    if (!/^https?:/i.test(target)) {
+      throw new Error("Only http and https URLs can be opened")
+    }
+    const current = await getTabUrl(tabId)
+    const origin = new URL(target).origin
+    if (
+      (!current || new URL(current).origin !== origin) &&
+      !(await context.confirm?.({
+        tool: "browser_navigate",
+        description: `Open ${target}, which leaves the current site`
+      }))
+    ) {
+      return declined(`Opened ${target}`)
+    }
+    await navigateTab(tabId, target)
+    return withSnapshot(tabId, `Opened ${target}`)
+  }
+}
+
+export const extractTool: PageAssistTool = {
+  name: "browser_extract",
+  description:
+    "Get the full text of an element, or the readable content of the whole page when no ref is given.",
+  parameters: {
+    type: "object",
+    properties: {
+      ref: {
+        type: "number",
+        description: "Ref of the element, leave out for the whole page"
+      }
+    }
+  },
+  execute: async ({ ref }, context) => {
+    const tabId = requireTab(context)
+    if (ref) {
+      const text = await extractText(tabId, ref)
+      if (text === null) {
+        throw new Error(`No element with ref ${ref}`)
+      }
+      return { content: text, summary: `Extracted element ${ref}` }
+    }
+    const tab = await browser.tabs.get(tabId)
+    const content = await getTabContents([
+      { type: "tab", tabId, title: tab.title, url: tab.url }
+    ])
+    return {
+      content: content || "The page has no readable content.",
+      summary: `Extracted ${tab.url}`,
+      sources: [{ url: tab.url, name: tab.title || tab.url, type: "url" }]
+    }
+  }
+}
+
+export const BROWSER_TOOLS: PageAssistTool[] = [
+  snapshotTool,
+  clickTool,
+  typeTool,
+  scrollTool,
+  navigateTool,
+  extractTool
+]
@@ -0,0 +1,33 @@
+import { formatCodeResult, runCode } from "@/libs/code-interpreter"
+import type { PageAssistTool } from "./types"
+
+export const codeInterpreterTool: PageAssistTool = {
+  name: "run_code",
+  description:
+    "Run JavaScript or Python code in a sandbox without access to the browser or the page and get its output. Use it for calculations, data processing and charts. Python has its standard library; numpy, pandas and matplotlib can only be imported when the user turned on package downloads from the Pyodide CDN, otherwise the import fails. Matplotlib figures left open are shown to the user. In JavaScript use console.log for output and display(OffscreenCanvas or SVG string) for charts, there is no DOM.",
+  parameters: {
+    type: "object",
+    properties: {
+      language: {
+        type: "string",
+        enum: ["python", "javascript"]
+      },
+      code: {
+        type: "string",
+        description: "The code to run"
+      }
+    },
+    required: ["language", "code"]
+  },
+  execute: async ({ language, code }) => {
+    const result = await runCode(
+      language === "javascript" ? "javascript" : "python",
+      String(code)
+    )
+    return {
+      content: formatCodeResult(result),
+      summary: result.error ? "Failed" : undefined,
+      images: result.images
+    }
+  }
+}
@@ -0,0 +1,51 @@
+import { getTabContents } from "@/libs/get-tab-contents"
+import type { PageAssistTool } from "./types"
+
+const isWebPage = (url?: string) => !!url && /^(https?|file):/.test(url)
+
+/**
+ * The active tab, or when that is an extension page (the chat itself) the
+ * web page used last.
+ */
+export const getCurrentTab = async () => {
+  const [active] = await browser.tabs.query({
+    active: true,
+    lastFocusedWindow: true
+  })
+  if (isWebPage(active?.url)) {
+    return active
+  }
+  const tabs = await browser.tabs.query({})
+  return tabs
+    .filter((tab) => isWebPage(tab.url))
+    .sort((a, b) => (b.lastAccessed ?? 0) - (a.lastAccessed ?? 0))[0]
+}
+
+export const currentTabTool: PageAssistTool = {
+  name: "read_current_tab",
+  description:
+    "Read the text content of the web page the user is currently looking at.",
+  parameters: {
+    type: "object",
+    properties: {}
+  },
+  execute: async () => {
+    const tab = await getCurrentTab()
+    if (!tab?.id) {
+      return { content: "No web page is open." }
+    }
+    const content = await getTabContents([
+      { type: "tab", tabId: tab.id, title: tab.title, url: tab.url }
+    ])
+    return {
+      content: content || "The page has no readable content.",
+      sources: [
+        {
+          url: tab.url,
+          name: tab.title || tab.url,
+          type: "url"
+        }
+      ]
+    }
+  }
+}
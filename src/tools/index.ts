@@ -0,0 +1,42 @@
+import { isCodeInterpreterSupported } from "@/libs/code-interpreter"
+import { filterEnabledTools, isToolCallingEnabled } from "@/services/tools"
+import { codeInterpreterTool } from "./code-interpreter"
+import { currentTabTool } from "./current-tab"
+import { knowledgeSearchTool } from "./knowledge-search"
+import { getMcpTools } from "./mcp"
+import type { PageAssistTool } from "./types"
+import { webSearchTool } from "./web-search"
+
+export const BUILTIN_TOOLS: PageAssistTool[] = [
+  webSearchTool,
+  knowledgeSearchTool,
+  currentTabTool,
+  ...(isCodeInterpreterSupported() ? [codeInterpreterTool] : [])
+]
+
+/**
+ * Tools offered to the chat model, the enabled built-in tools and the tools
+ * of the enabled MCP servers. None when tool calling is turned off.
+ */
+export const getEnabledTools = async (): Promise<PageAssistTool[]> => {
+  if (!(await isToolCallingEnabled())) {
+    return []
+  }
+  const enabled = await filterEnabledTools(
+    BUILTIN_TOOLS.map((tool) => tool.name)
+  )
+  return [
+    ...BUILTIN_TOOLS.filter((tool) => enabled.includes(tool.name)),
+    ...(await getMcpTools())
+  ]
+}
+
+// the function tool format of OpenAI, which Ollama accepts as well
+export const toFunctionTool = (tool: PageAssistTool) => ({
+  type: "function",
+  function: {
+    name: tool.name,
+    description: tool.description,
+    parameters: tool.parameters
+  }
+})
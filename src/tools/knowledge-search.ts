@@ -0,0 +1,89 @@
+import { getAllKnowledge, getKnowledgeById } from "@/db/dexie/knowledge"
+import { retrieveKnowledge } from "@/libs/retrieve-knowledge"
+import { PageAssistVectorStore } from "@/libs/PageAssistVectorStore"
+import { cleanUrl } from "@/libs/clean-url"
+import { pageAssistEmbeddingModel } from "@/models/embedding"
+import { getOllamaURL } from "@/services/ollama"
+import type { PageAssistTool } from "./types"
+
+const findKnowledge = async (name?: string, knowledgeId?: string) => {
+  if (name) {
+    const knowledge = await getAllKnowledge("finished")
+    const match = knowledge?.find(
+      (kb) => kb.title.toLowerCase() === name.toLowerCase()
+    )
+    if (match) {
+      return match
+    }
+  }
+  return knowledgeId ? getKnowledgeById(knowledgeId) : undefined
+}
+
+export const knowledgeSearchTool: PageAssistTool = {
+  name: "knowledge_search",
+  description:
+    "Search a knowledge base of the user's documents. Searches the knowledge base selected for the chat unless a name is given.",
+  parameters: {
+    type: "object",
+    properties: {
+      query: {
+        type: "string",
+        description: "What to look for in the documents"
+      },
+      knowledge_base: {
+        type: "string",
+        description: "Name of the knowledge base to search"
+      }
+    },
+    required: ["query"]
+  },
+  execute: async ({ query, knowledge_base }, { selectedModel, knowledgeId }) => {
+    const knowledge = await findKnowledge(knowledge_base, knowledgeId)
+    if (!knowledge) {
+      const available = (await getAllKnowledge("finished")) || []
+      return {
+        content: available.length
+          ? `Knowledge base not found. Available knowledge bases: ${available
+              .map((kb) => kb.title)
+              .join(", ")}`
+          : "There are no knowledge bases."
+      }
+    }
+
+    const ollamaUrl = await getOllamaURL()
+    const embedding = await pageAssistEmbeddingModel({
+      model: knowledge.embedding_model,
+      baseUrl: cleanUrl(ollamaUrl)
+    })
+    const vectorstore = await PageAssistVectorStore.fromExistingIndex(
+      embedding,
+      { file_id: null, knownledge_id: knowledge.id }
+    )
+    const docs = await retrieveKnowledge({
+      vectorstore,
+      embedding,
+      query,
+      selectedModel,
+      retrievalMode: knowledge.retrievalMode
+    })
+    if (docs.length === 0) {
+      return { content: "No matching documents found." }
+    }
+
+    return {
+      content: docs
+        .map(
+          (doc) =>
+            `<doc source="${doc.metadata?.source || "untitled"}">${doc.pageContent}</doc>`
+        )
+        .join("\n"),
+      sources: docs.map((doc) => ({
+        ...doc,
+        name: doc?.metadata?.source || "untitled",
+        type: doc?.metadata?.type || "unknown",
+        mode: "rag",
+        url: ""
+      }))
+    }
+  }
+}
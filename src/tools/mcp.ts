@@ -0,0 +1,58 @@
+import { getEnabledMcpServers } from "@/db/dexie/mcp"
+import type { McpServer } from "@/db/dexie/types"
+import { getMcpClient, type McpTool } from "@/libs/mcp-client"
+import type { PageAssistTool } from "./types"
+
+// function names allow letters, digits, underscores and dashes
+const toFunctionName = (text: string) =>
+  text.replace(/[^a-zA-Z0-9_-]+/g, "_").replace(/^_+|_+$/g, "")
+
+const toPageAssistTool = (
+  server: McpServer,
+  tool: McpTool,
+  name: string
+): PageAssistTool => ({
+  name,
+  description: `${tool.description || tool.name} (MCP server ${server.name})`,
+  parameters: tool.inputSchema || { type: "object", properties: {} },
+  execute: async (args, { signal }) => {
+    const client = await getMcpClient(server)
+    return { content: await client.callTool(tool.name, args, signal) }
+  }
+})
+
+/**
+ * Tools of the enabled MCP servers, named `<server>__<tool>` so that tools
+ * of different servers do not clash. The lists are cached per client
+ * session, a client is replaced when its server settings change. Servers
+ * that fail to connect are skipped.
+ */
+export const getMcpTools = async (): Promise<PageAssistTool[]> => {
+  const servers = await getEnabledMcpServers()
+  const lists = await Promise.all(
+    servers.map(async (server) => {
+      try {
+        const client = await getMcpClient(server)
+        return { server, tools: await client.getTools() }
+      } catch (e) {
+        console.error(`Failed to list tools of MCP server ${server.name}`, e)
+        return { server, tools: [] as McpTool[] }
+      }
+    })
+  )
+
+  const names = new Set<string>()
+  return lists.flatMap(({ server, tools }) =>
+    tools.map((tool) => {
+      const base = `${toFunctionName(server.name) || "mcp"}__${toFunctionName(
+        tool.name
+      )}`.slice(0, 60)
+      let name = base
+      for (let i = 2; names.has(name); i++) {
+        name = `${base}_${i}`
+      }
+      names.add(name)
+      return toPageAssistTool(server, tool, name)
+    })
+  )
+}
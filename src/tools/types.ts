@@ -0,0 +1,49 @@
+export type ToolContext = {
+  selectedModel: string
+  signal?: AbortSignal
+  // knowledge base selected for the chat, searched by default
+  knowledgeId?: string
+  // tab the browser agent acts on
+  tabId?: number
+  // asks the user before an action with side effects, e.g. a form submit
+  confirm?: (request: ToolConfirmation) => Promise<boolean>
+}
+
+export type ToolConfirmation = {
+  tool: string
+  description: string
+}
+
+export type ToolResult = {
+  // text returned to the model
+  content: string
+  // shown as message sources, same shape as the chat mode sources
+  sources?: any[]
+  // short description of what the tool did, shown in the chat
+  summary?: string
+  // charts and other images as data URLs, shown in the chat
+  images?: string[]
+}
+
+export type PageAssistTool = {
+  name: string
+  description: string
+  // JSON schema of the arguments
+  parameters: Record<string, any>
+  execute: (
+    args: Record<string, any>,
+    context: ToolContext
+  ) => Promise<ToolResult>
+}
+
+// a tool call as shown in the chat and saved with the message
+export type ToolCallStep = {
+  id: string
+  name: string
+  args: Record<string, any>
+  status: "running" | "done" | "error"
+  result?: string
+  error?: string
+  summary?: string
+  images?: string[]
+}
@@ -0,0 +1,28 @@
+import { getSystemPromptForWeb } from "@/web/web"
+import type { PageAssistTool } from "./types"
+
+export const webSearchTool: PageAssistTool = {
+  name: "web_search",
+  description:
+    "Search the web with the configured search engine. Use it for recent events or facts you are not sure about.",
+  parameters: {
+    type: "object",
+    properties: {
+      query: {
+        type: "string",
+        description: "The search query"
+      }
+    },
+    required: ["query"]
+  },
+  execute: async ({ query }) => {
+    const { prompt, source } = await getSystemPromptForWeb(query, true)
+    if (!prompt) {
+      return { content: "No results found." }
+    }
+    return {
+      content: prompt,
+      sources: source
+    }
+  }
+}
@@ -1,4 +1,5 @@
 import { ChatDocuments } from "@/models/ChatTypes"
+import type { ToolCallStep } from "@/tools/types"
 
 type WebSearch = {
   search_engine: string
@@ -23,4 +24,5 @@ export type Message = {
   modelImage?: string
   modelName?: string
   documents?: ChatDocuments
+  toolCalls?: ToolCallStep[]
 }
\ No newline at end of file
@@ -0,0 +1,72 @@
+const CITATION_REGEX = /\[(\d+(?:\s*,\s*\d+)*)\](?!\()/g
+const CITATION_HREF_PREFIX = "#citation-"
+
+/**
+ * Turns `[n]` and `[n, m]` markers emitted by the model into links the
+ * markdown renderer can pick up. Markers inside code fences, inline code and
+ * numbers that do not belong to a cited source are left untouched.
+ */
+export const linkCitations = (message: string, citations: number[]) => {
+  if (!citations.length) {
+    return message
+  }
+  const known = new Set(citations)
+  return message
+    .split(/(```[\s\S]*?(?:```|$)|``[^\n]*?``|`[^`\n]+`)/)
+    .map((part, i) => {
+      if (i % 2 === 1) {
+        return part
+      }
+      return part.replace(CITATION_REGEX, (match, ids: string) => {
+        const numbers = ids.split(",").map((id) => parseInt(id.trim()))
+        if (numbers.some((n) => !known.has(n))) {
+          return match
+        }
+        return numbers
+          .map((n) => `[${n}](${CITATION_HREF_PREFIX}${n})`)
+          .join("")
+      })
+    })
+    .join("")
+}
+
+export const getCitationFromHref = (href?: string): number | null => {
+  if (!href?.startsWith(CITATION_HREF_PREFIX)) {
+    return null
+  }
+  const citation = parseInt(href.slice(CITATION_HREF_PREFIX.length))
+  return isNaN(citation) ? null : citation
+}
+
+export type SourceLocation = {
+  page?: number
+  row?: number
+  heading?: string
+  lines?: { from: number; to: number }
+}
+
+/**
+ * Where a retrieved chunk sits inside its file: the page for PDFs, the row
+ * for CSVs and the heading path for Markdown.
+ */
+export const getSourceLocation = (
+  metadata?: Record<string, any>
+): SourceLocation => {
+  const location: SourceLocation = {}
+  if (!metadata) {
+    return location
+  }
+  if (typeof metadata.page === "number") {
+    location.page = metadata.page
+  }
+  if (metadata.type === "csv" && typeof metadata.line === "number") {
+    location.row = metadata.line
+  }
+  if (metadata.heading) {
+    location.heading = metadata.heading
+  }
+  if (metadata.loc?.lines?.from && metadata.loc?.lines?.to) {
+    location.lines = metadata.loc.lines
+  }
+  return location
+}
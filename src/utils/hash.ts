@@ -0,0 +1,7 @@
+export const sha256 = async (text: string): Promise<string> => {
+  const data = new TextEncoder().encode(text)
+  const digest = await crypto.subtle.digest("SHA-256", data)
+  return Array.from(new Uint8Array(digest))
+    .map((b) => b.toString(16).padStart(2, "0"))
+    .join("")
+}
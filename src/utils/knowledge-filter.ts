@@ -0,0 +1,126 @@
+import type { Document } from "@langchain/core/documents"
+import type { Knowledge, Source } from "@/db/dexie/types"
+
+export type KnowledgeFilter = {
+  files: string[]
+  types: string[]
+  after?: number
+  before?: number
+}
+
+// @file:report.pdf, @file:"annual report.pdf", @type:pdf,
+// @after:2024-01-01, @before:2024-12-31
+const FILTER_REGEX = /(^|\s)@(file|type|after|before):(?:"([^"]+)"|(\S+))/g
+
+/**
+ * Formats a file name as a `@file:` mention, quoting names that contain
+ * whitespace so they can be parsed back.
+ */
+export const toFileMention = (filename: string) => {
+  return /\s/.test(filename) ? `@file:"${filename}"` : `@file:${filename}`
+}
+
+/**
+ * Extracts `@file:`, `@type:`, `@after:` and `@before:` filters from a
+ * message. Returns the filter, or `null` when the message has none, together
+ * with the message without the filter tokens.
+ */
+export const parseKnowledgeFilter = (
+  message: string
+): { filter: KnowledgeFilter | null; query: string } => {
+  const filter: KnowledgeFilter = { files: [], types: [] }
+  let found = false
+
+  const query = message
+    .replace(FILTER_REGEX, (match, prefix, key, quoted, plain) => {
+      const value: string = quoted ?? plain
+      switch (key) {
+        case "file":
+          filter.files.push(value)
+          break
+        case "type":
+          filter.types.push(value.replace(/^\./, "").toLowerCase())
+          break
+        case "after":
+        case "before": {
+          const date = Date.parse(value)
+          if (isNaN(date)) {
+            return match
+          }
+          if (key === "after") {
+            filter.after = date
+          } else {
+            // include the whole day
+            filter.before = date + 24 * 60 * 60 * 1000 - 1
+          }
+          break
+        }
+      }
+      found = true
+      return prefix
+    })
+    .replace(/\s{2,}/g, " ")
+    .trim()
+
+  return { filter: found ? filter : null, query: query || message }
+}
+
+const getSourceExtension = (source: Source) => {
+  return source.filename?.split(".").pop()?.toLowerCase()
+}
+
+/**
+ * The sources of a knowledge base that match the filter. Sources added
+ * before dates were tracked fall back to the creation date of the knowledge
+ * base.
+ */
+export const getFilteredSources = (
+  filter: KnowledgeFilter,
+  knowledge: Knowledge
+): Source[] => {
+  const files = filter.files.map((file) => file.toLowerCase())
+  return knowledge.source.filter((source) => {
+    const filename = source.filename?.toLowerCase() || ""
+    if (files.length && !files.includes(filename)) {
+      return false
+    }
+    if (
+      filter.types.length &&
+      !filter.types.some(
+        (type) =>
+          getSourceExtension(source) === type ||
+          source.type?.toLowerCase().includes(type)
+      )
+    ) {
+      return false
+    }
+    const date = source.updatedAt ?? knowledge.createdAt
+    if (filter.after && date < filter.after) {
+      return false
+    }
+    if (filter.before && date > filter.before) {
+      return false
+    }
+    return true
+  })
+}
+
+/**
+ * Builds the filter function passed to `PageAssistVectorStore`. Chunks are
+ * matched to their source through `metadata.file_id`, so sources that share
+ * a file name are told apart.
+ */
+export const createKnowledgeFilter = (
+  filter: KnowledgeFilter | null,
+  knowledge: Knowledge
+): ((doc: Document) => boolean) | undefined => {
+  if (!filter) {
+    return undefined
+  }
+
+  const allowed = new Set(
+    getFilteredSources(filter, knowledge).map((source) => source.source_id)
+  )
+
+  return (doc: Document) => allowed.has(doc.metadata?.file_id)
+}
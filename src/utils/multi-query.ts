@@ -0,0 +1,76 @@
+import type { Document } from "@langchain/core/documents"
+import { pageAssistModel } from "@/models"
+import { getOllamaURL } from "@/services/ollama"
+import { cleanUrl } from "@/libs/clean-url"
+import { removeReasoning } from "@/libs/reasoning"
+import { reciprocalRankFusion } from "@/libs/bm25"
+
+const MULTI_QUERY_PROMPT = `You are helping to search a knowledge base. Write {count} different versions of the question below that use other words or look at it from another angle, so that together they find more relevant documents. Keep each version self-contained and in the language of the original question.
+Return only the questions, one per line, without numbering or any other text.
+
+Question: {question}`
+
+/**
+ * Asks the chat model for `count` paraphrases of the (already standalone)
+ * query. The original query is always returned first so retrieval never
+ * depends on the model producing usable output.
+ */
+export const generateQueryVariants = async ({
+  model,
+  query,
+  count
+}: {
+  model: string
+  query: string
+  count: number
+}): Promise<string[]> => {
+  if (count <= 0) {
+    return [query]
+  }
+  try {
+    const url = await getOllamaURL()
+    const llm = await pageAssistModel({
+      model,
+      baseUrl: cleanUrl(url)
+    })
+    const response = await llm.invoke(
+      MULTI_QUERY_PROMPT.replaceAll("{count}", count.toString()).replaceAll(
+        "{question}",
+        query
+      )
+    )
+    const variants = removeReasoning(response.content.toString())
+      .split("\n")
+      .map((line) =>
+        line
+          .replace(/^\s*(?:[-*•]|\d+[.)])\s*/, "")
+          .replace(/^["']|["']$/g, "")
+          .trim()
+      )
+      .filter((line) => line.length > 0 && line !== query)
+      .slice(0, count)
+    return [query, ...variants]
+  } catch (e) {
+    console.error("Failed to generate query variants", e)
+    return [query]
+  }
+}
+
+/**
+ * Runs `search` for every query and merges the results with reciprocal rank
+ * fusion. Chunks found by several queries rank higher and duplicates are
+ * collapsed on their content.
+ */
+export const multiQuerySearch = async (
+  queries: string[],
+  k: number,
+  search: (query: string, k: number) => Promise<Document[]>
+): Promise<Document[]> => {
+  if (queries.length === 1) {
+    return search(queries[0], k)
+  }
+  const results = await Promise.all(queries.map((query) => search(query, k)))
+  return reciprocalRankFusion(results, (doc) => doc.pageContent, k).map(
+    ([doc]) => doc
+  )
+}
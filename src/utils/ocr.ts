@@ -1,18 +1,22 @@
 import { getOCRLanguageToUse, isOfflineOCR } from "@/services/ocr"
 import { createWorker } from "pa-tesseract.js"
 
+export const createOCRWorker = async (lang: string) => {
+    const isOCROffline = isOfflineOCR(lang)
+
+    return createWorker(lang, undefined, {
+        workerPath: "/ocr/worker.min.js",
+        workerBlobURL: false,
+        corePath: "/ocr/tesseract-core-simd.js",
+        errorHandler: (e) => console.error(e),
+        langPath: !isOCROffline ? "/ocr/lang" : undefined
+    })
+}
+
 export async function processImageForOCR(imageData: string): Promise<string> {
     try {
         const lang = await getOCRLanguageToUse() 
-        const isOCROffline = isOfflineOCR(lang)
-        
-        const worker = await createWorker(lang, undefined, {
-            workerPath: "/ocr/worker.min.js",
-            workerBlobURL: false,
-            corePath: "/ocr/tesseract-core-simd.js",
-            errorHandler: (e) => console.error(e),
-            langPath: !isOCROffline ? "/ocr/lang" : undefined
-        })
+        const worker = await createOCRWorker(lang)
 
         const result = await worker.recognize(imageData)
 
@@ -1,44 +1,230 @@
 import type { EmbeddingsInterface } from "@langchain/core/embeddings"
 import type { Document } from "@langchain/core/documents"
 import * as ml_distance from "ml-distance"
+import { cleanUrl } from "@/libs/clean-url"
+import { removeReasoning } from "@/libs/reasoning"
+import { pageAssistModel } from "@/models"
+import { getOllamaURL } from "@/services/ollama"
+import { getRerankSettings } from "@/services/rerank"
 
-export const rerankDocs = async ({
-  query,
-  docs,
-  embedding
-}: {
+/**
+ * A reranker scores each document against the query. Higher is more
+ * relevant; the scale depends on the implementation.
+ */
+export interface Reranker {
+  score(query: string, docs: Document[]): Promise<number[]>
+}
+
+export class EmbeddingReranker implements Reranker {
+  constructor(private embedding: EmbeddingsInterface) {}
+
+  async score(query: string, docs: Document[]) {
+    const [docEmbeddings, queryEmbedding] = await Promise.all([
+      this.embedding.embedDocuments(docs.map((doc) => doc.pageContent)),
+      this.embedding.embedQuery(query)
+    ])
+
+    // perform cosine similarity between query and document
+    return docEmbeddings.map((docEmbedding) =>
+      ml_distance.similarity.cosine(queryEmbedding, docEmbedding)
+    )
+  }
+}
+
+/**
+ * Calls an OpenAI-compatible `/rerank` endpoint as exposed by llama.cpp,
+ * vLLM, Jina and Cohere-style APIs.
+ */
+export class EndpointReranker implements Reranker {
+  constructor(
+    private config: { baseUrl: string; model: string; apiKey?: string }
+  ) {}
+
+  async score(query: string, docs: Document[]) {
+    let url = cleanUrl(this.config.baseUrl)
+    if (!url.endsWith("/rerank")) {
+      url = `${url}/rerank`
+    }
+
+    const response = await fetch(url, {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+        ...(this.config.apiKey
+          ? { Authorization: `Bearer ${this.config.apiKey}` }
+          : {})
+      },
+      body: JSON.stringify({
+        model: this.config.model,
+        query,
+        documents: docs.map((doc) => doc.pageContent),
+        top_n: docs.length
+      })
+    })
+
+    if (!response.ok) {
+      throw new Error(
+        `Rerank request failed: ${response.status} ${response.statusText}`
+      )
+    }
+
+    const data = await response.json()
+    const results: any[] = data?.results || data?.data || []
+    const scores = new Array(docs.length).fill(Number.NEGATIVE_INFINITY)
+    for (const result of results) {
+      if (typeof result?.index === "number") {
+        scores[result.index] = result.relevance_score ?? result.score ?? 0
+      }
+    }
+    return scores
+  }
+}
+
+const LLM_RERANK_PROMPT = `You are a relevance grader. Rate how relevant the document is to the question on a scale from 0 (not relevant) to 10 (directly answers the question).
+
+ONLY RETURN THE NUMBER WITHOUT ANY TEXT
+
+Question: {question}
+
+Document:
+{document}
+
+Score:`
+
+// documents graded at the same time, local models serve few requests in
+// parallel
+const LLM_RERANK_CONCURRENCY = 4
+
+/**
+ * Uses the selected chat model as a judge. Scores are normalised to 0..1.
+ */
+export class LLMReranker implements Reranker {
+  constructor(private model: string) {}
+
+  async score(query: string, docs: Document[]) {
+    const url = await getOllamaURL()
+    const llm = await pageAssistModel({
+      model: this.model,
+      baseUrl: cleanUrl(url)
+    })
+
+    const grade = async (doc: Document) => {
+      try {
+        const response = await llm.invoke(
+          LLM_RERANK_PROMPT.replace("{question}", query).replace(
+            "{document}",
+            doc.pageContent
+          )
+        )
+        const text = removeReasoning(response.content.toString())
+        const match = text.match(/\d+(\.\d+)?/)
+        if (!match) {
+          return 0
+        }
+        return Math.min(Math.max(parseFloat(match[0]), 0), 10) / 10
+      } catch (e) {
+        console.error("LLM rerank error", e)
+        return 0
+      }
+    }
+
+    const scores: number[] = []
+    for (let i = 0; i < docs.length; i += LLM_RERANK_CONCURRENCY) {
+      scores.push(
+        ...(await Promise.all(
+          docs.slice(i, i + LLM_RERANK_CONCURRENCY).map(grade)
+        ))
+      )
+    }
+    return scores
+  }
+}
+
+type RerankOptions = {
   query: string
   docs: Document[]
   embedding: EmbeddingsInterface
-}) => {
-  if (docs.length === 0) {
-    return docs
-  }
+  reranker?: Reranker
+  threshold?: number
+  topN?: number
+}
 
+/**
+ * Returns the documents scoring above `threshold`, best first, with their
+ * rerank scores.
+ */
+export const rerankDocsWithScores = async ({
+  query,
+  docs,
+  embedding,
+  reranker,
+  threshold = 0.5,
+  topN = 15
+}: RerankOptions): Promise<[Document, number][]> => {
   const docsWithContent = docs.filter(
     (doc) => doc.pageContent && doc.pageContent.length > 0
   )
+  if (docsWithContent.length === 0) {
+    return []
+  }
 
-  const [docEmbeddings, queryEmbedding] = await Promise.all([
-    embedding.embedDocuments(docsWithContent.map((doc) => doc.pageContent)),
-    embedding.embedQuery(query)
-  ])
+  const scores = await (reranker ?? new EmbeddingReranker(embedding)).score(
+    query,
+    docsWithContent
+  )
 
-  const similarity = docEmbeddings.map((docEmbedding, i) => {
-    // perform cosine similarity between query and document
-    const sim = ml_distance.similarity.cosine(queryEmbedding, docEmbedding)
+  return scores
+    .map((similarity, index) => ({ index, similarity }))
+    .sort((a, b) => b.similarity - a.similarity)
+    .filter((sim) => sim.similarity > threshold)
+    .slice(0, topN)
+    .map((sim) => [docsWithContent[sim.index], sim.similarity])
+}
 
-    return {
-      index: i,
-      similarity: sim
-    }
-  })
+export const rerankDocs = async (options: RerankOptions) => {
+  if (options.docs.length === 0) {
+    return options.docs
+  }
+  const results = await rerankDocsWithScores(options)
+  return results.map(([doc]) => doc)
+}
 
-  const sortedDocs = similarity
-    .sort((a, b) => b.similarity - a.similarity)
-    .filter((sim) => sim.similarity > 0.5)
-    .slice(0, 15)
-    .map((sim) => docsWithContent[sim.index])
+/**
+ * Returns the reranker configured in the RAG settings, or `null` when
+ * reranking is disabled.
+ */
+export const getPageAssistReranker = async ({
+  embedding,
+  selectedModel
+}: {
+  embedding: EmbeddingsInterface
+  selectedModel: string
+}) => {
+  const settings = await getRerankSettings()
 
-  return sortedDocs
+  let reranker: Reranker | null = null
+  switch (settings.type) {
+    case "embedding":
+      reranker = new EmbeddingReranker(embedding)
+      break
+    case "endpoint":
+      if (settings.baseUrl) {
+        reranker = new EndpointReranker({
+          baseUrl: settings.baseUrl,
+          model: settings.model,
+          apiKey: settings.apiKey
+        })
+      }
+      break
+    case "llm":
+      reranker = new LLMReranker(selectedModel)
+      break
+  }
+
+  return {
+    reranker,
+    threshold:
+      settings.type === "none" ? 0 : settings.thresholds[settings.type],
+    topN: settings.topN
+  }
 }
@@ -1,23 +1,593 @@
 import {
   RecursiveCharacterTextSplitter,
-  CharacterTextSplitter
+  CharacterTextSplitter,
+  TextSplitter,
+  type SupportedTextSplitterLanguage,
+  type TextSplitterParams
 } from "langchain/text_splitter"
+import { Document } from "@langchain/core/documents"
+import type { EmbeddingsInterface } from "@langchain/core/embeddings"
 
 import {
+  defaultChunkSizeUnit,
   defaultEmbeddingChunkOverlap,
   defaultEmbeddingChunkSize,
+  defaultSemanticBreakpointPercentile,
   defaultSsplttingSeparator,
   defaultSplittingStrategy
 } from "@/services/ollama"
+import { programmingLanguages } from "./langauge-extension"
+import { sha256 } from "./hash"
+import type { TiktokenEncoding, TiktokenModel } from "js-tiktoken"
 
-export const getPageAssistTextSplitter = async () => {
-  const chunkSize = await defaultEmbeddingChunkSize()
-  const chunkOverlap = await defaultEmbeddingChunkOverlap()
-  const splittingStrategy = await defaultSplittingStrategy()
+export const SPLITTING_STRATEGIES = [
+  "RecursiveCharacterTextSplitter",
+  "CharacterTextSplitter",
+  "MarkdownHeaderTextSplitter",
+  "CodeTextSplitter",
+  "SemanticTextSplitter"
+]
+
+// langchain only ships separators for some languages, the rest fall back
+// to the closest syntax family
+const SPLITTER_LANGUAGES: Partial<
+  Record<keyof typeof programmingLanguages, SupportedTextSplitterLanguage>
+> = {
+  html: "html",
+  vue: "html",
+  xml: "html",
+  javascript: "js",
+  typescript: "js",
+  jsx: "js",
+  tsx: "js",
+  python: "python",
+  java: "java",
+  kotlin: "java",
+  csharp: "java",
+  dart: "java",
+  cpp: "cpp",
+  c: "cpp",
+  ruby: "ruby",
+  php: "php",
+  swift: "swift",
+  go: "go",
+  rust: "rust",
+  markdown: "markdown"
+}
+
+export const getLanguageFromFilename = (
+  filename?: string
+): keyof typeof programmingLanguages | null => {
+  const extension = filename?.split(".").pop()?.toLowerCase()
+  if (!extension || extension === filename?.toLowerCase()) {
+    return null
+  }
+  if (extension === "markdown") {
+    return "markdown"
+  }
+  const entry = Object.entries(programmingLanguages).find(
+    ([, ext]) => ext === extension
+  )
+  return entry ? (entry[0] as keyof typeof programmingLanguages) : null
+}
+
+const tokenLengthFunctions = new Map<string, (text: string) => number>()
+
+/**
+ * Token counter used when chunk sizes are measured in tokens, with the
+ * tokenizer of the embedding model. Only the OpenAI tokenizers are bundled,
+ * models without one (e.g. those served by Ollama) are counted with
+ * cl100k_base as an approximation.
+ */
+const getTokenLengthFunction = async (model?: string) => {
+  const { getEncoding, getEncodingNameForModel } = await import("js-tiktoken")
+  let name: TiktokenEncoding = "cl100k_base"
+  try {
+    if (model) {
+      // OpenAI compatible providers may prefix the name, e.g. "openai/"
+      name = getEncodingNameForModel(model.split("/").pop() as TiktokenModel)
+    }
+  } catch (e) {
+    // not an OpenAI model
+  }
+  let lengthFunction = tokenLengthFunctions.get(name)
+  if (!lengthFunction) {
+    const encoding = getEncoding(name)
+    lengthFunction = (text: string) => encoding.encode(text).length
+    tokenLengthFunctions.set(name, lengthFunction)
+  }
+  return lengthFunction
+}
+
+type MarkdownBlock = {
+  text: string
+  headings: string[]
+  kind: "text" | "code" | "table"
+  language?: string
+}
+
+const parseMarkdownBlocks = (text: string): MarkdownBlock[] => {
+  const blocks: MarkdownBlock[] = []
+  let headings: string[] = []
+  let buffer: string[] = []
+  let kind: MarkdownBlock["kind"] = "text"
+  let language: string | undefined
+  let fence: string | null = null
+
+  const flush = () => {
+    const content = buffer.join("\n").trim()
+    if (content) {
+      blocks.push({
+        text: content,
+        headings: headings.filter(Boolean),
+        kind,
+        language
+      })
+    }
+    buffer = []
+    kind = "text"
+    language = undefined
+  }
+
+  for (const line of text.split("\n")) {
+    if (fence) {
+      buffer.push(line)
+      if (line.trim().startsWith(fence)) {
+        fence = null
+        flush()
+      }
+      continue
+    }
+
+    const fenceMatch = line.match(/^\s*(`{3,}|~{3,})\s*([\w+-]*)/)
+    if (fenceMatch) {
+      flush()
+      fence = fenceMatch[1]
+      kind = "code"
+      language = fenceMatch[2] || undefined
+      buffer.push(line)
+      continue
+    }
+
+    const headingMatch = line.match(/^(#{1,6})\s+(.*?)\s*#*\s*$/)
+    if (headingMatch) {
+      flush()
+      const level = headingMatch[1].length
+      headings = headings.slice(0, level - 1)
+      headings[level - 1] = headingMatch[2]
+      buffer.push(line)
+      continue
+    }
+
+    const isTableRow = line.trim().startsWith("|")
+    if (isTableRow && kind !== "table") {
+      flush()
+      kind = "table"
+    } else if (!isTableRow && kind === "table") {
+      flush()
+    }
+
+    if (kind === "text" && line.trim() === "") {
+      flush()
+      continue
+    }
+
+    buffer.push(line)
+  }
+  flush()
+
+  return blocks
+}
+
+type StructuredSplitterParams = TextSplitterParams & {
+  lengthFunction?: (text: string) => number
+}
+
+/**
+ * Splits Markdown along its structure. Headings, code blocks and tables are
+ * kept whole whenever they fit into a chunk, and every chunk carries the
+ * heading path it belongs to in `metadata.heading`.
+ */
+export class MarkdownHeaderTextSplitter extends TextSplitter {
+  lengthFunction: (text: string) => number
+
+  constructor(fields?: Partial<StructuredSplitterParams>) {
+    super(fields)
+    this.lengthFunction = fields?.lengthFunction ?? ((text) => text.length)
+  }
+
+  private fallbackSplitter(block: MarkdownBlock) {
+    const params = {
+      chunkSize: this.chunkSize,
+      chunkOverlap: this.chunkOverlap,
+      lengthFunction: this.lengthFunction
+    }
+    if (block.kind === "code" && block.language) {
+      // fence info strings are either a language name or an extension
+      const name =
+        block.language in programmingLanguages
+          ? (block.language as keyof typeof programmingLanguages)
+          : getLanguageFromFilename(`.${block.language}`)
+      const language = SPLITTER_LANGUAGES[name]
+      if (language) {
+        return RecursiveCharacterTextSplitter.fromLanguage(language, params)
+      }
+    }
+    return new RecursiveCharacterTextSplitter(params)
+  }
+
+  private async splitBlock(block: MarkdownBlock): Promise<string[]> {
+    if (this.lengthFunction(block.text) <= this.chunkSize) {
+      return [block.text]
+    }
+
+    if (block.kind === "table") {
+      // repeat the header row so every part of the table stays readable
+      const rows = block.text.split("\n")
+      const header = rows.slice(0, 2)
+      const parts: string[] = []
+      let current: string[] = [...header]
+      for (const row of rows.slice(2)) {
+        const candidate = [...current, row].join("\n")
+        if (
+          current.length > header.length &&
+          this.lengthFunction(candidate) > this.chunkSize
+        ) {
+          parts.push(current.join("\n"))
+          current = [...header]
+        }
+        current.push(row)
+      }
+      parts.push(current.join("\n"))
+      return parts
+    }
+
+    return this.fallbackSplitter(block).splitText(block.text)
+  }
+
+  async splitSections(
+    text: string
+  ): Promise<{ text: string; heading: string }[]> {
+    const chunks: { text: string; heading: string }[] = []
+    let current: string[] = []
+    let currentHeading = ""
+
+    const flush = () => {
+      if (current.length) {
+        chunks.push({ text: current.join("\n\n"), heading: currentHeading })
+      }
+      current = []
+    }
+
+    for (const block of parseMarkdownBlocks(text)) {
+      const heading = block.headings.join(" > ")
+      if (heading !== currentHeading) {
+        flush()
+        currentHeading = heading
+      }
+
+      for (const part of await this.splitBlock(block)) {
+        const candidate = [...current, part].join("\n\n")
+        if (current.length && this.lengthFunction(candidate) > this.chunkSize) {
+          flush()
+        }
+        current.push(part)
+      }
+    }
+    flush()
+
+    return chunks
+  }
+
+  async splitText(text: string): Promise<string[]> {
+    const sections = await this.splitSections(text)
+    return sections.map((section) => section.text)
+  }
+
+  async splitDocuments(documents: Document[]): Promise<Document[]> {
+    const result: Document[] = []
+    for (const document of documents) {
+      const sections = await this.splitSections(document.pageContent)
+      for (const section of sections) {
+        result.push(
+          new Document({
+            pageContent: section.text,
+            metadata: {
+              ...document.metadata,
+              ...(section.heading ? { heading: section.heading } : {})
+            }
+          })
+        )
+      }
+    }
+    return result
+  }
+}
+
+/**
+ * Picks separators based on the file extension of `metadata.source` so
+ * that functions and classes are not cut in half. Markdown files go through
+ * the header-aware splitter, unknown types through the recursive splitter.
+ */
+export class CodeTextSplitter extends TextSplitter {
+  lengthFunction: (text: string) => number
+
+  constructor(fields?: Partial<StructuredSplitterParams>) {
+    super(fields)
+    this.lengthFunction = fields?.lengthFunction ?? ((text) => text.length)
+  }
+
+  private splitterFor(language: keyof typeof programmingLanguages | null) {
+    const params = {
+      chunkSize: this.chunkSize,
+      chunkOverlap: this.chunkOverlap,
+      lengthFunction: this.lengthFunction
+    }
+    if (language === "markdown") {
+      return new MarkdownHeaderTextSplitter(params)
+    }
+    const splitterLanguage = SPLITTER_LANGUAGES[language]
+    if (splitterLanguage) {
+      return RecursiveCharacterTextSplitter.fromLanguage(
+        splitterLanguage,
+        params
+      )
+    }
+    return new RecursiveCharacterTextSplitter(params)
+  }
+
+  async splitText(text: string): Promise<string[]> {
+    return this.splitterFor(null).splitText(text)
+  }
+
+  async splitDocuments(documents: Document[]): Promise<Document[]> {
+    const result: Document[] = []
+    for (const document of documents) {
+      const language = getLanguageFromFilename(document.metadata?.source)
+      const chunks = await this.splitterFor(language).splitDocuments([
+        document
+      ])
+      result.push(
+        ...chunks.map((chunk) =>
+          language
+            ? new Document({
+                pageContent: chunk.pageContent,
+                metadata: { ...chunk.metadata, language }
+              })
+            : chunk
+        )
+      )
+    }
+    return result
+  }
+}
+
+const splitSentences = (text: string): string[] => {
+  // PDF pages come in as one text item per line, so single line breaks are
+  // folded into spaces and only blank lines are kept as hard boundaries
+  return text
+    .split(/\n\s*\n/)
+    .map((paragraph) => paragraph.replace(/\s*\n\s*/g, " ").trim())
+    .filter(Boolean)
+    .flatMap((paragraph) =>
+      paragraph
+        .split(/(?<=[.!?。！？])\s+/)
+        .map((sentence) => sentence.trim())
+        .filter(Boolean)
+    )
+}
+
+const cosineSimilarity = (a: number[], b: number[]) => {
+  let dot = 0
+  let normA = 0
+  let normB = 0
+  for (let i = 0; i < a.length; i++) {
+    dot += a[i] * b[i]
+    normA += a[i] * a[i]
+    normB += b[i] * b[i]
+  }
+  if (normA === 0 || normB === 0) {
+    return 0
+  }
+  return dot / (Math.sqrt(normA) * Math.sqrt(normB))
+}
+
+const percentile = (values: number[], p: number) => {
+  const sorted = [...values].sort((a, b) => a - b)
+  const rank = (p / 100) * (sorted.length - 1)
+  const lower = Math.floor(rank)
+  const upper = Math.ceil(rank)
+  return sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower)
+}
+
+type SemanticSplitterParams = StructuredSplitterParams & {
+  embeddings: EmbeddingsInterface
+  breakpointPercentile?: number
+  bufferSize?: number
+}
+
+/**
+ * Splits text where the topic changes instead of at a fixed size. Every
+ * sentence is embedded together with its neighbours and a new chunk starts
+ * wherever the similarity between two adjacent sentences falls below the
+ * given percentile of all adjacent similarities. Chunks that still exceed
+ * `chunkSize` are split further with the recursive splitter.
+ */
+export class SemanticTextSplitter extends TextSplitter {
+  lengthFunction: (text: string) => number
+  embeddings: EmbeddingsInterface
+  breakpointPercentile: number
+  bufferSize: number
+
+  constructor(fields: Partial<SemanticSplitterParams>) {
+    super(fields)
+    this.lengthFunction = fields?.lengthFunction ?? ((text) => text.length)
+    this.embeddings = fields.embeddings
+    this.breakpointPercentile = fields?.breakpointPercentile ?? 5
+    this.bufferSize = fields?.bufferSize ?? 1
+  }
+
+  private async groupSentences(sentences: string[]): Promise<string[]> {
+    if (sentences.length < 3) {
+      return [sentences.join(" ")]
+    }
+
+    const windows = sentences.map((_, i) =>
+      sentences
+        .slice(Math.max(0, i - this.bufferSize), i + this.bufferSize + 1)
+        .join(" ")
+    )
+    const embeddings = await this.embeddings.embedDocuments(windows)
+
+    const similarities: number[] = []
+    for (let i = 0; i < embeddings.length - 1; i++) {
+      similarities.push(cosineSimilarity(embeddings[i], embeddings[i + 1]))
+    }
+    const threshold = percentile(similarities, this.breakpointPercentile)
+
+    const groups: string[] = []
+    let current: string[] = [sentences[0]]
+    for (let i = 1; i < sentences.length; i++) {
+      if (similarities[i - 1] < threshold) {
+        groups.push(current.join(" "))
+        current = []
+      }
+      current.push(sentences[i])
+    }
+    groups.push(current.join(" "))
+
+    return groups
+  }
+
+  async splitText(text: string): Promise<string[]> {
+    const groups = await this.groupSentences(splitSentences(text))
+    const fallback = new RecursiveCharacterTextSplitter({
+      chunkSize: this.chunkSize,
+      chunkOverlap: this.chunkOverlap,
+      lengthFunction: this.lengthFunction
+    })
+
+    const chunks: string[] = []
+    for (const group of groups) {
+      if (!group) {
+        continue
+      }
+      if (this.lengthFunction(group) <= this.chunkSize) {
+        chunks.push(group)
+      } else {
+        chunks.push(...(await fallback.splitText(group)))
+      }
+    }
+    return chunks
+  }
+}
+
+/**
+ * Splits documents into parent passages of `parentChunkSize` and each
+ * passage into child chunks with `textSplitter`. Children are what gets
+ * embedded; they carry their passage in `parent_content` together with a
+ * `parent_id`, so retrieval can return the passage instead of the chunk.
+ * The vector store keeps each passage once, in its parent chunks.
+ */
+export const splitDocumentsWithParents = async (
+  documents: Document[],
+  textSplitter: TextSplitter,
+  parentChunkSize?: number
+): Promise<Document[]> => {
+  if (!parentChunkSize || parentChunkSize <= textSplitter.chunkSize) {
+    return textSplitter.splitDocuments(documents)
+  }
+
+  const parentSplitter = new RecursiveCharacterTextSplitter({
+    chunkSize: parentChunkSize,
+    chunkOverlap: 0,
+    lengthFunction: textSplitter.lengthFunction
+  })
+  const parents = await parentSplitter.splitDocuments(documents)
+
+  const result: Document[] = []
+  for (const parent of parents) {
+    const parentId = await sha256(parent.pageContent)
+    const children = await textSplitter.splitDocuments([parent])
+    for (const child of children) {
+      result.push(
+        new Document({
+          pageContent: child.pageContent,
+          metadata: {
+            ...child.metadata,
+            // locate citations by the passage, not the child chunk
+            loc: parent.metadata.loc,
+            parent_id: parentId,
+            parent_content: parent.pageContent
+          }
+        })
+      )
+    }
+  }
+  return result
+}
+
+export type SplitterSettings = {
+  chunkSize: number
+  chunkOverlap: number
+  splittingStrategy: string
+  chunkSizeUnit: string
+  splittingSeparator: string
+  semanticBreakpoint: number
+}
+
+// the settings that decide how sources are split into chunks
+export const getSplitterSettings = async (): Promise<SplitterSettings> => {
+  const [
+    chunkSize,
+    chunkOverlap,
+    splittingStrategy,
+    chunkSizeUnit,
+    splittingSeparator,
+    semanticBreakpoint
+  ] = await Promise.all([
+    defaultEmbeddingChunkSize(),
+    defaultEmbeddingChunkOverlap(),
+    defaultSplittingStrategy(),
+    defaultChunkSizeUnit(),
+    defaultSsplttingSeparator(),
+    defaultSemanticBreakpointPercentile()
+  ])
+  return {
+    chunkSize,
+    chunkOverlap,
+    splittingStrategy,
+    chunkSizeUnit,
+    splittingSeparator,
+    semanticBreakpoint
+  }
+}
+
+/**
+ * Returns the text splitter configured in the RAG settings. The semantic
+ * splitter needs an embedding model; callers that do not pass one get the
+ * recursive splitter instead.
+ */
+export const getPageAssistTextSplitter = async (
+  embeddings?: EmbeddingsInterface
+) => {
+  const {
+    chunkSize,
+    chunkOverlap,
+    splittingStrategy,
+    chunkSizeUnit,
+    splittingSeparator,
+    semanticBreakpoint
+  } = await getSplitterSettings()
+
+  // both the Ollama and the OpenAI embeddings keep the model name
+  const lengthFunction =
+    chunkSizeUnit === "tokens"
+      ? await getTokenLengthFunction((embeddings as { model?: string })?.model)
+      : undefined
 
   switch (splittingStrategy) {
     case "CharacterTextSplitter":
-      const splittingSeparator = await defaultSsplttingSeparator()
       const processedSeparator = splittingSeparator
         .replace(/\\n/g, "\n")
         .replace(/\\t/g, "\t")
@@ -25,12 +595,41 @@ export const getPageAssistTextSplitter = async () => {
       return new CharacterTextSplitter({
         chunkSize,
         chunkOverlap,
-        separator: processedSeparator
+        separator: processedSeparator,
+        lengthFunction
+      })
+    case "MarkdownHeaderTextSplitter":
+      return new MarkdownHeaderTextSplitter({
+        chunkSize,
+        chunkOverlap,
+        lengthFunction
+      })
+    case "CodeTextSplitter":
+      return new CodeTextSplitter({
+        chunkSize,
+        chunkOverlap,
+        lengthFunction
+      })
+    case "SemanticTextSplitter":
+      if (embeddings) {
+        return new SemanticTextSplitter({
+          chunkSize,
+          chunkOverlap,
+          lengthFunction,
+          embeddings,
+          breakpointPercentile: semanticBreakpoint
+        })
+      }
+      return new RecursiveCharacterTextSplitter({
+        chunkSize,
+        chunkOverlap,
+        lengthFunction
       })
     default:
       return new RecursiveCharacterTextSplitter({
         chunkSize,
-        chunkOverlap
+        chunkOverlap,
+        lengthFunction
       })
   }
 }
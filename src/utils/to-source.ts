@@ -1,5 +1,6 @@
 import { Source } from "@/db/knowledge"
 import { processSource } from "@/libs/process-source"
+import { getOfficeDocumentType, officeMimeTypes } from "@/loader/office"
 import { UploadFile } from "antd"
 
 export const toBase64 = (file: File | Blob): Promise<string> => {
@@ -59,11 +60,12 @@ export const convertFileToSource = async ({
     "text/csv",
     "text/plain",
     "text/markdown",
-    "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ...officeMimeTypes
   ]
   let type = mime || file.type
   if (!allowedTypes.includes(type)) {
-    type = "text/plain"
+    type = getOfficeDocumentType(file.name) || "text/plain"
   }
   let filename = file.name
   const url = await toBase64(file)
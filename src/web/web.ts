@@ -29,7 +29,7 @@ interface SearchProviderResult {
   results: ProviderResults[] | null
 }
 
-const getHostName = (url: string) => {
+export const getHostName = (url: string) => {
   try {
     return new URL(url).hostname
   } catch (e) {
@@ -38,7 +38,7 @@ const getHostName = (url: string) => {
   }
 }
 
-const searchWeb = (provider: string, query: string) => {
+export const searchWeb = (provider: string, query: string) => {
   switch (provider) {
     case "duckduckgo":
       return webDuckDuckGoSearch(query)
@@ -1,6 +1,7 @@
 import { defineConfig } from "wxt"
 import react from "@vitejs/plugin-react"
 import topLevelAwait from "vite-plugin-top-level-await"
+import { resolve } from "node:path"
 
 const chromeMV3Permissions = [
   "storage",
@@ -12,7 +13,9 @@ const chromeMV3Permissions = [
   "unlimitedStorage",
   "contextMenus",
   "tts",
-  "notifications"
+  "notifications",
+  "alarms",
+  "offscreen"
 ]
 
 const firefoxMV2Permissions = [
@@ -24,11 +27,21 @@ const firefoxMV2Permissions = [
   "webRequest",
   "webRequestBlocking",
   "notifications",
+  "alarms",
   "http://*/*",
   "https://*/*",
   "file://*/*"
 ]
 
+// Python runtime of the code interpreter, packages are loaded from the CDN
+// when package downloads are turned on
+const pyodideFiles = [
+  "pyodide.asm.js",
+  "pyodide.asm.wasm",
+  "pyodide-lock.json",
+  "python_stdlib.zip"
+]
+
 // See https://wxt.dev/api/config.html
 export default defineConfig({
   vite: () => ({
@@ -43,8 +56,24 @@ export default defineConfig({
       rollupOptions: {
         external: ["langchain", "@langchain/community"]
       }
+    },
+    optimizeDeps: {
+      exclude: ["pyodide"]
     }
   }),
+  hooks: {
+    "build:publicAssets": (_, assets) => {
+      if (process.env.TARGET === "firefox") {
+        return
+      }
+      for (const file of pyodideFiles) {
+        assets.push({
+          absoluteSrc: resolve("node_modules/pyodide", file),
+          relativeDest: `pyodide/${file}`
+        })
+      }
+    }
+  },
   entrypointsDir:
     process.env.TARGET === "firefox" ? "entries-firefox" : "entries",
   srcDir: "src",